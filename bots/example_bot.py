@@ -0,0 +1,46 @@
+#!/usr/bin/env python3
+"""Example bot for the external bot protocol (see src/external.rs).
+
+Raises pocket pairs and big cards, calls cheap bets with anything else and
+checks when it can. Add a difficulty tier to config/ai_profiles.json to play
+it from the game or the arena:
+
+    { "name": "Python", "strategy": "external",
+      "bot_command": ["python3", "bots/example_bot.py"], "bot_timeout_ms": 2000 }
+"""
+
+import json
+import sys
+
+RANKS = "23456789TJQKA"
+
+
+def choose(observation):
+    legal = observation["legal_actions"]
+    ranks = sorted((RANKS.index(card[0]) for card in observation["hole_cards"]), reverse=True)
+    pair = ranks[0] == ranks[1]
+    strong = pair or ranks[1] >= RANKS.index("T")
+
+    if strong:
+        for action in ("raise", "bet"):
+            if action in legal:
+                return action, "pair or two big cards"
+    if "check" in legal:
+        return "check", "free card"
+    if observation["to_call"] <= observation["big_blind"] and "call" in legal:
+        return "call", "cheap enough to see another card"
+    return "fold", "too expensive"
+
+
+def main():
+    for line in sys.stdin:
+        message = json.loads(line)
+        if message["type"] != "act":
+            continue
+        action, reason = choose(message)
+        reply = {"action": action, "id": message["id"], "reason": reason}
+        print(json.dumps(reply), flush=True)
+
+
+if __name__ == "__main__":
+    main()
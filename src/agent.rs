@@ -0,0 +1,1189 @@
+//! Pluggable decision makers for the seats at the table.
+//!
+//! Every seat is driven by a [`PokerAgent`]. The engine asks the agent for
+//! the seat to act, validates the answer against the legal actions, and
+//! applies it. New bots only need to implement the trait and be added to
+//! [`AgentKind`] so a difficulty tier can select them.
+
+use crate::cfr::{CfrAgent, StrategyTable};
+use crate::constants::*;
+use crate::external::ExternalAgent;
+use crate::game::{
+    choose_action_based_on_strength, evaluate_current_hand_strength, ActionRecord, GameConfig,
+    PlayerView, PokerAction, StrengthDecision,
+};
+use crate::icm::call_risk_premium;
+use crate::poker_logic::{PokerRound, SeatIndex};
+use crate::profile::{AiConfig, AiProfile, Difficulty};
+use crate::pushfold::{PushFoldAgent, PushFoldCharts};
+use crate::range::{range_equity, Range};
+use crate::stats::{is_aggressive, PlayerStats, StatsTracker};
+use crate::strategy::{mix_action, HandClass, MixedAction};
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// A bot that picks betting actions for one seat.
+pub trait PokerAgent: Send + Sync {
+    /// Short name used in logs and the UI.
+    fn name(&self) -> &str;
+
+    /// Chooses one of `actions` for the seat described by `view`.
+    ///
+    /// `actions` is never empty. Returning an action that is not in the list
+    /// is treated as a fold (or a check, when checking is free). Any
+    /// randomness must come from `rng` so seeded games are reproducible.
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        actions: &[PokerAction],
+        rng: &mut dyn RngCore,
+    ) -> PokerAction;
+
+    /// Called for every seat when a hand ends, with the hand's actions and
+    /// whether it reached showdown. Agents that learn across hands override
+    /// this.
+    fn observe_hand_end(&mut self, _history: &[ActionRecord], _went_to_showdown: bool) {}
+
+    /// Why the most recent [`choose_action`](Self::choose_action) picked
+    /// what it did, for agents that record it.
+    fn explain(&self) -> Option<Explanation> {
+        None
+    }
+}
+
+/// What an agent's decision was based on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecisionBasis {
+    /// The threshold rules of [`choose_action_based_on_strength`].
+    Strength(StrengthDecision),
+    /// Simulated equity against the price of continuing.
+    Equity { equity: f64, required: f64 },
+    /// Sampled from a precomputed table with `probability`.
+    Table { source: String, probability: f64 },
+    /// No estimate was possible, so the agent checked or folded.
+    Passive,
+    /// Answered by an external bot program, with the reason it gave.
+    External { reason: Option<String> },
+}
+
+/// A structured account of one decision: its basis, then every adjustment
+/// layered on top (mixing, mistakes, chart overrides) in the order applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub basis: DecisionBasis,
+    pub notes: Vec<String>,
+}
+
+impl Explanation {
+    pub fn new(basis: DecisionBasis) -> Self {
+        Self {
+            basis,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Records a mixing deviation, if `mixed` made one from `base`.
+    fn with_mix(mut self, base: PokerAction, mixed: MixedAction) -> Self {
+        if let Some(reason) = mixed.reason {
+            self.notes.push(format!(
+                "mixed {:?} into {:?} ({})",
+                base, mixed.action, reason
+            ));
+        }
+        self
+    }
+
+    /// The explanation as short lines for the on-screen panel.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = match &self.basis {
+            DecisionBasis::Strength(decision) => vec![
+                format!(
+                    "strength {:.2} = raw {:.2} + position {:+.2} + preflop {:+.2}",
+                    decision.final_strength,
+                    decision.raw_strength,
+                    decision.position_bonus,
+                    decision.preflop_adjustment
+                ),
+                format!("pot odds {:.2}", decision.pot_odds),
+                format!("rule: {}", decision.branch.description()),
+            ],
+            DecisionBasis::Equity { equity, required } if *required > 0.0 => vec![format!(
+                "equity {:.1}% vs {:.1}% needed to call",
+                equity * 100.0,
+                required * 100.0
+            )],
+            DecisionBasis::Equity { equity, .. } => vec![format!("equity {:.1}%", equity * 100.0)],
+            DecisionBasis::Table {
+                source,
+                probability,
+            } => vec![format!("{} ({:.0}%)", source, probability * 100.0)],
+            DecisionBasis::Passive => vec!["no estimate: check or fold".to_string()],
+            DecisionBasis::External {
+                reason: Some(reason),
+            } => vec![format!("bot: {}", reason)],
+            DecisionBasis::External { reason: None } => vec!["bot gave no reason".to_string()],
+        };
+        lines.extend(self.notes.iter().cloned());
+        lines
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.lines().join("; "))
+    }
+}
+
+/// The original rule-based bot: scores the hand with
+/// [`evaluate_current_hand_strength`], compares it against the profile's
+/// thresholds adjusted for position and pot odds, then mixes the result.
+#[derive(Debug, Default, Clone)]
+pub struct ThresholdAgent {
+    profile: AiProfile,
+    last: Option<Explanation>,
+}
+
+impl ThresholdAgent {
+    pub fn new(profile: AiProfile) -> Self {
+        Self {
+            profile,
+            last: None,
+        }
+    }
+}
+
+impl PokerAgent for ThresholdAgent {
+    fn name(&self) -> &str {
+        "Threshold"
+    }
+
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        actions: &[PokerAction],
+        rng: &mut dyn RngCore,
+    ) -> PokerAction {
+        let strength = evaluate_current_hand_strength(view);
+        let decision = choose_action_based_on_strength(actions, strength, view, &self.profile);
+        let base = decision.action;
+        let class = if !view.board.is_empty() && strength >= AI_MONSTER_STRENGTH {
+            HandClass::Monster
+        } else if strength >= self.profile.call_threshold {
+            HandClass::Made
+        } else {
+            HandClass::Weak
+        };
+        let mixed = mix_action(base, class, view, actions, &self.profile, rng);
+        self.last = Some(Explanation::new(DecisionBasis::Strength(decision)).with_mix(base, mixed));
+        mixed.action
+    }
+
+    fn explain(&self) -> Option<Explanation> {
+        self.last.clone()
+    }
+}
+
+/// A bot that simulates its equity against the range the opponent is
+/// likely to hold and weighs it against the price of continuing.
+///
+/// The assumed range narrows each time the opponent bets or raises. Calls
+/// are made when the equity beats the pot odds, with the pot credited for
+/// part of what could still be won on later streets (implied odds). In a
+/// tournament a call also has to cover the ICM risk premium, which makes
+/// the agent tighter near the bubble. The result is mixed like
+/// [`ThresholdAgent`]'s.
+pub struct EquityAgent {
+    profile: AiProfile,
+    ranges: [Range; 3],
+    samples: u32,
+    last: Option<Explanation>,
+}
+
+impl EquityAgent {
+    /// Creates an agent that simulates `samples` runouts per decision.
+    pub fn new(profile: AiProfile, samples: u32) -> Self {
+        let parse = |notation: &str| {
+            notation
+                .parse::<Range>()
+                .expect("built-in opponent ranges are valid")
+        };
+        Self {
+            profile,
+            ranges: [
+                parse(AI_EQUITY_PASSIVE_RANGE),
+                parse(AI_EQUITY_AGGRESSIVE_RANGE),
+                parse(AI_EQUITY_VERY_AGGRESSIVE_RANGE),
+            ],
+            samples,
+            last: None,
+        }
+    }
+
+    /// The range the opponent is assumed to hold given their aggression so far.
+    fn opponent_range(&self, view: &PlayerView, opponent: SeatIndex) -> &Range {
+        let aggressive_actions = view
+            .history
+            .iter()
+            .filter(|record| record.seat == opponent)
+            .filter(|record| {
+                matches!(
+                    record.action,
+                    PokerAction::Bet | PokerAction::Raise | PokerAction::AllIn
+                )
+            })
+            .count();
+        &self.ranges[aggressive_actions.min(self.ranges.len() - 1)]
+    }
+
+    /// Estimates this seat's share of the pot at showdown, or `None` when the
+    /// hand cannot be simulated (for example every opponent combo is blocked).
+    fn estimate_equity(&self, view: &PlayerView, rng: &mut dyn RngCore) -> Option<f64> {
+        let ranges: Vec<Range> = std::iter::once(Range::from_hand(view.hole_cards))
+            .chain(
+                view.opponents()
+                    .map(|opponent| self.opponent_range(view, opponent).clone()),
+            )
+            .collect();
+        range_equity(&ranges, &view.board, &[], self.samples, rng)
+            .ok()
+            .map(|result| result.equities[0].share())
+    }
+
+    /// Equity this agent wants before calling: the price with implied odds,
+    /// plus the ICM premium in a tournament, less the profile's margin.
+    fn required_to_call(&self, view: &PlayerView) -> f64 {
+        required_equity(view, self.profile.implied_odds_factor) + icm_premium(view)
+            - self.profile.call_margin
+    }
+
+    /// The unmixed action for a hand with `equity`.
+    fn base_action(&self, view: &PlayerView, actions: &[PokerAction], equity: f64) -> PokerAction {
+        let find = |wanted: PokerAction| actions.iter().copied().find(|&a| a == wanted);
+        let passive = passive_action(actions);
+
+        if view.to_call() == 0 {
+            if equity >= self.profile.equity_bet_threshold {
+                return find(PokerAction::Bet).unwrap_or(passive);
+            }
+            return passive;
+        }
+        if equity >= self.profile.equity_raise_threshold {
+            if let Some(raise) = find(PokerAction::Raise) {
+                return raise;
+            }
+        }
+        if equity >= self.required_to_call(view) {
+            return find(PokerAction::Call).unwrap_or(passive);
+        }
+        passive
+    }
+}
+
+/// Check when it is free, otherwise fold.
+fn passive_action(actions: &[PokerAction]) -> PokerAction {
+    [PokerAction::Check, PokerAction::Fold]
+        .into_iter()
+        .find(|action| actions.contains(action))
+        .unwrap_or(actions[0])
+}
+
+impl Default for EquityAgent {
+    fn default() -> Self {
+        Self::new(AiProfile::default(), AI_EQUITY_SAMPLES)
+    }
+}
+
+/// Equity needed to call `to_call` profitably, crediting the pot with
+/// `implied_odds_factor` of each later street's call amount.
+fn required_equity(view: &PlayerView, implied_odds_factor: f64) -> f64 {
+    let to_call = f64::from(view.to_call());
+    if to_call == 0.0 {
+        return 0.0;
+    }
+    let streets_left = match view.round {
+        PokerRound::PreFlop => 3.0,
+        PokerRound::Flop => 2.0,
+        PokerRound::Turn => 1.0,
+        PokerRound::River | PokerRound::Showdown => 0.0,
+    };
+    let opponent_stack = view
+        .opponents()
+        .map(|opponent| view.stacks[opponent])
+        .max()
+        .unwrap_or(0);
+    let implied = (streets_left * to_call * implied_odds_factor).min(f64::from(opponent_stack));
+    to_call / (f64::from(view.pot) + to_call + implied)
+}
+
+/// Extra equity tournament payouts ask for on top of the pot odds, against
+/// the opponent who has bet the most; zero outside a tournament.
+fn icm_premium(view: &PlayerView) -> f64 {
+    if view.payouts.is_empty() {
+        return 0.0;
+    }
+    let Some(opponent) = view.opponents().max_by_key(|&opponent| view.bets[opponent]) else {
+        return 0.0;
+    };
+    call_risk_premium(
+        &view.stacks,
+        view.seat,
+        opponent,
+        view.pot,
+        view.to_call(),
+        &view.payouts,
+    )
+}
+
+impl PokerAgent for EquityAgent {
+    fn name(&self) -> &str {
+        "Equity"
+    }
+
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        actions: &[PokerAction],
+        rng: &mut dyn RngCore,
+    ) -> PokerAction {
+        let Some(equity) = self.estimate_equity(view, rng) else {
+            self.last = Some(Explanation::new(DecisionBasis::Passive));
+            return passive_action(actions);
+        };
+        let base = self.base_action(view, actions, equity);
+        let class = if equity >= AI_MONSTER_EQUITY {
+            HandClass::Monster
+        } else if equity >= self.profile.equity_bet_threshold {
+            HandClass::Made
+        } else {
+            HandClass::Weak
+        };
+        let mixed = mix_action(base, class, view, actions, &self.profile, rng);
+        let required = if view.to_call() > 0 {
+            self.required_to_call(view)
+        } else {
+            0.0
+        };
+        let basis = DecisionBasis::Equity { equity, required };
+        self.last = Some(Explanation::new(basis).with_mix(base, mixed));
+        mixed.action
+    }
+
+    fn explain(&self) -> Option<Explanation> {
+        self.last.clone()
+    }
+}
+
+/// Wraps another agent and, with probability `rate`, swaps its decision for
+/// a random legal action. Used to make the lower difficulty tiers beatable.
+/// Moving all-in is never picked as a mistake, so a blunder costs a bet
+/// rather than the whole stack.
+pub struct MistakeAgent {
+    inner: Box<dyn PokerAgent>,
+    rate: f64,
+    /// The inner agent's choice and its replacement, if the last decision
+    /// was a mistake.
+    last_mistake: Option<(PokerAction, PokerAction)>,
+}
+
+impl MistakeAgent {
+    pub fn new(inner: Box<dyn PokerAgent>, rate: f64) -> Self {
+        Self {
+            inner,
+            rate: rate.clamp(0.0, 1.0),
+            last_mistake: None,
+        }
+    }
+}
+
+impl PokerAgent for MistakeAgent {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        actions: &[PokerAction],
+        rng: &mut dyn RngCore,
+    ) -> PokerAction {
+        let action = self.inner.choose_action(view, actions, rng);
+        self.last_mistake = None;
+        if rng.gen_bool(self.rate) {
+            let blunders: Vec<PokerAction> = actions
+                .iter()
+                .copied()
+                .filter(|&a| a != PokerAction::AllIn)
+                .collect();
+            let blunder = blunders.choose(rng).copied().unwrap_or(action);
+            self.last_mistake = (blunder != action).then_some((action, blunder));
+            return blunder;
+        }
+        action
+    }
+
+    fn observe_hand_end(&mut self, history: &[ActionRecord], went_to_showdown: bool) {
+        self.inner.observe_hand_end(history, went_to_showdown);
+    }
+
+    fn explain(&self) -> Option<Explanation> {
+        let mut explanation = self.inner.explain()?;
+        if let Some((intended, blunder)) = self.last_mistake {
+            explanation.notes.push(format!(
+                "mistake: random {:?} instead of {:?}",
+                blunder, intended
+            ));
+        }
+        Some(explanation)
+    }
+}
+
+/// An equity bot that tracks its opponent's statistics across hands and
+/// shifts its calling and bluffing thresholds to exploit them: it calls
+/// loose or aggressive players lighter, bluffs players who fold to c-bets,
+/// and value-bets thinner against players who go to showdown too often.
+pub struct AdaptiveAgent {
+    base: AiProfile,
+    inner: EquityAgent,
+    tracker: StatsTracker,
+}
+
+impl AdaptiveAgent {
+    pub fn new(profile: AiProfile, samples: u32) -> Self {
+        Self {
+            base: profile.clone(),
+            inner: EquityAgent::new(profile, samples),
+            tracker: StatsTracker::new(MAX_SEATS),
+        }
+    }
+}
+
+/// Shifts `base` in response to `opponent`'s statistics. Each adjustment is
+/// scaled by how many hands have been seen, reaching full strength after
+/// `AI_ADAPT_FULL_HANDS`.
+pub fn adapt_profile(base: &AiProfile, opponent: &PlayerStats) -> AiProfile {
+    let confidence = (f64::from(opponent.hands) / f64::from(AI_ADAPT_FULL_HANDS)).min(1.0);
+    let step = AI_ADAPT_STEP * confidence;
+    let mut profile = base.clone();
+
+    // Loose or aggressive opponents bet and call with weaker hands
+    if let Some(vpip) = opponent.vpip() {
+        if vpip > AI_ADAPT_LOOSE_VPIP {
+            profile.call_margin += step;
+        } else if vpip < AI_ADAPT_TIGHT_VPIP {
+            profile.call_margin -= step;
+        }
+    }
+    let aggression = opponent.aggression_factor().or(
+        // Never called post-flop yet: aggressive if they have bet at all
+        (opponent.postflop_aggressive > 0).then_some(f64::INFINITY),
+    );
+    if let Some(af) = aggression {
+        if af > AI_ADAPT_AGGRESSIVE_AF {
+            profile.call_margin += step;
+        } else if af < AI_ADAPT_PASSIVE_AF {
+            profile.call_margin -= step;
+        }
+    }
+    // Frequent raisers and re-raisers do it with lighter hands
+    let raises_light = opponent.pfr().is_some_and(|rate| rate > AI_ADAPT_HIGH_PFR)
+        || opponent
+            .three_bet()
+            .is_some_and(|rate| rate > AI_ADAPT_HIGH_THREE_BET);
+    if raises_light {
+        profile.call_margin += step;
+    }
+
+    // Bluff players who give up, value-bet players who do not
+    if let Some(fold_rate) = opponent.fold_to_cbet() {
+        let shift = (fold_rate - 0.5) * 2.0 * confidence;
+        profile.bluff_frequency = (profile.bluff_frequency * (1.0 + shift)).clamp(0.0, 1.0);
+        profile.semi_bluff_frequency =
+            (profile.semi_bluff_frequency * (1.0 + shift)).clamp(0.0, 1.0);
+    }
+    if opponent
+        .went_to_showdown()
+        .is_some_and(|wtsd| wtsd > AI_ADAPT_STATION_WTSD)
+    {
+        profile.bluff_frequency *= 1.0 - confidence;
+        profile.equity_bet_threshold -= step;
+    }
+    profile
+}
+
+impl PokerAgent for AdaptiveAgent {
+    fn name(&self) -> &str {
+        "Adaptive"
+    }
+
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        actions: &[PokerAction],
+        rng: &mut dyn RngCore,
+    ) -> PokerAction {
+        // Adapt to whoever last bet into us, or else the next player along
+        let aggressor = view
+            .history
+            .iter()
+            .rev()
+            .filter(|record| record.seat != view.seat && is_aggressive(record.action))
+            .map(|record| record.seat)
+            .next();
+        let target = aggressor
+            .or_else(|| view.opponents().find(|&seat| seat > view.seat))
+            .or_else(|| view.opponents().next())
+            .unwrap_or(view.seat);
+        let opponent = self.tracker.player(target);
+        self.inner.profile = adapt_profile(&self.base, opponent);
+        self.inner.choose_action(view, actions, rng)
+    }
+
+    fn observe_hand_end(&mut self, history: &[ActionRecord], went_to_showdown: bool) {
+        self.tracker.observe_hand(history, went_to_showdown);
+    }
+
+    fn explain(&self) -> Option<Explanation> {
+        let mut explanation = self.inner.explain()?;
+        let (base, adapted) = (&self.base, &self.inner.profile);
+        explanation.notes.push(format!(
+            "adapted: call margin {:+.3}, bluff frequency {:.2} (base {:.2})",
+            adapted.call_margin - base.call_margin,
+            adapted.bluff_frequency,
+            base.bluff_frequency
+        ));
+        Some(explanation)
+    }
+}
+
+/// Selects which built-in strategy a difficulty tier plays.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentKind {
+    #[default]
+    Threshold,
+    Equity,
+    Adaptive,
+    /// Plays a strategy table trained by the CFR solver.
+    Cfr,
+    /// Asks an external program; see [`crate::external`].
+    External,
+}
+
+/// Creates a fresh agent that plays `profile` at `difficulty`.
+pub fn build_agent(profile: &AiProfile, difficulty: &Difficulty) -> Box<dyn PokerAgent> {
+    let agent: Box<dyn PokerAgent> = match difficulty.strategy {
+        AgentKind::Threshold => Box::new(ThresholdAgent::new(profile.clone())),
+        AgentKind::Equity => Box::new(EquityAgent::new(profile.clone(), difficulty.equity_samples)),
+        AgentKind::Adaptive => Box::new(AdaptiveAgent::new(
+            profile.clone(),
+            difficulty.equity_samples,
+        )),
+        AgentKind::Cfr => match StrategyTable::load(&difficulty.strategy_file) {
+            Ok(table) => Box::new(CfrAgent::new(table, profile.clone())),
+            Err(err) => {
+                warn!(
+                    "{} ({}); playing the threshold strategy",
+                    err, difficulty.strategy_file
+                );
+                Box::new(ThresholdAgent::new(profile.clone()))
+            }
+        },
+        AgentKind::External => {
+            let timeout = Duration::from_millis(difficulty.bot_timeout_ms);
+            match ExternalAgent::spawn(difficulty.bot_command.clone(), timeout) {
+                Ok(agent) => Box::new(agent),
+                Err(err) => {
+                    warn!(
+                        "cannot start bot {:?} ({}); playing the threshold strategy",
+                        difficulty.bot_command, err
+                    );
+                    Box::new(ThresholdAgent::new(profile.clone()))
+                }
+            }
+        }
+    };
+    let agent = if difficulty.push_fold_below_bb > 0.0 {
+        match PushFoldCharts::load(&difficulty.push_fold_file) {
+            Ok(charts) => Box::new(PushFoldAgent::new(
+                agent,
+                charts,
+                difficulty.push_fold_below_bb,
+            )),
+            Err(err) => {
+                warn!(
+                    "{} ({}); playing without push/fold charts",
+                    err, difficulty.push_fold_file
+                );
+                agent
+            }
+        }
+    } else {
+        agent
+    };
+    if difficulty.mistake_rate > 0.0 {
+        Box::new(MistakeAgent::new(agent, difficulty.mistake_rate))
+    } else {
+        agent
+    }
+}
+
+/// The agents currently seated at the table, indexed by seat.
+#[derive(Resource)]
+pub struct SeatAgents {
+    agents: Vec<Box<dyn PokerAgent>>,
+}
+
+impl SeatAgents {
+    /// Builds one agent per seat from the profiles and difficulties chosen
+    /// in `config`.
+    pub fn from_config(config: &GameConfig, ai_config: &AiConfig) -> Self {
+        Self {
+            agents: (0..config.seats.len())
+                .map(|seat| Self::build_seat(config, ai_config, seat))
+                .collect(),
+        }
+    }
+
+    /// Builds the agent configured for `seat`. Unknown profile or difficulty
+    /// names fall back to the defaults.
+    pub fn build_seat(
+        config: &GameConfig,
+        ai_config: &AiConfig,
+        seat: usize,
+    ) -> Box<dyn PokerAgent> {
+        let setup = &config.seats[seat];
+        let profile = ai_config
+            .profile(&setup.profile)
+            .cloned()
+            .unwrap_or_else(|| {
+                warn!("Unknown AI profile '{}' for P{}", setup.profile, seat + 1);
+                AiProfile::default()
+            });
+        let difficulty = ai_config
+            .difficulty(&setup.difficulty)
+            .cloned()
+            .unwrap_or_else(|| {
+                warn!(
+                    "Unknown difficulty '{}' for P{}",
+                    setup.difficulty,
+                    seat + 1
+                );
+                Difficulty::default()
+            });
+        build_agent(&profile, &difficulty)
+    }
+
+    /// Replaces the agent in `seat`.
+    pub fn set(&mut self, seat: usize, agent: Box<dyn PokerAgent>) {
+        self.agents[seat] = agent;
+    }
+
+    /// Tells every seat's agent that the hand is over.
+    pub fn observe_hand_end(&mut self, history: &[ActionRecord], went_to_showdown: bool) {
+        for agent in &mut self.agents {
+            agent.observe_hand_end(history, went_to_showdown);
+        }
+    }
+
+    /// Returns the agent in `seat`.
+    pub fn seat(&self, seat: usize) -> &dyn PokerAgent {
+        self.agents[seat].as_ref()
+    }
+
+    /// Returns the agent in `seat` for deciding.
+    pub fn seat_mut(&mut self, seat: usize) -> &mut dyn PokerAgent {
+        self.agents[seat].as_mut()
+    }
+}
+
+impl FromWorld for SeatAgents {
+    fn from_world(world: &mut World) -> Self {
+        Self::from_config(world.resource::<GameConfig>(), world.resource::<AiConfig>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        apply_action, get_valid_actions, perform_validated_action, post_blinds, GameStateResource,
+        StrengthBranch,
+    };
+    use crate::poker_logic::parse_cards;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Always answers with the same action, legal or not.
+    struct FixedAgent(PokerAction);
+
+    impl PokerAgent for FixedAgent {
+        fn name(&self) -> &str {
+            "Fixed"
+        }
+
+        fn choose_action(
+            &mut self,
+            _: &PlayerView,
+            _: &[PokerAction],
+            _: &mut dyn RngCore,
+        ) -> PokerAction {
+            self.0
+        }
+    }
+
+    /// Checks and remembers the last view it was shown.
+    #[derive(Default)]
+    struct RecordingAgent(Option<PlayerView>);
+
+    impl PokerAgent for RecordingAgent {
+        fn name(&self) -> &str {
+            "Recording"
+        }
+
+        fn choose_action(
+            &mut self,
+            view: &PlayerView,
+            _: &[PokerAction],
+            _: &mut dyn RngCore,
+        ) -> PokerAction {
+            self.0 = Some(view.clone());
+            PokerAction::Check
+        }
+    }
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(33)
+    }
+
+    fn new_hand() -> GameStateResource {
+        GameStateResource::with_stacks(vec![1000; HEADS_UP_SEATS])
+    }
+
+    #[test]
+    fn test_default_config_seats_agents_from_config() {
+        let mut agents = SeatAgents::from_config(&GameConfig::default(), &AiConfig::built_in());
+        assert_eq!(agents.seat_mut(0).name(), "Threshold");
+        assert_eq!(agents.seat_mut(1).name(), "Equity");
+    }
+
+    #[test]
+    fn test_agent_action_is_applied() {
+        let config = GameConfig::default();
+        let mut agents = SeatAgents::from_config(&config, &AiConfig::built_in());
+        agents.set(0, Box::new(FixedAgent(PokerAction::Bet)));
+        let mut game_state = new_hand();
+
+        perform_validated_action(&mut game_state, &config, agents.seat_mut(0), &mut rng());
+
+        assert_eq!(game_state.pot, config.bet_amount);
+        assert_eq!(game_state.current_bet, config.bet_amount);
+        assert_eq!(game_state.current_player, 1);
+    }
+
+    #[test]
+    fn test_illegal_action_falls_back_to_check() {
+        let config = GameConfig::default();
+        let mut game_state = new_hand();
+
+        perform_validated_action(
+            &mut game_state,
+            &config,
+            &mut FixedAgent(PokerAction::Call),
+            &mut rng(),
+        );
+
+        assert_eq!(game_state.pot, 0);
+        assert_eq!(game_state.winner, None);
+        assert_eq!(game_state.last_action, "P1: Check");
+    }
+
+    #[test]
+    fn test_illegal_action_facing_bet_folds() {
+        let config = GameConfig::default();
+        let mut game_state = new_hand();
+        game_state.current_bet = config.bet_amount;
+
+        perform_validated_action(
+            &mut game_state,
+            &config,
+            &mut FixedAgent(PokerAction::Bet),
+            &mut rng(),
+        );
+
+        assert_eq!(game_state.winner, Some(1));
+        assert_eq!(game_state.current_round, PokerRound::Showdown);
+    }
+
+    #[test]
+    fn test_view_shows_only_own_cards_and_revealed_board() {
+        let config = GameConfig::default();
+        let mut game_state = new_hand();
+        let cards = parse_cards("AhKh QsQc 2d 7c 9s Td 3h").unwrap();
+        game_state.hole_cards[0] = [cards[0], cards[1]];
+        game_state.hole_cards[1] = [cards[2], cards[3]];
+        game_state.community_cards.copy_from_slice(&cards[4..]);
+        game_state.current_round = PokerRound::Flop;
+        game_state.current_player = 1;
+
+        let mut agent = RecordingAgent::default();
+        perform_validated_action(&mut game_state, &config, &mut agent, &mut rng());
+
+        let view = agent.0.expect("agent was asked to act");
+        assert_eq!(view.seat, 1);
+        assert_eq!(view.hole_cards, game_state.hole_cards[1]);
+        assert_eq!(view.board, cards[4..7]);
+    }
+
+    #[test]
+    fn test_actions_are_recorded_in_history() {
+        let config = GameConfig::default();
+        let mut game_state = new_hand();
+
+        perform_validated_action(
+            &mut game_state,
+            &config,
+            &mut FixedAgent(PokerAction::Bet),
+            &mut rng(),
+        );
+        let mut agent = RecordingAgent::default();
+        perform_validated_action(&mut game_state, &config, &mut agent, &mut rng());
+
+        let expected = ActionRecord {
+            seat: 0,
+            round: PokerRound::PreFlop,
+            action: PokerAction::Bet,
+            amount: config.bet_amount,
+        };
+        assert_eq!(agent.0.unwrap().history, vec![expected]);
+        assert_eq!(game_state.action_history.len(), 2);
+    }
+
+    /// A view for seat 0 holding `hole` on `board`, facing `to_call` into `pot`.
+    fn view(hole: &str, board: &str, pot: u32, to_call: u32) -> PlayerView {
+        let hole = parse_cards(hole).unwrap();
+        let board = if board.is_empty() {
+            Vec::new()
+        } else {
+            parse_cards(board).unwrap()
+        };
+        let round = match board.len() {
+            0 => PokerRound::PreFlop,
+            3 => PokerRound::Flop,
+            4 => PokerRound::Turn,
+            _ => PokerRound::River,
+        };
+        PlayerView {
+            seat: 0,
+            hole_cards: [hole[0], hole[1]],
+            board,
+            round,
+            hand_number: 1,
+            dealer_position: 0,
+            stacks: vec![1000; HEADS_UP_SEATS],
+            bets: vec![0, to_call],
+            folded: vec![false; HEADS_UP_SEATS],
+            current_bet: to_call,
+            pot,
+            big_blind: 50,
+            bet_amount: 50,
+            raise_amount: 100,
+            history: Vec::new(),
+            payouts: Vec::new(),
+        }
+    }
+
+    /// The default profile with every mixing frequency at zero.
+    fn pure_profile() -> AiProfile {
+        AiProfile {
+            bluff_frequency: 0.0,
+            semi_bluff_frequency: 0.0,
+            slow_play_frequency: 0.0,
+            check_raise_frequency: 0.0,
+            ..AiProfile::default()
+        }
+    }
+
+    /// An equity agent that never mixes. Its runouts are drawn from the
+    /// RNG each decision is given, so with [`rng`] its choices repeat.
+    fn pure_equity_agent() -> EquityAgent {
+        EquityAgent::new(pure_profile(), AI_EQUITY_SAMPLES)
+    }
+
+    const FACING_RAISE: [PokerAction; 3] =
+        [PokerAction::Call, PokerAction::Raise, PokerAction::Fold];
+
+    #[test]
+    fn test_equity_agent_raises_the_nuts() {
+        let mut agent = pure_equity_agent();
+        let view = view("AhKh", "Qh Jh Th 2c 3d", 200, 100);
+        assert_eq!(
+            agent.choose_action(&view, &FACING_RAISE, &mut rng()),
+            PokerAction::Raise
+        );
+    }
+
+    #[test]
+    fn test_equity_agent_folds_air_on_the_river() {
+        let mut agent = pure_equity_agent();
+        let view = view("7c2d", "As Kd Qh 9s 4c", 100, 100);
+        assert_eq!(
+            agent.choose_action(&view, &FACING_RAISE, &mut rng()),
+            PokerAction::Fold
+        );
+    }
+
+    #[test]
+    fn test_equity_agent_calls_a_cheap_draw() {
+        let mut agent = pure_equity_agent();
+        let view = view("9h8h", "Ah 7h 2c", 400, 50);
+        assert_eq!(
+            agent.choose_action(&view, &FACING_RAISE, &mut rng()),
+            PokerAction::Call
+        );
+    }
+
+    #[test]
+    fn test_equity_agent_checks_weak_hands_when_free() {
+        let mut agent = pure_equity_agent();
+        let view = view("7c2d", "As Kd Qh", 100, 0);
+        let actions = [PokerAction::Check, PokerAction::Bet, PokerAction::Fold];
+        assert_eq!(
+            agent.choose_action(&view, &actions, &mut rng()),
+            PokerAction::Check
+        );
+    }
+
+    #[test]
+    fn test_implied_odds_lower_the_price_on_early_streets() {
+        let flop = view("9h8h", "Ah 7h 2c", 100, 100);
+        let river = view("9h8h", "Ah 7h 2c 3d 4s", 100, 100);
+        let factor = AI_IMPLIED_ODDS_FACTOR;
+        assert!((required_equity(&river, factor) - 0.5).abs() < 1e-9);
+        assert!(required_equity(&flop, factor) < required_equity(&river, factor));
+        assert_eq!(required_equity(&flop, 0.0), required_equity(&river, 0.0));
+    }
+
+    #[test]
+    fn test_equity_agent_wants_more_to_call_off_on_the_bubble() {
+        let agent = pure_equity_agent();
+        // Seat 0 faces the chip leader's shove with a short stack yet to bust
+        let mut view = view("AhQd", "", 3000, 3000);
+        view.stacks = vec![3000, 3000, 600, 2400];
+        view.bets = vec![0, 3000, 0, 0];
+        view.folded = vec![false, false, true, true];
+        let cash_price = agent.required_to_call(&view);
+
+        view.payouts = vec![0.5, 0.3, 0.2];
+        assert!(agent.required_to_call(&view) > cash_price + 0.05);
+        view.payouts = vec![1.0];
+        assert!((agent.required_to_call(&view) - cash_price).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_opponent_range_narrows_with_aggression() {
+        let agent = pure_equity_agent();
+        let mut view = view("AhKh", "", 0, 0);
+        let passive = agent.opponent_range(&view, 1).combos().len();
+        view.history.push(ActionRecord {
+            seat: 1,
+            round: PokerRound::PreFlop,
+            action: PokerAction::Raise,
+            amount: 100,
+        });
+        assert!(agent.opponent_range(&view, 1).combos().len() < passive);
+    }
+
+    #[test]
+    fn test_profiles_change_threshold_decisions() {
+        let ai_config = AiConfig::built_in();
+        let view = view("7c2d", "", 100, 100);
+        let mut maniac = ThresholdAgent::new(ai_config.profile("Maniac").unwrap().clone());
+        let mut rock = ThresholdAgent::new(ai_config.profile("Rock").unwrap().clone());
+        assert_eq!(
+            maniac.choose_action(&view, &FACING_RAISE, &mut rng()),
+            PokerAction::Raise
+        );
+        assert_eq!(
+            rock.choose_action(&view, &FACING_RAISE, &mut rng()),
+            PokerAction::Fold
+        );
+    }
+
+    #[test]
+    fn test_threshold_fold_explains_its_inputs() {
+        let rock = AiConfig::built_in().profile("Rock").unwrap().clone();
+        let mut agent = ThresholdAgent::new(rock.clone());
+        let view = view("7c2d", "", 100, 100);
+        assert_eq!(
+            agent.choose_action(&view, &FACING_RAISE, &mut rng()),
+            PokerAction::Fold
+        );
+        let explanation = agent.explain().unwrap();
+        let DecisionBasis::Strength(decision) = explanation.basis else {
+            panic!("expected a strength basis, got {:?}", explanation.basis);
+        };
+        assert_eq!(decision.action, PokerAction::Fold);
+        assert!(matches!(
+            decision.branch,
+            StrengthBranch::FoldWeak | StrengthBranch::FoldBadOdds
+        ));
+        assert_eq!(decision.raw_strength, evaluate_current_hand_strength(&view));
+        assert_eq!(decision.position_bonus, rock.position_bonus);
+        assert_eq!(decision.preflop_adjustment, rock.preflop_dealer_bonus);
+        assert!((decision.pot_odds - 0.5).abs() < 1e-6);
+        let expected = (decision.raw_strength + decision.position_bonus).min(1.0)
+            + decision.preflop_adjustment;
+        assert!((decision.final_strength - expected.clamp(0.0, 1.0)).abs() < 1e-6);
+        assert!(explanation.to_string().contains("rule: fold"));
+    }
+
+    #[test]
+    fn test_decisions_are_logged_with_explanations() {
+        let config = GameConfig::default();
+        let mut game_state = new_hand();
+        post_blinds(&mut game_state, &config);
+        let mut rng = rng();
+        let mut agent = ThresholdAgent::new(pure_profile());
+        perform_validated_action(&mut game_state, &config, &mut agent, &mut rng);
+        assert!(game_state.last_explanations[0].is_some());
+        assert!(game_state.last_explanations[1].is_none());
+        assert_eq!(game_state.hand_log.len(), 1);
+        assert!(game_state.hand_log[0].contains("P1 (Threshold)"));
+        assert!(game_state.hand_log[0].contains("rule:"));
+
+        perform_validated_action(
+            &mut game_state,
+            &config,
+            &mut FixedAgent(PokerAction::Check),
+            &mut rng,
+        );
+        assert!(game_state.hand_log[1].ends_with("no explanation"));
+    }
+
+    #[test]
+    fn test_mistakes_are_noted_in_the_explanation() {
+        let actions = [PokerAction::Check, PokerAction::Bet, PokerAction::Fold];
+        let inner = Box::new(ThresholdAgent::new(pure_profile()));
+        let mut agent = MistakeAgent::new(inner, 1.0);
+        let mut rng = rng();
+        let view = view("7c2d", "Ks Qh 9d", 100, 0);
+        let noted = (0..20).any(|_| {
+            agent.choose_action(&view, &actions, &mut rng);
+            let explanation = agent.explain().unwrap();
+            explanation
+                .notes
+                .iter()
+                .any(|note| note.starts_with("mistake"))
+        });
+        assert!(noted);
+    }
+
+    #[test]
+    fn test_mistakes_stay_within_legal_actions() {
+        let actions = [PokerAction::Check, PokerAction::Bet, PokerAction::Fold];
+        let inner = Box::new(FixedAgent(PokerAction::Check));
+        let mut agent = MistakeAgent::new(inner, 1.0);
+        let mut rng = rng();
+        let view = view("AhKh", "", 0, 0);
+        let chosen: Vec<PokerAction> = (0..50)
+            .map(|_| agent.choose_action(&view, &actions, &mut rng))
+            .collect();
+        assert!(chosen.iter().all(|a| actions.contains(a)));
+        assert!(chosen.iter().any(|&a| a != PokerAction::Check));
+    }
+
+    /// Deals and plays one hand between the default seats using only `seed`.
+    fn play_seeded_hand(seed: u64) -> Vec<ActionRecord> {
+        use crate::game::GameRng;
+        use crate::poker_logic::Deck;
+
+        let config = GameConfig::default();
+        let mut rng = GameRng::new(Some(seed));
+        let mut game_state = new_hand();
+        game_state.deck = Deck::with_rng(&mut rng.0);
+        let mut draw = || game_state.deck.draw().unwrap();
+        let cards: Vec<_> = (0..9).map(|_| draw()).collect();
+        game_state.hole_cards[0] = [cards[0], cards[1]];
+        game_state.hole_cards[1] = [cards[2], cards[3]];
+        game_state.community_cards.copy_from_slice(&cards[4..]);
+
+        let mut agents = SeatAgents::from_config(&config, &AiConfig::built_in());
+        while game_state.current_round != PokerRound::Showdown {
+            let seat = game_state.current_player;
+            perform_validated_action(&mut game_state, &config, agents.seat_mut(seat), &mut rng.0);
+        }
+        game_state.action_history
+    }
+
+    #[test]
+    fn test_seeded_hands_replay_exactly() {
+        for seed in 0..5 {
+            assert_eq!(play_seeded_hand(seed), play_seeded_hand(seed));
+        }
+    }
+
+    /// Plays `actions` through the engine from the blinds and has `agent`
+    /// watch the hand enough times for its adjustments to reach full
+    /// strength.
+    fn observe_many(agent: &mut AdaptiveAgent, actions: &[PokerAction]) {
+        let config = GameConfig::default();
+        let mut game_state = GameStateResource::with_stacks(vec![1000; 2]);
+        post_blinds(&mut game_state, &config);
+        for &action in actions {
+            assert!(get_valid_actions(&game_state, &config).contains(&action));
+            apply_action(&mut game_state, &config, action);
+        }
+        let went_to_showdown =
+            game_state.current_round == PokerRound::Showdown && game_state.winner.is_none();
+        for _ in 0..AI_ADAPT_FULL_HANDS {
+            agent.observe_hand_end(&game_state.action_history, went_to_showdown);
+        }
+    }
+
+    #[test]
+    fn test_adaptive_agent_calls_maniacs_lighter() {
+        let base = pure_profile();
+        let mut agent = AdaptiveAgent::new(base.clone(), AI_EQUITY_SAMPLES);
+        // Opponent (seat 1) re-raises every open and we fold
+        observe_many(
+            &mut agent,
+            &[PokerAction::Raise, PokerAction::Raise, PokerAction::Fold],
+        );
+        let opponent = agent.tracker.player(1);
+        assert_eq!(opponent.vpip(), Some(1.0));
+        assert_eq!(opponent.three_bet(), Some(1.0));
+        assert!(adapt_profile(&base, opponent).call_margin > base.call_margin);
+    }
+
+    #[test]
+    fn test_adaptive_agent_bluffs_players_who_fold_to_cbets() {
+        let base = AiProfile::default();
+        let mut agent = AdaptiveAgent::new(base.clone(), AI_EQUITY_SAMPLES);
+        // We open and the opponent calls, then check-folds to the c-bet
+        observe_many(
+            &mut agent,
+            &[
+                PokerAction::Raise,
+                PokerAction::Call,
+                PokerAction::Check,
+                PokerAction::Bet,
+                PokerAction::Fold,
+            ],
+        );
+        let opponent = agent.tracker.player(1);
+        assert_eq!(opponent.fold_to_cbet(), Some(1.0));
+        let adapted = adapt_profile(&base, opponent);
+        assert!(adapted.bluff_frequency > base.bluff_frequency);
+        assert!(adapted.semi_bluff_frequency > base.semi_bluff_frequency);
+    }
+
+    #[test]
+    fn test_no_adaptation_without_history() {
+        let base = AiProfile::default();
+        assert_eq!(adapt_profile(&base, &PlayerStats::default()), base);
+    }
+}
@@ -1,20 +1,33 @@
+use crate::agent::SeatAgents;
+use crate::cash::CashGame;
 use crate::constants::*;
 use crate::game::*;
 use crate::poker_logic::{Deck, PokerRound};
+use crate::tournament::Tournament;
 use bevy::prelude::*;
 
 /// System that handles betting actions with AI decision making.
 pub fn handle_betting(
     config: Res<GameConfig>,
     mut game_state: ResMut<GameStateResource>,
+    mut agents: ResMut<SeatAgents>,
+    mut rng: ResMut<GameRng>,
     time: Res<Time>,
+    tournament: Option<Res<Tournament>>,
+    cash: Option<Res<CashGame>>,
 ) {
+    if tournament.is_some_and(|tournament| tournament.is_finished())
+        || cash.is_some_and(|cash| cash.is_closed())
+    {
+        return;
+    }
     let action_delay = config.action_delay;
     let elapsed = time.elapsed_seconds() - game_state.animation_start_time;
 
     let current_tick = ((elapsed - BETTING_INITIAL_DELAY).max(0.0) / action_delay) as u32;
     if elapsed > BETTING_INITIAL_DELAY && current_tick > game_state.action_tick {
-        perform_validated_action(&mut game_state, &config);
+        let seat = game_state.current_player;
+        perform_validated_action(&mut game_state, &config, agents.seat_mut(seat), &mut rng.0);
         game_state.action_tick = current_tick;
     }
 }
@@ -27,11 +40,14 @@ pub fn check_game_flow(mut game_state: ResMut<GameStateResource>, time: Res<Time
 }
 
 /// System that handles showdown resolution and hand cleanup.
-pub fn handle_showdown(mut game_state: ResMut<GameStateResource>) {
+pub fn handle_showdown(mut game_state: ResMut<GameStateResource>, mut agents: ResMut<SeatAgents>) {
     if game_state.current_round == PokerRound::Showdown && game_state.showdown_timer <= 0.0 {
-        if game_state.winner.is_none() {
+        // A winner is already set when the hand ended with a fold
+        let went_to_showdown = game_state.winner.is_none();
+        if went_to_showdown {
             process_showdown_result(&mut game_state);
         }
+        agents.observe_hand_end(&game_state.action_history, went_to_showdown);
 
         game_state.current_round = PokerRound::PreFlop;
         game_state.showdown_timer = -1.0;
@@ -46,32 +62,22 @@ pub fn start_hand(
     config: &GameConfig,
     colors: &ColorPalette,
     time: &Time,
+    rng: &mut GameRng,
 ) {
     let animation_start_time = time.elapsed_seconds();
-    game_state.pot = 0;
-    game_state.pot_remainder = 0;
-    game_state.current_round = PokerRound::PreFlop;
-    game_state.last_action = "New hand".to_string();
-    if game_state.hand_number > 0 {
-        game_state.hand_number += 1;
-    } else {
-        game_state.hand_number = 1;
-    }
-    game_state.showdown_timer = 0.0;
-    game_state.dealer_position = (game_state.dealer_position + 1) % PLAYER_COUNT;
-    game_state.current_player = (game_state.dealer_position + 1) % PLAYER_COUNT;
-    game_state.player_bets = [0; PLAYER_COUNT];
-    game_state.current_bet = 0;
-    game_state.winner = None;
-    game_state.last_winner_message = String::new();
-
-    if game_state.deck.cards_remaining() < config.min_cards_for_reshuffle {
-        game_state.deck = Deck::new();
-    }
+    begin_hand(game_state, config, &mut rng.0);
 
     use crate::ui::*;
     spawn_table(commands, config.screen_width, config.screen_height, *colors);
-    spawn_all_players(commands, game_state, config, *colors, animation_start_time);
+    let labels = seat_labels(game_state.seat_count(), Some(0));
+    spawn_all_players(
+        commands,
+        game_state,
+        config,
+        *colors,
+        &labels,
+        animation_start_time,
+    );
     spawn_all_community_cards(commands, game_state, config, colors, animation_start_time);
     spawn_ui(commands, game_state, config, colors);
 }
@@ -90,36 +96,87 @@ pub fn cleanup_old_hand(
     }
 }
 
-/// System that triggers hand start when appropriate.
+/// System that triggers hand start when appropriate. In a tournament it
+/// first knocks out the players who busted and sets the blinds for the
+/// next hand, and once one player is left it clears the table instead.
 pub fn start_hand_system(
     mut commands: Commands,
     mut game_state: ResMut<GameStateResource>,
-    config: Res<GameConfig>,
+    mut config: ResMut<GameConfig>,
     colors: Res<ColorPalette>,
     time: Res<Time>,
+    mut rng: ResMut<GameRng>,
+    tournament: Option<ResMut<Tournament>>,
+    cash: Option<ResMut<CashGame>>,
 ) {
     if game_state.needs_hand_restart {
         game_state.needs_cleanup = true;
+        if let Some(mut cash) = cash {
+            if game_state.hand_number > 0 {
+                settle_hand(&mut cash, &mut game_state);
+            }
+            if cash.is_closed() {
+                game_state.needs_hand_restart = false;
+                return;
+            }
+        }
+        if let Some(mut tournament) = tournament {
+            if game_state.hand_number > 0 {
+                tournament.end_hand(&game_state);
+            }
+            if tournament.is_finished() {
+                game_state.needs_hand_restart = false;
+                return;
+            }
+            tournament.begin_hand(&game_state, &mut config, time.elapsed_seconds());
+        }
         game_state.animation_start_time = time.elapsed_seconds();
         game_state.showdown_timer = 0.0;
         game_state.action_tick = 0;
         game_state.winner = None;
         game_state.last_winner_message = String::new();
         game_state.needs_hand_restart = false;
-        start_hand(&mut commands, &mut game_state, &config, &colors, time);
+        start_hand(
+            &mut commands,
+            &mut game_state,
+            &config,
+            &colors,
+            &time,
+            &mut rng,
+        );
     }
 }
 
-/// System that sets up the initial game state.
+/// Saves the cash game's bankrolls after a hand and tops up the stacks
+/// for the next one.
+fn settle_hand(cash: &mut CashGame, game_state: &mut GameStateResource) {
+    for (seat, chips) in cash.between_hands(game_state) {
+        info!("P{} tops up {} chips", seat + 1, chips);
+    }
+    if let Err(err) = cash.save(game_state) {
+        warn!("{}; the session is not saved", err);
+    }
+}
+
+/// System that sets up the initial game state. In a cash game the stacks
+/// are bought in from the players' bankrolls, otherwise everyone starts
+/// with `starting_chips`.
 pub fn setup_game(
     mut commands: Commands,
     mut game_state: ResMut<GameStateResource>,
     config: Res<GameConfig>,
+    mut rng: ResMut<GameRng>,
+    cash: Option<ResMut<CashGame>>,
 ) {
     commands.spawn((Camera2d, HandMarker));
+    game_state.deck = Deck::with_rng(&mut rng.0);
     game_state.hand_number = 0;
-    game_state.player_chips = [config.starting_chips; PLAYER_COUNT];
-    game_state.player_bets = [0; PLAYER_COUNT];
+    match cash {
+        Some(mut cash) => cash.sit_down(&mut game_state),
+        None => game_state.player_chips = vec![config.starting_chips; config.seats.len()],
+    }
+    game_state.player_bets = vec![0; config.seats.len()];
+    game_state.folded = vec![false; config.seats.len()];
     game_state.current_bet = 0;
     game_state.winner = None;
     game_state.dealer_position = 0;
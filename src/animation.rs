@@ -6,11 +6,11 @@ use bevy::prelude::*;
 pub fn update_animations(
     time: Res<Time>,
     config: Res<GameConfig>,
-    mut query: Query<(Entity, &mut Transform, &mut DealAnimation)>,
+    mut query: Query<(&mut Transform, &mut DealAnimation)>,
 ) {
     let elapsed = time.elapsed_seconds();
 
-    for (entity, mut transform, mut anim) in query.iter_mut() {
+    for (mut transform, mut anim) in query.iter_mut() {
         let anim_elapsed = elapsed - anim.start_time - anim.delay;
 
         if anim_elapsed > 0.0 && anim.duration > 0.0 {
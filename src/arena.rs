@@ -0,0 +1,440 @@
+//! Headless bot-vs-bot matches on the game engine.
+//!
+//! An [`Arena`] plays a round robin between registered agents. Every deal is
+//! played twice with the seats swapped (duplicate dealing), so each agent
+//! holds both sets of cards and most of the luck of the deal cancels out.
+//! Results are reported in milli-big-blinds per hand with a confidence
+//! interval, and as Elo ratings fitted to the outcome of every duplicate
+//! pair.
+
+use crate::agent::PokerAgent;
+use crate::constants::*;
+use crate::game::{
+    perform_validated_action, post_blinds, process_showdown_result, GameConfig, GameStateResource,
+};
+use crate::poker_logic::{full_deck, Board, HoleCards, PokerRound, SeatIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{RngCore, SeedableRng};
+use std::fmt;
+
+/// Builds a fresh agent for each match, so agents that learn start clean.
+pub type AgentFactory = Box<dyn Fn() -> Box<dyn PokerAgent>>;
+
+/// The cards of one hand: each seat's hole cards and the whole board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deal {
+    pub holes: [HoleCards; HEADS_UP_SEATS],
+    pub board: Board,
+}
+
+impl Deal {
+    pub fn random(rng: &mut dyn RngCore) -> Self {
+        let mut deck = full_deck();
+        deck.shuffle(rng);
+        Self {
+            holes: [[deck[0], deck[1]], [deck[2], deck[3]]],
+            board: [deck[4], deck[5], deck[6], deck[7], deck[8]],
+        }
+    }
+}
+
+/// Plays one hand of `deal` from fresh stacks, with `seats[i]` acting for
+/// seat `i`, and returns each seat's net chips. Every agent is shown the
+/// end of the hand.
+pub fn play_hand(
+    config: &GameConfig,
+    mut seats: [&mut dyn PokerAgent; HEADS_UP_SEATS],
+    deal: &Deal,
+    dealer: SeatIndex,
+    rng: &mut dyn RngCore,
+) -> [i64; HEADS_UP_SEATS] {
+    let mut game_state = GameStateResource {
+        hole_cards: deal.holes.to_vec(),
+        dealer_position: dealer,
+        community_cards: deal.board,
+        hand_number: 1,
+        ..GameStateResource::with_stacks(vec![config.starting_chips; HEADS_UP_SEATS])
+    };
+    post_blinds(&mut game_state, config);
+    while game_state.current_round != PokerRound::Showdown {
+        let seat = game_state.current_player;
+        perform_validated_action(&mut game_state, config, &mut *seats[seat], rng);
+    }
+
+    // A winner is already set when the hand ended with a fold
+    let went_to_showdown = game_state.winner.is_none();
+    if went_to_showdown {
+        process_showdown_result(&mut game_state);
+    }
+    for agent in seats.iter_mut() {
+        agent.observe_hand_end(&game_state.action_history, went_to_showdown);
+    }
+    std::array::from_fn(|seat| {
+        i64::from(game_state.player_chips[seat]) - i64::from(config.starting_chips)
+    })
+}
+
+/// Mean and confidence half-width of `samples`.
+fn interval(samples: &[f64]) -> (f64, f64) {
+    if samples.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    if samples.len() < 2 {
+        return (mean, 0.0);
+    }
+    let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, ARENA_CONFIDENCE_Z * (variance / n).sqrt())
+}
+
+/// The duplicate pairs of one match, seen from `first`'s side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchResult {
+    pub first: usize,
+    pub second: usize,
+    /// `first`'s net chips over both hands of each deal.
+    pub pair_results: Vec<i64>,
+}
+
+impl MatchResult {
+    pub fn hands(&self) -> usize {
+        self.pair_results.len() * 2
+    }
+
+    /// `first`'s win rate per duplicate pair, in milli-big-blinds per hand.
+    fn mbb_samples(&self, big_blind: u32) -> Vec<f64> {
+        let scale = 1000.0 / f64::from(big_blind.max(1)) / 2.0;
+        self.pair_results
+            .iter()
+            .map(|&chips| chips as f64 * scale)
+            .collect()
+    }
+
+    /// `first`'s win rate in milli-big-blinds per hand and the half-width of
+    /// its confidence interval.
+    pub fn mbb_per_hand(&self, big_blind: u32) -> (f64, f64) {
+        interval(&self.mbb_samples(big_blind))
+    }
+
+    /// Duplicate pairs `first` won, drew and lost.
+    pub fn score(&self) -> (u32, u32, u32) {
+        let count = |f: fn(&i64) -> bool| self.pair_results.iter().filter(|x| f(x)).count() as u32;
+        (count(|&x| x > 0), count(|&x| x == 0), count(|&x| x < 0))
+    }
+}
+
+/// Expected score of a player rated `rating` against one rated `opponent`.
+fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+/// Elo ratings for `entries` players fitted to every duplicate pair in
+/// `results`, each counted as a win, draw or loss. Every player also gets
+/// one draw against an average player, which keeps the ratings finite when
+/// someone wins or loses every pair.
+pub fn fit_elo(entries: usize, results: &[MatchResult]) -> Vec<f64> {
+    let mut ratings = vec![ARENA_ELO_BASE; entries];
+    for _ in 0..ARENA_ELO_ITERATIONS {
+        let mut surplus: Vec<f64> = ratings
+            .iter()
+            .map(|&rating| 0.5 - expected_score(rating, ARENA_ELO_BASE))
+            .collect();
+        let mut games = vec![1.0; entries];
+        for result in results {
+            let (wins, draws, losses) = result.score();
+            let played = f64::from(wins + draws + losses);
+            let expected = played * expected_score(ratings[result.first], ratings[result.second]);
+            let gain = f64::from(wins) + f64::from(draws) / 2.0 - expected;
+            surplus[result.first] += gain;
+            surplus[result.second] -= gain;
+            games[result.first] += played;
+            games[result.second] += played;
+        }
+        for ((rating, gain), played) in ratings.iter_mut().zip(&surplus).zip(&games) {
+            *rating += ARENA_ELO_STEP * gain / played;
+        }
+    }
+    ratings
+}
+
+/// Registered agents and the table they play at.
+pub struct Arena {
+    config: GameConfig,
+    entries: Vec<(String, AgentFactory)>,
+}
+
+impl Arena {
+    pub fn new(config: GameConfig) -> Self {
+        Self {
+            config,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds an entry that plays agents built by `factory`.
+    pub fn register(&mut self, name: impl Into<String>, factory: AgentFactory) {
+        self.entries.push((name.into(), factory));
+    }
+
+    /// Plays `deals` duplicate pairs between entries `first` and `second`.
+    /// The button alternates between deals; both hands of a pair use the
+    /// same button and the same decision seed.
+    pub fn play_match(
+        &self,
+        first: usize,
+        second: usize,
+        deals: u32,
+        rng: &mut dyn RngCore,
+    ) -> MatchResult {
+        let mut a = (self.entries[first].1)();
+        let mut b = (self.entries[second].1)();
+        let pair_results = (0..deals as usize)
+            .map(|index| {
+                let deal = Deal::random(rng);
+                let seed = rng.next_u64();
+                let dealer = index % HEADS_UP_SEATS;
+                let straight = play_hand(
+                    &self.config,
+                    [a.as_mut(), b.as_mut()],
+                    &deal,
+                    dealer,
+                    &mut StdRng::seed_from_u64(seed),
+                );
+                let swapped = play_hand(
+                    &self.config,
+                    [b.as_mut(), a.as_mut()],
+                    &deal,
+                    dealer,
+                    &mut StdRng::seed_from_u64(seed),
+                );
+                straight[0] + swapped[1]
+            })
+            .collect();
+        MatchResult {
+            first,
+            second,
+            pair_results,
+        }
+    }
+
+    /// Plays every pair of entries against each other, calling `on_match`
+    /// as each match finishes.
+    pub fn round_robin(
+        &self,
+        deals: u32,
+        rng: &mut dyn RngCore,
+        mut on_match: impl FnMut(&MatchResult),
+    ) -> ArenaReport {
+        let mut matches = Vec::new();
+        for first in 0..self.entries.len() {
+            for second in first + 1..self.entries.len() {
+                let result = self.play_match(first, second, deals, rng);
+                on_match(&result);
+                matches.push(result);
+            }
+        }
+        ArenaReport {
+            names: self.entries.iter().map(|(name, _)| name.clone()).collect(),
+            big_blind: self.config.big_blind,
+            matches,
+        }
+    }
+
+    pub fn name(&self, entry: usize) -> &str {
+        &self.entries[entry].0
+    }
+}
+
+/// One line of the leaderboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub elo: f64,
+    pub mbb_per_hand: f64,
+    pub confidence: f64,
+    pub hands: usize,
+}
+
+/// Every match of a round robin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaReport {
+    pub names: Vec<String>,
+    pub big_blind: u32,
+    pub matches: Vec<MatchResult>,
+}
+
+impl ArenaReport {
+    /// `entry`'s win rate per duplicate pair over all its matches, in
+    /// milli-big-blinds per hand.
+    fn entry_samples(&self, entry: usize) -> Vec<f64> {
+        let mut samples = Vec::new();
+        for result in &self.matches {
+            let sign = if result.first == entry {
+                1.0
+            } else if result.second == entry {
+                -1.0
+            } else {
+                continue;
+            };
+            let mbb = result.mbb_samples(self.big_blind);
+            samples.extend(mbb.into_iter().map(|x| sign * x));
+        }
+        samples
+    }
+
+    /// Entries ranked by Elo, each with its win rate over all its matches.
+    pub fn leaderboard(&self) -> Vec<Standing> {
+        let ratings = fit_elo(self.names.len(), &self.matches);
+        let mut standings: Vec<Standing> = self
+            .names
+            .iter()
+            .enumerate()
+            .map(|(entry, name)| {
+                let samples = self.entry_samples(entry);
+                let (mbb_per_hand, confidence) = interval(&samples);
+                Standing {
+                    name: name.clone(),
+                    elo: ratings[entry],
+                    mbb_per_hand,
+                    confidence,
+                    hands: samples.len() * 2,
+                }
+            })
+            .collect();
+        standings.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+        standings
+    }
+}
+
+impl fmt::Display for ArenaReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>4}  {:<28} {:>6} {:>18} {:>7}",
+            "Rank", "Entry", "Elo", "mb/hand", "Hands"
+        )?;
+        for (rank, standing) in self.leaderboard().iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:<28} {:>6.0} {:>+9.1} ± {:<6.1} {:>7}",
+                rank + 1,
+                standing.name,
+                standing.elo,
+                standing.mbb_per_hand,
+                standing.confidence,
+                standing.hands
+            )?;
+        }
+        writeln!(f, "\nMatches")?;
+        for result in &self.matches {
+            let (mbb, confidence) = result.mbb_per_hand(self.big_blind);
+            let (wins, draws, losses) = result.score();
+            writeln!(
+                f,
+                "  {} vs {}: {:+.1} ± {:.1} mb/hand (W {} D {} L {})",
+                self.names[result.first],
+                self.names[result.second],
+                mbb,
+                confidence,
+                wins,
+                draws,
+                losses
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::{MistakeAgent, ThresholdAgent};
+    use crate::profile::AiProfile;
+
+    fn pure_threshold() -> Box<dyn PokerAgent> {
+        Box::new(ThresholdAgent::new(AiProfile {
+            bluff_frequency: 0.0,
+            semi_bluff_frequency: 0.0,
+            slow_play_frequency: 0.0,
+            check_raise_frequency: 0.0,
+            ..AiProfile::default()
+        }))
+    }
+
+    #[test]
+    fn test_duplicate_deals_cancel_out_between_equal_agents() {
+        let mut arena = Arena::new(GameConfig::default());
+        arena.register("A", Box::new(pure_threshold));
+        arena.register("B", Box::new(pure_threshold));
+        let result = arena.play_match(0, 1, 40, &mut StdRng::seed_from_u64(40));
+        assert_eq!(result.hands(), 80);
+        assert!(result.pair_results.iter().all(|&chips| chips == 0));
+        assert_eq!(result.mbb_per_hand(50), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_hands_are_zero_sum() {
+        let config = GameConfig::default();
+        let mut rng = StdRng::seed_from_u64(41);
+        let (mut a, mut b) = (pure_threshold(), pure_threshold());
+        for dealer in [0, 1, 0, 1] {
+            let deal = Deal::random(&mut rng);
+            let net = play_hand(&config, [a.as_mut(), b.as_mut()], &deal, dealer, &mut rng);
+            assert_eq!(net[0] + net[1], 0);
+        }
+    }
+
+    #[test]
+    fn test_round_robin_ranks_the_stronger_agent_first() {
+        let mut arena = Arena::new(GameConfig::default());
+        arena.register("Threshold", Box::new(pure_threshold));
+        arena.register(
+            "Random",
+            Box::new(|| Box::new(MistakeAgent::new(pure_threshold(), 1.0)) as Box<dyn PokerAgent>),
+        );
+        arena.register("Threshold 2", Box::new(pure_threshold));
+        let mut played = 0;
+        let report = arena.round_robin(60, &mut StdRng::seed_from_u64(42), |_| played += 1);
+        assert_eq!(played, 3);
+        let board = report.leaderboard();
+        assert_eq!(board.len(), 3);
+        assert_eq!(board[2].name, "Random");
+        assert!(board[2].mbb_per_hand < 0.0);
+        assert_eq!(board.iter().map(|s| s.hands).sum::<usize>(), 2 * 3 * 120);
+        assert!(report.to_string().contains("Threshold vs Random"));
+    }
+
+    #[test]
+    fn test_elo_matches_the_score() {
+        // 75% of pairs won is worth about 191 Elo points
+        let result = MatchResult {
+            first: 0,
+            second: 1,
+            pair_results: [vec![1; 750], vec![-1; 250]].concat(),
+        };
+        let ratings = fit_elo(2, &[result]);
+        assert!(
+            (ratings[0] - ratings[1] - 190.8).abs() < 5.0,
+            "{:?}",
+            ratings
+        );
+        assert!((ratings[0] + ratings[1] - 2.0 * ARENA_ELO_BASE).abs() < 1.0);
+
+        let shutout = MatchResult {
+            first: 0,
+            second: 1,
+            pair_results: vec![5; 10],
+        };
+        assert!(fit_elo(2, &[shutout]).iter().all(|r| r.is_finite()));
+    }
+
+    #[test]
+    fn test_confidence_interval() {
+        let (mean, half_width) = interval(&[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(mean, 2.5);
+        // sd = sqrt(5/3), so 1.96 * sd / 2
+        assert!((half_width - 1.96 * (5.0f64 / 3.0).sqrt() / 2.0).abs() < 1e-9);
+    }
+}
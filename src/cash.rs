@@ -0,0 +1,348 @@
+//! Cash game sessions and persistent bankrolls.
+//!
+//! In a cash game chips stand for money. Every player has a [`Bankroll`]
+//! account that survives between runs; sitting down moves a buy-in from
+//! the account to the table, players can top up between hands, and
+//! leaving puts the stack back. Each visit to the table is kept as a
+//! [`SessionRecord`], so the account's balance can be followed over time.
+//!
+//! A [`CashGame`] holds the accounts with the chips on the table taken
+//! out. After every hand it writes the bankroll file as if everyone had
+//! left then, so closing the window at any point keeps the results up to
+//! the last finished hand.
+
+use crate::constants::*;
+use crate::game::GameStateResource;
+use crate::poker_logic::SeatIndex;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One visit to the table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    /// Seconds since the Unix epoch when the player sat down
+    pub started_at: u64,
+    pub hands: u32,
+    /// Chips taken from the bankroll: the buy-in and every top-up
+    pub bought_in: u64,
+    /// Chips returned to the bankroll on leaving
+    pub cashed_out: u64,
+    /// Bankroll balance after leaving
+    pub balance: u64,
+}
+
+impl SessionRecord {
+    /// Chips won (positive) or lost (negative) over the session.
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn profit(&self) -> i64 {
+        self.cashed_out as i64 - self.bought_in as i64
+    }
+}
+
+/// One player's money off the table and their past sessions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    pub balance: u64,
+    pub sessions: Vec<SessionRecord>,
+}
+
+impl Default for Account {
+    fn default() -> Self {
+        Self {
+            balance: CASH_STARTING_BANKROLL,
+            sessions: Vec::new(),
+        }
+    }
+}
+
+impl Account {
+    /// Takes up to `amount` from the balance and returns what was taken.
+    pub fn withdraw(&mut self, amount: u32) -> u32 {
+        // Safe: the result is at most `amount`
+        let taken = u64::from(amount).min(self.balance) as u32;
+        self.balance -= u64::from(taken);
+        taken
+    }
+
+    /// Profit over every recorded session.
+    pub fn lifetime_profit(&self) -> i64 {
+        self.sessions.iter().map(SessionRecord::profit).sum()
+    }
+}
+
+/// Error returned when the bankroll file cannot be read or written.
+#[derive(Debug)]
+pub enum BankrollError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for BankrollError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BankrollError::Io(err) => write!(f, "cannot access bankroll: {}", err),
+            BankrollError::Parse(err) => write!(f, "invalid bankroll: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for BankrollError {}
+
+/// Every player's account, by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bankroll {
+    pub accounts: BTreeMap<String, Account>,
+}
+
+impl Bankroll {
+    /// Reads a bankroll file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BankrollError> {
+        let json = std::fs::read_to_string(path).map_err(BankrollError::Io)?;
+        serde_json::from_str(&json).map_err(BankrollError::Parse)
+    }
+
+    /// Reads `path`, or starts an empty bankroll if there is no such file.
+    /// An unreadable file is an error rather than a fresh start, so saving
+    /// never overwrites accounts that failed to load.
+    pub fn load_or_new(path: impl AsRef<Path>) -> Result<Self, BankrollError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::load(path)
+    }
+
+    /// Writes the bankroll as JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), BankrollError> {
+        let json = serde_json::to_string_pretty(self).map_err(BankrollError::Parse)?;
+        std::fs::write(path, json + "\n").map_err(BankrollError::Io)
+    }
+
+    /// The account of `name`, opened with `CASH_STARTING_BANKROLL` on first
+    /// use.
+    pub fn account(&mut self, name: &str) -> &mut Account {
+        self.accounts.entry(name.to_string()).or_default()
+    }
+}
+
+/// A cash game in progress: what each seat has bought in for and the
+/// accounts the chips came from.
+#[derive(Resource, Debug, Clone)]
+pub struct CashGame {
+    /// Chips a player sits down with, and tops up to
+    pub buy_in: u32,
+    /// Whether stacks below the buy-in are topped up between hands; when
+    /// off, only a busted player buys in again
+    pub top_up: bool,
+    path: PathBuf,
+    /// Balances with the chips on the table taken out
+    bankroll: Bankroll,
+    /// Account name for each seat
+    players: Vec<String>,
+    started_at: u64,
+    /// Chips each seat has taken from its account this session
+    bought_in: Vec<u64>,
+    /// Set once fewer than two players can afford to play on
+    closed: bool,
+}
+
+impl CashGame {
+    /// A table of `seats` players "P1", "P2" and so on, with accounts in
+    /// `bankroll`, which is saved to `path`.
+    pub fn new(bankroll: Bankroll, path: impl Into<PathBuf>, seats: usize, buy_in: u32) -> Self {
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Self {
+            buy_in,
+            top_up: true,
+            path: path.into(),
+            bankroll,
+            players: (1..=seats).map(|seat| format!("P{}", seat)).collect(),
+            started_at,
+            bought_in: vec![0; seats],
+            closed: false,
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Seats every player with a buy-in from their account, or whatever is
+    /// left in it. The table stays closed unless two players can pay.
+    pub fn sit_down(&mut self, game_state: &mut GameStateResource) {
+        game_state.player_chips = vec![0; self.players.len()];
+        self.between_hands(game_state);
+    }
+
+    /// Tops up the stacks for the next hand and returns the seats topped
+    /// up with how much each added. Closes the table when fewer than two
+    /// players have chips.
+    pub fn between_hands(&mut self, game_state: &mut GameStateResource) -> Vec<(SeatIndex, u32)> {
+        let added = self.top_up_stacks(game_state);
+        self.closed = game_state
+            .player_chips
+            .iter()
+            .filter(|&&chips| chips > 0)
+            .count()
+            < 2;
+        added
+    }
+
+    /// Brings every stack that may be topped up back to the buy-in.
+    fn top_up_stacks(&mut self, game_state: &mut GameStateResource) -> Vec<(SeatIndex, u32)> {
+        let mut added = Vec::new();
+        for (seat, player) in self.players.iter().enumerate() {
+            let stack = game_state.player_chips[seat];
+            if stack >= self.buy_in || (stack > 0 && !self.top_up) {
+                continue;
+            }
+            let chips = self.bankroll.account(player).withdraw(self.buy_in - stack);
+            if chips > 0 {
+                game_state.player_chips[seat] += chips;
+                self.bought_in[seat] += u64::from(chips);
+                added.push((seat, chips));
+            }
+        }
+        added
+    }
+
+    /// The bankroll as it would be if everyone left the table now, each
+    /// stack returned and the session recorded.
+    pub fn settled(&self, game_state: &GameStateResource) -> Bankroll {
+        let mut bankroll = self.bankroll.clone();
+        let hands = u32::try_from(game_state.hand_number.max(0)).unwrap_or(0);
+        for (seat, player) in self.players.iter().enumerate() {
+            let stack = u64::from(game_state.player_chips[seat]);
+            let account = bankroll.account(player);
+            account.balance += stack;
+            account.sessions.push(SessionRecord {
+                started_at: self.started_at,
+                hands,
+                bought_in: self.bought_in[seat],
+                cashed_out: stack,
+                balance: account.balance,
+            });
+        }
+        bankroll
+    }
+
+    /// Writes the [`settled`](Self::settled) bankroll to disk.
+    pub fn save(&self, game_state: &GameStateResource) -> Result<(), BankrollError> {
+        self.settled(game_state).save(&self.path)
+    }
+
+    /// Each seat's profit this session, e.g. "P/L  P1 +250  P2 -1000".
+    pub fn session_summary(&self, game_state: &GameStateResource) -> String {
+        let seats: Vec<String> = (0..self.players.len())
+            .map(|seat| format!("P{} {:+}", seat + 1, self.profit(game_state, seat)))
+            .collect();
+        format!("P/L  {}", seats.join("  "))
+    }
+
+    /// The session's results and each player's bankroll, one line per seat.
+    pub fn results(&self, game_state: &GameStateResource) -> String {
+        let settled = self.settled(game_state);
+        let lines: Vec<String> = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(seat, player)| {
+                let balance = settled.accounts.get(player).map_or(0, |a| a.balance);
+                format!(
+                    "{}  {:+}  bankroll {}",
+                    player,
+                    self.profit(game_state, seat),
+                    balance
+                )
+            })
+            .collect();
+        format!("Session over\n\n{}", lines.join("\n"))
+    }
+
+    /// Chips `seat` is up or down this session.
+    #[allow(clippy::cast_possible_wrap)]
+    fn profit(&self, game_state: &GameStateResource, seat: SeatIndex) -> i64 {
+        i64::from(game_state.player_chips[seat]) - self.bought_in[seat] as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bankroll(balances: &[u64]) -> Bankroll {
+        let mut bankroll = Bankroll::default();
+        for (seat, &balance) in balances.iter().enumerate() {
+            bankroll.account(&format!("P{}", seat + 1)).balance = balance;
+        }
+        bankroll
+    }
+
+    #[test]
+    fn test_buy_ins_come_out_of_the_bankroll_and_stacks_go_back() {
+        let mut cash = CashGame::new(bankroll(&[5000, 600]), "unused.json", 2, 1000);
+        let mut game_state = GameStateResource::default();
+        cash.sit_down(&mut game_state);
+        assert_eq!(game_state.player_chips, [1000, 600]);
+
+        game_state.hand_number = 12;
+        game_state.player_chips = vec![1400, 200];
+        let settled = cash.settled(&game_state);
+        let winner = &settled.accounts["P1"];
+        assert_eq!(winner.balance, 5400);
+        assert_eq!(winner.sessions[0].profit(), 400);
+        assert_eq!(winner.sessions[0].hands, 12);
+        let loser = &settled.accounts["P2"];
+        assert_eq!((loser.balance, loser.lifetime_profit()), (200, -400));
+        assert_eq!(cash.session_summary(&game_state), "P/L  P1 +400  P2 -400");
+    }
+
+    #[test]
+    fn test_stacks_are_topped_up_until_the_bankroll_runs_out() {
+        let mut cash = CashGame::new(bankroll(&[3000, 1500, 1000]), "unused.json", 3, 1000);
+        let mut game_state = GameStateResource::default();
+        cash.sit_down(&mut game_state);
+
+        game_state.player_chips = vec![2600, 400, 0];
+        assert_eq!(cash.between_hands(&mut game_state), [(1, 500)]);
+        assert_eq!(game_state.player_chips, [2600, 900, 0]);
+        assert!(!cash.is_closed());
+
+        // Without top-ups only a busted player buys in again
+        cash.top_up = false;
+        game_state.player_chips = vec![3500, 0, 0];
+        assert!(cash.between_hands(&mut game_state).is_empty());
+        assert!(cash.is_closed());
+        let settled = cash.settled(&game_state);
+        assert_eq!(settled.accounts["P1"].balance, 5500);
+        assert_eq!(settled.accounts["P2"].sessions[0].profit(), -1500);
+    }
+
+    #[test]
+    fn test_bankroll_history_survives_a_save_and_load() {
+        let path = std::env::temp_dir().join("poker_simulation_bankroll_test.json");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(Bankroll::load_or_new(&path).unwrap(), Bankroll::default());
+
+        for session in 1..=2 {
+            let mut cash = CashGame::new(Bankroll::load_or_new(&path).unwrap(), &path, 2, 1000);
+            let mut game_state = GameStateResource::default();
+            cash.sit_down(&mut game_state);
+            game_state.player_chips = vec![1250, 750];
+            cash.save(&game_state).unwrap();
+
+            let account = &Bankroll::load(&path).unwrap().accounts["P1"];
+            assert_eq!(account.sessions.len(), session);
+            assert_eq!(account.lifetime_profit(), 250 * session as i64);
+        }
+        std::fs::write(&path, "not json").unwrap();
+        assert!(Bankroll::load_or_new(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,839 @@
+//! Counterfactual regret minimization for a simplified heads-up limit game.
+//!
+//! The abstract game follows the engine's fixed-limit betting: the dealer
+//! posts the small blind and acts first preflop, the other seat posts the big
+//! blind and acts first on every later street, an opening bet is `bet_amount`,
+//! raises add `raise_amount` to the current bet, and at most
+//! [`AI_CFR_MAX_BETS`] bets and raises are made per street. Going all-in is
+//! left out, so stacks are assumed deep enough never to run out. Cards are
+//! abstracted into equity buckets: preflop the 169 starting hands are split
+//! into equally sized groups by their equity against a random hand, and after
+//! the flop a hand's bucket is its sampled equity against a random hand on
+//! the current board.
+//!
+//! [`Trainer`] runs chance-sampled CFR or CFR+ over that game and produces a
+//! [`StrategyTable`] of average strategies, which is saved as JSON and played
+//! by [`CfrAgent`].
+
+use crate::agent::{DecisionBasis, Explanation, PokerAgent, ThresholdAgent};
+use crate::constants::*;
+use crate::game::{GameConfig, PlayerView, PokerAction};
+use crate::poker_logic::{evaluate_hand, full_deck, Card, HoleCards, PokerRound, SeatIndex};
+use crate::profile::AiProfile;
+use crate::range::{starting_hand_grid, starting_hand_name, Range};
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+/// Number of betting rounds before showdown.
+const STREETS: usize = 4;
+
+/// Which regret update the trainer uses.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CfrVariant {
+    /// Plain CFR: regrets accumulate freely and every iteration counts the
+    /// same towards the average strategy.
+    #[serde(rename = "cfr")]
+    Vanilla,
+    /// CFR+: negative regrets are reset to zero and later iterations weigh
+    /// more in the average strategy, which converges much faster.
+    #[default]
+    #[serde(rename = "cfr+")]
+    Plus,
+}
+
+impl fmt::Display for CfrVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CfrVariant::Vanilla => write!(f, "CFR"),
+            CfrVariant::Plus => write!(f, "CFR+"),
+        }
+    }
+}
+
+/// The card and bet abstraction a strategy table was trained for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Abstraction {
+    pub buckets: usize,
+    /// Runouts sampled to bucket a post-flop hand
+    pub bucket_samples: u32,
+    pub small_blind: u32,
+    pub big_blind: u32,
+    pub bet_amount: u32,
+    pub raise_amount: u32,
+    /// Bets plus raises allowed per street
+    pub max_bets: u32,
+    /// Bucket of every starting hand, keyed like "AKs", "QQ" or "72o"
+    pub preflop: BTreeMap<String, usize>,
+}
+
+impl Abstraction {
+    /// Builds the abstraction for the bet sizes in `config`, estimating each
+    /// starting hand's equity from `preflop_samples` runouts.
+    pub fn build<R: Rng + ?Sized>(
+        config: &GameConfig,
+        buckets: usize,
+        bucket_samples: u32,
+        preflop_samples: u32,
+        rng: &mut R,
+    ) -> Self {
+        let mut hands: Vec<(String, f64, f64)> = starting_hands()
+            .into_iter()
+            .map(|(key, hole, combos)| {
+                let equity = equity_vs_random(&hole, &[], preflop_samples, rng);
+                (key, equity, combos)
+            })
+            .collect();
+        hands.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let total: f64 = hands.iter().map(|(_, _, combos)| combos).sum();
+        let mut below = 0.0;
+        let mut preflop = BTreeMap::new();
+        for (key, _, combos) in hands {
+            let percentile = (below + combos / 2.0) / total;
+            preflop.insert(key, bucket_of(percentile, buckets));
+            below += combos;
+        }
+
+        Self {
+            buckets,
+            bucket_samples,
+            small_blind: config.small_blind,
+            big_blind: config.big_blind,
+            bet_amount: config.bet_amount,
+            raise_amount: config.raise_amount,
+            max_bets: AI_CFR_MAX_BETS,
+            preflop,
+        }
+    }
+
+    /// Bucket of `hole` on `board`; 0 is the weakest.
+    pub fn bucket<R: Rng + ?Sized>(&self, hole: &HoleCards, board: &[Card], rng: &mut R) -> usize {
+        if board.is_empty() {
+            return self
+                .preflop
+                .get(&starting_hand_name(hole))
+                .copied()
+                .unwrap_or(0);
+        }
+        let equity = equity_vs_random(hole, board, self.bucket_samples, rng);
+        bucket_of(equity, self.buckets)
+    }
+}
+
+fn bucket_of(fraction: f64, buckets: usize) -> usize {
+    ((fraction * buckets as f64) as usize).min(buckets - 1)
+}
+
+/// One example of each of the 169 starting hands with its number of combos.
+fn starting_hands() -> Vec<(String, HoleCards, f64)> {
+    starting_hand_grid()
+        .into_iter()
+        .map(|name| {
+            let range: Range = name.parse().expect("starting hand names are valid ranges");
+            let combos = range.combos();
+            (name, combos[0].cards, combos.len() as f64)
+        })
+        .collect()
+}
+
+/// Share of `samples` random deals that `hole` wins against a random hand,
+/// counting ties as half.
+fn equity_vs_random<R: Rng + ?Sized>(
+    hole: &HoleCards,
+    board: &[Card],
+    samples: u32,
+    rng: &mut R,
+) -> f64 {
+    if samples == 0 {
+        return 0.5;
+    }
+    let mut deck: Vec<Card> = full_deck()
+        .into_iter()
+        .filter(|c| !hole.contains(c) && !board.contains(c))
+        .collect();
+    let missing = MAX_COMMUNITY_CARDS - board.len();
+    let mut score = 0.0;
+    for _ in 0..samples {
+        let (drawn, _) = deck.partial_shuffle(rng, 2 + missing);
+        let (opponent, runout) = drawn.split_at(2);
+        let mine: Vec<Card> = hole.iter().chain(board).chain(runout).copied().collect();
+        let theirs: Vec<Card> = opponent
+            .iter()
+            .chain(board)
+            .chain(runout)
+            .copied()
+            .collect();
+        score += match evaluate_hand(&mine).cmp(&evaluate_hand(&theirs)) {
+            Ordering::Greater => 1.0,
+            Ordering::Equal => 0.5,
+            Ordering::Less => 0.0,
+        };
+    }
+    score / f64::from(samples)
+}
+
+/// Street number (0 = preflop) of a betting round.
+fn street_index(round: PokerRound) -> Option<usize> {
+    match round {
+        PokerRound::PreFlop => Some(0),
+        PokerRound::Flop => Some(1),
+        PokerRound::Turn => Some(2),
+        PokerRound::River => Some(3),
+        PokerRound::Showdown => None,
+    }
+}
+
+fn action_char(action: PokerAction) -> char {
+    match action {
+        PokerAction::Check => 'k',
+        PokerAction::Bet => 'b',
+        PokerAction::Call => 'c',
+        PokerAction::Raise => 'r',
+        PokerAction::AllIn => 'a',
+        PokerAction::Fold => 'f',
+    }
+}
+
+/// Information set key: the acting player's bucket and the betting so far,
+/// with streets separated by '/', e.g. "5:kbc/k".
+fn info_set_key(bucket: usize, history: &str) -> String {
+    format!("{}:{}", bucket, history)
+}
+
+/// A decision point in the abstract game. Player 0 is the big blind and
+/// player 1 the dealer, who posts the small blind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Betting {
+    street: usize,
+    history: String,
+    to_act: usize,
+    contributions: [u32; 2],
+    street_bets: [u32; 2],
+    bets: u32,
+    actions: u32,
+}
+
+/// What follows an action in the abstract game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Decision(Betting),
+    Fold {
+        folder: usize,
+        contributions: [u32; 2],
+    },
+    Showdown {
+        contributions: [u32; 2],
+    },
+}
+
+impl Betting {
+    fn new(abstraction: &Abstraction) -> Self {
+        let blinds = [abstraction.big_blind, abstraction.small_blind];
+        Self {
+            street: 0,
+            history: String::new(),
+            to_act: 1,
+            contributions: blinds,
+            street_bets: blinds,
+            bets: 0,
+            actions: 0,
+        }
+    }
+
+    fn to_call(&self) -> u32 {
+        self.street_bets[1 - self.to_act] - self.street_bets[self.to_act]
+    }
+
+    fn legal_actions(&self, abstraction: &Abstraction) -> Vec<PokerAction> {
+        let can_raise = self.bets < abstraction.max_bets;
+        // The big blind's option preflop is a raise, not a bet
+        let opened = self.street_bets[1 - self.to_act] > 0;
+        match (self.to_call(), can_raise) {
+            (0, true) if opened => vec![PokerAction::Check, PokerAction::Raise],
+            (0, true) => vec![PokerAction::Check, PokerAction::Bet],
+            (0, false) => vec![PokerAction::Check],
+            (_, true) => vec![PokerAction::Fold, PokerAction::Call, PokerAction::Raise],
+            (_, false) => vec![PokerAction::Fold, PokerAction::Call],
+        }
+    }
+
+    fn put_in(&mut self, chips: u32) {
+        self.street_bets[self.to_act] += chips;
+        self.contributions[self.to_act] += chips;
+    }
+
+    fn apply(&self, action: PokerAction, abstraction: &Abstraction) -> Node {
+        let mut next = self.clone();
+        next.history.push(action_char(action));
+        next.actions += 1;
+        let street_over = match action {
+            PokerAction::Fold => {
+                return Node::Fold {
+                    folder: self.to_act,
+                    contributions: self.contributions,
+                }
+            }
+            PokerAction::Check => next.actions >= 2,
+            // The small blind completing preflop leaves the big blind to act
+            PokerAction::Call => {
+                next.put_in(self.to_call());
+                next.actions >= 2
+            }
+            PokerAction::Bet => {
+                next.put_in(abstraction.bet_amount);
+                next.bets += 1;
+                false
+            }
+            PokerAction::Raise => {
+                let target = self.street_bets[1 - self.to_act] + abstraction.raise_amount;
+                next.put_in(target - self.street_bets[self.to_act]);
+                next.bets += 1;
+                false
+            }
+            PokerAction::AllIn => unreachable!("all-in is not part of the abstract game"),
+        };
+
+        if !street_over {
+            next.to_act = 1 - self.to_act;
+            return Node::Decision(next);
+        }
+        if self.street + 1 == STREETS {
+            return Node::Showdown {
+                contributions: next.contributions,
+            };
+        }
+        next.street += 1;
+        next.history.push('/');
+        next.street_bets = [0; 2];
+        next.bets = 0;
+        next.actions = 0;
+        next.to_act = 0;
+        Node::Decision(next)
+    }
+}
+
+impl Node {
+    /// Chips won by `player` at a terminal node; `showdown` compares player
+    /// 0's hand with player 1's.
+    fn payoff(&self, player: usize, showdown: Ordering) -> f64 {
+        match *self {
+            Node::Fold {
+                folder,
+                contributions,
+            } => {
+                if folder == player {
+                    -f64::from(contributions[player])
+                } else {
+                    f64::from(contributions[folder])
+                }
+            }
+            Node::Showdown { contributions } => {
+                let result = if player == 0 {
+                    showdown
+                } else {
+                    showdown.reverse()
+                };
+                match result {
+                    Ordering::Greater => f64::from(contributions[1 - player]),
+                    Ordering::Equal => 0.0,
+                    Ordering::Less => -f64::from(contributions[player]),
+                }
+            }
+            Node::Decision(_) => unreachable!("payoff of a decision node"),
+        }
+    }
+}
+
+/// Regrets and strategy totals for one information set.
+#[derive(Debug, Clone, PartialEq)]
+struct RegretNode {
+    regrets: Vec<f64>,
+    strategy_sum: Vec<f64>,
+}
+
+/// Normalizes `values` into probabilities, or uniform when they sum to zero.
+fn normalize(values: impl Iterator<Item = f64>) -> Vec<f64> {
+    let values: Vec<f64> = values.collect();
+    let total: f64 = values.iter().sum();
+    if total > 0.0 {
+        values.iter().map(|v| v / total).collect()
+    } else {
+        vec![1.0 / values.len() as f64; values.len()]
+    }
+}
+
+impl RegretNode {
+    fn new(actions: usize) -> Self {
+        Self {
+            regrets: vec![0.0; actions],
+            strategy_sum: vec![0.0; actions],
+        }
+    }
+
+    /// Regret matching: play each action in proportion to its positive regret.
+    fn current_strategy(&self) -> Vec<f64> {
+        normalize(self.regrets.iter().map(|r| r.max(0.0)))
+    }
+
+    fn average_strategy(&self) -> Vec<f64> {
+        normalize(self.strategy_sum.iter().copied())
+    }
+}
+
+/// One sampled deal: every player's bucket on each street and the showdown
+/// result from player 0's point of view.
+struct Deal {
+    buckets: [[usize; STREETS]; 2],
+    showdown: Ordering,
+}
+
+impl Deal {
+    fn sample<R: Rng + ?Sized>(abstraction: &Abstraction, rng: &mut R) -> Self {
+        let mut deck = full_deck();
+        let (cards, _) = deck.partial_shuffle(rng, 4 + MAX_COMMUNITY_CARDS);
+        let holes = [[cards[0], cards[1]], [cards[2], cards[3]]];
+        let board = &cards[4..];
+        let mut buckets = [[0; STREETS]; 2];
+        for (player, hole) in holes.iter().enumerate() {
+            for (street, visible) in [0, 3, 4, 5].into_iter().enumerate() {
+                buckets[player][street] = abstraction.bucket(hole, &board[..visible], rng);
+            }
+        }
+        let hand = |hole: &HoleCards| {
+            let cards: Vec<Card> = hole.iter().chain(board).copied().collect();
+            evaluate_hand(&cards)
+        };
+        Self {
+            buckets,
+            showdown: hand(&holes[0]).cmp(&hand(&holes[1])),
+        }
+    }
+}
+
+/// Chance-sampled CFR trainer: every iteration deals one hand and walks the
+/// whole betting tree for it, updating one player's regrets.
+pub struct Trainer {
+    abstraction: Abstraction,
+    variant: CfrVariant,
+    nodes: HashMap<String, RegretNode>,
+    iterations: u64,
+}
+
+impl Trainer {
+    pub fn new(abstraction: Abstraction, variant: CfrVariant) -> Self {
+        Self {
+            abstraction,
+            variant,
+            nodes: HashMap::new(),
+            iterations: 0,
+        }
+    }
+
+    /// Runs `iterations` more training iterations.
+    pub fn run<R: Rng + ?Sized>(&mut self, iterations: u64, rng: &mut R) {
+        for _ in 0..iterations {
+            self.iterations += 1;
+            let deal = Deal::sample(&self.abstraction, rng);
+            let traverser = (self.iterations % 2) as usize;
+            let weight = match self.variant {
+                CfrVariant::Vanilla => 1.0,
+                CfrVariant::Plus => self.iterations as f64,
+            };
+            let root = Node::Decision(Betting::new(&self.abstraction));
+            self.traverse(root, &deal, traverser, [1.0; 2], weight);
+        }
+    }
+
+    /// Returns the expected value of `node` for `traverser` and updates the
+    /// traverser's regrets along the way.
+    fn traverse(
+        &mut self,
+        node: Node,
+        deal: &Deal,
+        traverser: usize,
+        reach: [f64; 2],
+        weight: f64,
+    ) -> f64 {
+        let state = match node {
+            Node::Decision(state) => state,
+            terminal => return terminal.payoff(traverser, deal.showdown),
+        };
+        let player = state.to_act;
+        let key = info_set_key(deal.buckets[player][state.street], &state.history);
+        let actions = state.legal_actions(&self.abstraction);
+        let strategy = self
+            .nodes
+            .entry(key.clone())
+            .or_insert_with(|| RegretNode::new(actions.len()))
+            .current_strategy();
+
+        let mut utilities = vec![0.0; actions.len()];
+        let mut node_utility = 0.0;
+        for (i, &action) in actions.iter().enumerate() {
+            if player != traverser && strategy[i] == 0.0 {
+                continue;
+            }
+            let mut child_reach = reach;
+            child_reach[player] *= strategy[i];
+            let child = state.apply(action, &self.abstraction);
+            utilities[i] = self.traverse(child, deal, traverser, child_reach, weight);
+            node_utility += strategy[i] * utilities[i];
+        }
+
+        if player == traverser {
+            let node = self.nodes.get_mut(&key).expect("node was inserted above");
+            for i in 0..actions.len() {
+                let regret = node.regrets[i] + reach[1 - player] * (utilities[i] - node_utility);
+                node.regrets[i] = match self.variant {
+                    CfrVariant::Vanilla => regret,
+                    CfrVariant::Plus => regret.max(0.0),
+                };
+                node.strategy_sum[i] += weight * reach[player] * strategy[i];
+            }
+        }
+        node_utility
+    }
+
+    /// The average strategy so far, rounded to four decimals.
+    pub fn strategy_table(&self) -> StrategyTable {
+        let strategies = self
+            .nodes
+            .iter()
+            .map(|(key, node)| {
+                let average = node.average_strategy();
+                let rounded = average.iter().map(|p| (p * 1e4).round() / 1e4).collect();
+                (key.clone(), rounded)
+            })
+            .collect();
+        StrategyTable {
+            variant: self.variant,
+            iterations: self.iterations,
+            abstraction: self.abstraction.clone(),
+            strategies,
+        }
+    }
+}
+
+/// Error returned when a strategy file cannot be read or written.
+#[derive(Debug)]
+pub enum StrategyFileError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for StrategyFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StrategyFileError::Io(err) => write!(f, "cannot access strategy file: {}", err),
+            StrategyFileError::Parse(err) => write!(f, "invalid strategy file: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for StrategyFileError {}
+
+/// A trained strategy: for every information set, the probability of each
+/// legal action in the order the abstract game lists them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyTable {
+    pub variant: CfrVariant,
+    pub iterations: u64,
+    pub abstraction: Abstraction,
+    pub strategies: BTreeMap<String, Vec<f64>>,
+}
+
+impl StrategyTable {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, StrategyFileError> {
+        let json = std::fs::read_to_string(path).map_err(StrategyFileError::Io)?;
+        serde_json::from_str(&json).map_err(StrategyFileError::Parse)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), StrategyFileError> {
+        let json = serde_json::to_string(self).map_err(StrategyFileError::Parse)?;
+        std::fs::write(path, json).map_err(StrategyFileError::Io)
+    }
+
+    /// Action probabilities for `bucket` after `history`.
+    fn strategy(&self, bucket: usize, history: &str) -> Option<&[f64]> {
+        self.strategies
+            .get(&info_set_key(bucket, history))
+            .map(Vec::as_slice)
+    }
+
+    /// Rebuilds the abstract betting state from the hand so far, or `None`
+    /// if the hand has left the abstract game (a different bet size, or more
+    /// raises than the abstraction allows, or a table that is not heads-up).
+    fn replay(&self, view: &PlayerView) -> Option<Betting> {
+        let abstraction = &self.abstraction;
+        if view.stacks.len() != HEADS_UP_SEATS
+            || view.big_blind != abstraction.big_blind
+            || view.bet_amount != abstraction.bet_amount
+            || view.raise_amount != abstraction.raise_amount
+        {
+            return None;
+        }
+        let player = |seat: SeatIndex| usize::from(seat == view.dealer_position);
+        let mut state = Betting::new(abstraction);
+        for record in &view.history {
+            let in_turn = player(record.seat) == state.to_act
+                && street_index(record.round) == Some(state.street);
+            if !in_turn || !state.legal_actions(abstraction).contains(&record.action) {
+                return None;
+            }
+            match state.apply(record.action, abstraction) {
+                Node::Decision(next) => state = next,
+                _ => return None,
+            }
+        }
+        let in_turn =
+            player(view.seat) == state.to_act && street_index(view.round) == Some(state.street);
+        in_turn.then_some(state)
+    }
+}
+
+/// Plays the average strategy of a [`StrategyTable`]. Spots the table does
+/// not cover are handed to a threshold bot with the seat's profile.
+pub struct CfrAgent {
+    table: StrategyTable,
+    fallback: ThresholdAgent,
+    last: Option<Explanation>,
+}
+
+impl CfrAgent {
+    pub fn new(table: StrategyTable, profile: AiProfile) -> Self {
+        Self {
+            table,
+            fallback: ThresholdAgent::new(profile),
+            last: None,
+        }
+    }
+
+    /// Samples an action from the table, if it covers this spot, along with
+    /// the explanation of the draw.
+    fn table_action(
+        &self,
+        view: &PlayerView,
+        rng: &mut dyn RngCore,
+    ) -> Option<(PokerAction, Explanation)> {
+        let state = self.table.replay(view)?;
+        let abstraction = &self.table.abstraction;
+        let bucket = abstraction.bucket(&view.hole_cards, &view.board, rng);
+        let probabilities = self.table.strategy(bucket, &state.history)?;
+        let actions = state.legal_actions(abstraction);
+        if probabilities.len() != actions.len() {
+            return None;
+        }
+        let explain = |probability: f64| {
+            Explanation::new(DecisionBasis::Table {
+                source: format!("{} table, bucket {}", self.table.variant, bucket),
+                probability,
+            })
+        };
+        let mut roll: f64 = rng.gen();
+        for (&action, &probability) in actions.iter().zip(probabilities) {
+            if roll < probability {
+                return Some((action, explain(probability)));
+            }
+            roll -= probability;
+        }
+        let last = *actions.last()?;
+        Some((last, explain(*probabilities.last()?)))
+    }
+}
+
+impl PokerAgent for CfrAgent {
+    fn name(&self) -> &str {
+        "CFR"
+    }
+
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        actions: &[PokerAction],
+        rng: &mut dyn RngCore,
+    ) -> PokerAction {
+        match self.table_action(view, rng) {
+            Some((action, explanation)) if actions.contains(&action) => {
+                self.last = Some(explanation);
+                action
+            }
+            _ => {
+                let action = self.fallback.choose_action(view, actions, rng);
+                self.last = self.fallback.explain().map(|mut explanation| {
+                    explanation
+                        .notes
+                        .push("spot not in the strategy table".to_string());
+                    explanation
+                });
+                action
+            }
+        }
+    }
+
+    fn explain(&self) -> Option<Explanation> {
+        self.last.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{perform_validated_action, post_blinds, GameStateResource};
+    use crate::poker_logic::{parse_cards, Deck};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn abstraction(buckets: usize) -> Abstraction {
+        let mut rng = StdRng::seed_from_u64(37);
+        Abstraction::build(&GameConfig::default(), buckets, 16, 40, &mut rng)
+    }
+
+    fn decision(node: Node) -> Betting {
+        match node {
+            Node::Decision(state) => state,
+            other => panic!("expected a decision, got {:?}", other),
+        }
+    }
+
+    fn trained(iterations: u64) -> StrategyTable {
+        let mut rng = StdRng::seed_from_u64(37);
+        let mut trainer = Trainer::new(abstraction(3), CfrVariant::Plus);
+        trainer.run(iterations, &mut rng);
+        trainer.strategy_table()
+    }
+
+    #[test]
+    fn test_preflop_buckets_cover_all_hands() {
+        let abstraction = abstraction(4);
+        assert_eq!(abstraction.preflop.len(), 169);
+        assert_eq!(abstraction.preflop["AA"], 3);
+        assert_eq!(abstraction.preflop["72o"], 0);
+        let hole = parse_cards("Kd Ac").unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        let bucket = abstraction.bucket(&[hole[0], hole[1]], &[], &mut rng);
+        assert_eq!(bucket, abstraction.preflop["AKo"]);
+    }
+
+    #[test]
+    fn test_betting_follows_engine_order_and_cap() {
+        let abstraction = abstraction(2);
+        let root = Betting::new(&abstraction);
+        assert_eq!(root.to_act, 1);
+        assert_eq!(
+            root.legal_actions(&abstraction),
+            [PokerAction::Fold, PokerAction::Call, PokerAction::Raise]
+        );
+        let completed = decision(root.apply(PokerAction::Call, &abstraction));
+        assert_eq!((completed.street, completed.to_act), (0, 0));
+        assert_eq!(
+            completed.legal_actions(&abstraction),
+            [PokerAction::Check, PokerAction::Raise]
+        );
+        let flop = decision(completed.apply(PokerAction::Check, &abstraction));
+        assert_eq!((flop.street, flop.to_act), (1, 0));
+        assert_eq!(flop.history, "ck/");
+
+        let bet = decision(flop.apply(PokerAction::Bet, &abstraction));
+        let raised = decision(bet.apply(PokerAction::Raise, &abstraction));
+        assert_eq!(raised.contributions, [100, 200]);
+        assert_eq!(
+            raised.legal_actions(&abstraction),
+            [PokerAction::Fold, PokerAction::Call]
+        );
+        let folded = raised.apply(PokerAction::Fold, &abstraction);
+        assert_eq!(folded.payoff(0, Ordering::Greater), -100.0);
+        assert_eq!(folded.payoff(1, Ordering::Greater), 100.0);
+    }
+
+    #[test]
+    fn test_regret_matching() {
+        let node = RegretNode {
+            regrets: vec![1.0, 3.0, -2.0],
+            strategy_sum: vec![0.0; 3],
+        };
+        assert_eq!(node.current_strategy(), [0.25, 0.75, 0.0]);
+        assert_eq!(node.average_strategy(), [1.0 / 3.0; 3]);
+    }
+
+    #[test]
+    fn test_strong_hands_fold_less_on_the_river() {
+        let table = trained(400);
+        let fold_to_bet = |bucket| {
+            let folds: Vec<f64> = table
+                .strategies
+                .iter()
+                .filter(|(key, _)| key.starts_with(&format!("{}:", bucket)))
+                .filter(|(key, _)| key.matches('/').count() == 3 && key.ends_with('b'))
+                .map(|(_, probabilities)| probabilities[0])
+                .collect();
+            folds.iter().sum::<f64>() / folds.len() as f64
+        };
+        assert!(fold_to_bet(2) < fold_to_bet(0));
+        for probabilities in table.strategies.values() {
+            assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_strategy_table_round_trip() {
+        let table = trained(20);
+        let path = std::env::temp_dir().join("poker_simulation_cfr_test.json");
+        table.save(&path).unwrap();
+        assert_eq!(StrategyTable::load(&path).unwrap(), table);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            StrategyTable::load("does/not/exist.json"),
+            Err(StrategyFileError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_cfr_agent_plays_legal_actions() {
+        let config = GameConfig::default();
+        let table = trained(50);
+        let mut from_table = 0;
+        for seed in 0..5 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut game_state = GameStateResource::with_stacks(vec![1000; HEADS_UP_SEATS]);
+            post_blinds(&mut game_state, &config);
+            game_state.deck = Deck::with_rng(&mut rng);
+            let mut draw = || game_state.deck.draw().unwrap();
+            let cards: Vec<_> = (0..9).map(|_| draw()).collect();
+            game_state.hole_cards[0] = [cards[0], cards[1]];
+            game_state.hole_cards[1] = [cards[2], cards[3]];
+            game_state.community_cards.copy_from_slice(&cards[4..]);
+
+            let mut agents = [
+                CfrAgent::new(table.clone(), AiProfile::default()),
+                CfrAgent::new(table.clone(), AiProfile::default()),
+            ];
+            while game_state.current_round != PokerRound::Showdown && game_state.winner.is_none() {
+                let seat = game_state.current_player;
+                perform_validated_action(&mut game_state, &config, &mut agents[seat], &mut rng);
+                // The engine notes any illegal choice it had to replace
+                let explanation = game_state.last_explanations[seat]
+                    .as_ref()
+                    .expect("the decision was explained");
+                assert!(
+                    !explanation
+                        .notes
+                        .iter()
+                        .any(|note| note.starts_with("illegal")),
+                    "{}",
+                    explanation
+                );
+                let basis = &explanation.basis;
+                from_table += usize::from(matches!(basis, DecisionBasis::Table { .. }));
+            }
+        }
+        assert!(
+            from_table > 0,
+            "every decision fell back to the threshold bot"
+        );
+    }
+}
@@ -0,0 +1,732 @@
+//! Command-line tools that run without opening the game window.
+//!
+//! Invoked as `poker_simulation <command> [args...]`; with no arguments the
+//! game starts normally.
+
+use crate::agent::{build_agent, SeatAgents};
+use crate::arena::Arena;
+use crate::cash::{Bankroll, CashGame};
+use crate::cfr::{Abstraction, CfrVariant, Trainer};
+use crate::constants::*;
+use crate::equity::{equity_with_rng, EquityResult};
+use crate::game::GameConfig;
+use crate::icm::PayoutConfig;
+use crate::net::{Server, ServerSettings};
+use crate::poker_logic::{evaluate_hand, format_cards, parse_cards, Card, HoleCards};
+use crate::profile::{
+    load_profiles, save_profiles, upsert_profile, AiConfig, AiProfile, Difficulty, SeatSetup,
+};
+use crate::pushfold::{format_chart, range_share, HandMatrix, PushFoldCharts};
+use crate::range::{range_equity, Range};
+use crate::tournament::{ordinal, simulate, BlindSchedule, LevelClock, Tournament};
+use crate::tuner::{Tuner, TunerSettings, PARAMETERS};
+use rand::{rngs::StdRng, thread_rng, RngCore, SeedableRng};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const USAGE: &str = "\
+Usage: poker_simulation [command]
+
+Commands:
+  play [--seats N] [--tournament hands:N|minutes:M] [--stack N] [--payouts NAME]
+       [--cash BUY_IN] [--top-up on|off]
+                 Open the game window with N AI seats (2-9, default 2). With
+                 --tournament a sit-and-go is played from --stack chips (default
+                 6 seats), the blinds rising every N hands or M minutes and the
+                 prizes paid by the named structure in config/payouts.json.
+                 With --cash each player buys in from their saved bankroll,
+                 tops up to the buy-in between hands unless --top-up is off,
+                 and cashes out when the window closes
+  eval <cards>   Evaluate the best hand from 5-7 cards, e.g. eval AhKh Qs Jd Tc
+  equity <hand|range> <hand|range>... [--board CARDS] [--dead CARDS] [--samples N] [--seed N]
+                 Win/tie/lose percentages, e.g. equity AhKh QsQd --board Js9s2c
+                 or between ranges, e.g. equity \"QQ+, AKs\" \"22+, A2s+, KTo+\"
+  train-cfr [--iterations N] [--buckets N] [--samples N] [--variant cfr|cfr+] [--seed N] [--out PATH]
+                 Train CFR strategy tables for heads-up limit play and save them as JSON
+  push-fold [--stack BB] [--samples N] [--iterations N] [--seed N] [--out PATH]
+                 Heads-up push/fold equilibrium charts; --out saves charts for every
+                 depth up to the maximum, otherwise the chart for --stack is printed
+  arena [--entries PROFILE:TIER,...] [--deals N] [--seed N]
+                 Round robin of duplicate-dealt matches between AI entries with
+                 mb/hand win rates and Elo ratings; by default every difficulty
+                 tier plays the Balanced profile
+  tune [--generations N] [--population N] [--deals N] [--base PROFILE] [--name NAME] [--seed N] [--out PATH]
+                 Evolve the threshold bot's parameters against the configured profiles
+                 and save the best set as a profile the game and arena can load
+  serve [--bind ADDR] [--hands N] [--opponent PROFILE:TIER] [--spectator-delay SECS]
+        [--action-time SECS] [--time-bank SECS] [--seed N]
+                 Host a heads-up table for players connecting over TCP; with
+                 --opponent the second seat is played by that AI entry. With a
+                 spectator delay, spectators follow that many seconds late and see
+                 the hole cards of every hand that is over.
+                 Players have the action time for each decision plus a time bank
+                 for the match; a dropped player may reconnect and carry on
+  tournament [--seats N] [--levels hands:N|minutes:M] [--stack N] [--payouts NAME]
+             [--entries PROFILE:TIER,...] [--seed N]
+                 Play a sit-and-go between AI seats without a window and print
+                 the finishing order and prizes; by default 6 seats with the
+                 configured setups
+  bankroll [--sessions N]
+                 Show each player's cash game bankroll and latest sessions
+  connect [ADDR] [--spectate]
+                 Open the game window as a player at a served table, or as a
+                 spectator (default 127.0.0.1:7878)
+  help           Show this message
+
+With no command the game window opens.";
+
+/// Runs the command described by `args` and returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => ("help", args),
+    };
+
+    let result = match command {
+        "eval" => eval_command(rest),
+        "equity" => equity_command(rest),
+        "train-cfr" => train_cfr_command(rest),
+        "push-fold" => push_fold_command(rest),
+        "arena" => arena_command(rest),
+        "tune" => tune_command(rest),
+        "serve" => serve_command(rest),
+        "tournament" => tournament_command(rest),
+        "bankroll" => bankroll_command(rest),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(format!("unknown command '{}'\n\n{}", other, USAGE)),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            1
+        }
+    }
+}
+
+/// Parses `--seats`, falling back to `default`.
+fn seats_option(options: &HashMap<String, String>, default: usize) -> Result<usize, String> {
+    let seats = numeric_option(options, "seats", default)?;
+    if !(MIN_SEATS..=MAX_SEATS).contains(&seats) {
+        return Err(format!(
+            "--seats must be between {} and {}",
+            MIN_SEATS, MAX_SEATS
+        ));
+    }
+    Ok(seats)
+}
+
+/// What the game window plays.
+pub enum PlayMode {
+    /// AI seats play on, every stack reset once one player has all the chips
+    Match,
+    Tournament(Tournament),
+    Cash(CashGame),
+}
+
+/// The game configuration for the `play` arguments, and what to play: a
+/// tournament when `--tournament` is given, a cash game with `--cash`.
+pub fn play_config(args: &[String]) -> Result<(GameConfig, PlayMode), String> {
+    let (_, options) = parse_options(args)?;
+    if options.contains_key("tournament") && options.contains_key("cash") {
+        return Err("choose either --tournament or --cash".to_string());
+    }
+    if let Some(buy_in) = options.get("cash") {
+        return cash_config(&options, buy_in);
+    }
+    let Some(clock) = options.get("tournament") else {
+        let seats = seats_option(&options, HEADS_UP_SEATS)?;
+        return Ok((GameConfig::with_seat_count(seats), PlayMode::Match));
+    };
+    let schedule = BlindSchedule::standard(LevelClock::parse(clock)?);
+    let mut config = GameConfig::with_seat_count(seats_option(&options, TOURNAMENT_SEATS)?);
+    config.starting_chips = stack_option(&options)?;
+    let payouts = payouts_option(&options, config.seats.len())?;
+    Ok((
+        config,
+        PlayMode::Tournament(Tournament::new(schedule).with_payouts(payouts)),
+    ))
+}
+
+/// A cash game for `play --cash BUY_IN`, drawing on the saved bankrolls.
+fn cash_config(
+    options: &HashMap<String, String>,
+    buy_in: &str,
+) -> Result<(GameConfig, PlayMode), String> {
+    let buy_in: u32 = match buy_in.parse() {
+        Ok(buy_in) if buy_in > 0 => buy_in,
+        _ => return Err(format!("invalid buy-in '{}' for --cash", buy_in)),
+    };
+    let top_up = match options.get("top-up").map(String::as_str) {
+        None | Some("on") => true,
+        Some("off") => false,
+        Some(other) => return Err(format!("expected on or off for --top-up, got '{}'", other)),
+    };
+    let config = GameConfig::with_seat_count(seats_option(options, HEADS_UP_SEATS)?);
+    let bankroll = Bankroll::load_or_new(CASH_BANKROLL_PATH).map_err(|e| e.to_string())?;
+    let mut cash = CashGame::new(bankroll, CASH_BANKROLL_PATH, config.seats.len(), buy_in);
+    cash.top_up = top_up;
+    Ok((config, PlayMode::Cash(cash)))
+}
+
+/// Parses the tournament's `--stack`, which must be at least one chip.
+fn stack_option(options: &HashMap<String, String>) -> Result<u32, String> {
+    match numeric_option(options, "stack", TOURNAMENT_STARTING_STACK)? {
+        0 => Err("--stack must be at least 1".to_string()),
+        stack => Ok(stack),
+    }
+}
+
+/// The prizes of the `--payouts` structure for a field of `entrants`.
+fn payouts_option(options: &HashMap<String, String>, entrants: usize) -> Result<Vec<f64>, String> {
+    let name = options
+        .get("payouts")
+        .map_or(TOURNAMENT_DEFAULT_PAYOUTS, String::as_str);
+    let config = PayoutConfig::load_or_built_in(TOURNAMENT_PAYOUTS_PATH);
+    let structure = config.structure(name).ok_or_else(|| {
+        let names: Vec<&str> = config.structures.iter().map(|s| s.name.as_str()).collect();
+        format!(
+            "unknown payout structure '{}' (choose from {})",
+            name,
+            names.join(", ")
+        )
+    })?;
+    Ok(structure.payouts(entrants))
+}
+
+/// Prints the hand category and tie-break values of the given cards.
+fn eval_command(args: &[String]) -> Result<(), String> {
+    let cards = parse_cards(&args.join(" ")).map_err(|e| e.to_string())?;
+    if !(5..=7).contains(&cards.len()) {
+        return Err(format!("expected 5 to 7 cards, got {}", cards.len()));
+    }
+
+    let evaluated = evaluate_hand(&cards);
+    let values: Vec<char> = evaluated
+        .primary_values
+        .iter()
+        .chain(evaluated.kickers.iter())
+        .map(|rank| rank.to_char())
+        .collect();
+    println!(
+        "{}: {:?} ({})",
+        format_cards(&cards),
+        evaluated.hand_rank,
+        values.iter().collect::<String>()
+    );
+    Ok(())
+}
+
+/// Splits arguments into positional values and `--flag value` options.
+fn parse_options(args: &[String]) -> Result<(Vec<String>, HashMap<String, String>), String> {
+    let mut positional = Vec::new();
+    let mut options = HashMap::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(flag) = arg.strip_prefix("--") {
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("missing value for --{}", flag))?;
+            options.insert(flag.to_string(), value.clone());
+        } else {
+            positional.push(arg.clone());
+        }
+    }
+    Ok((positional, options))
+}
+
+/// Parses an optional numeric `--flag`, falling back to `default`.
+fn numeric_option<T: std::str::FromStr>(
+    options: &HashMap<String, String>,
+    flag: &str,
+    default: T,
+) -> Result<T, String> {
+    match options.get(flag) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("invalid value '{}' for --{}", value, flag)),
+        None => Ok(default),
+    }
+}
+
+/// Parses an optional `--flag` holding a number of seconds.
+fn seconds_option(
+    options: &HashMap<String, String>,
+    flag: &str,
+    default: Duration,
+) -> Result<Duration, String> {
+    let seconds = numeric_option(options, flag, default.as_secs_f64())?;
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| format!("invalid value '{}' for --{}", seconds, flag))
+}
+
+/// Parses an optional `--flag` holding a card list, empty when absent.
+fn cards_option(options: &HashMap<String, String>, flag: &str) -> Result<Vec<Card>, String> {
+    match options.get(flag) {
+        Some(value) => parse_cards(value).map_err(|e| format!("--{}: {}", flag, e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Returns a seeded RNG when `--seed` is given, otherwise the thread RNG.
+fn rng_option(options: &HashMap<String, String>) -> Result<Box<dyn RngCore>, String> {
+    Ok(match options.get("seed") {
+        Some(_) => Box::new(StdRng::seed_from_u64(numeric_option(options, "seed", 0)?)),
+        None => Box::new(thread_rng()),
+    })
+}
+
+/// Parses a two-card hand such as "AhKh".
+fn parse_hole_cards(s: &str) -> Result<HoleCards, String> {
+    match parse_cards(s).map_err(|e| e.to_string())?.as_slice() {
+        [first, second] => Ok([*first, *second]),
+        _ => Err(format!("'{}' is not a two-card hand", s)),
+    }
+}
+
+/// Prints the equity of two or more hands, or of ranges when any argument is one.
+fn equity_command(args: &[String]) -> Result<(), String> {
+    let (positional, options) = parse_options(args)?;
+    let board = cards_option(&options, "board")?;
+    let dead = cards_option(&options, "dead")?;
+    let samples = numeric_option(&options, "samples", EQUITY_DEFAULT_SAMPLES)?;
+    let mut rng = rng_option(&options)?;
+
+    let hands: Result<Vec<HoleCards>, String> =
+        positional.iter().map(|s| parse_hole_cards(s)).collect();
+    if let Ok(hands) = hands {
+        let result =
+            equity_with_rng(&hands, &board, &dead, samples, &mut rng).map_err(|e| e.to_string())?;
+        print_equity(&positional, &board, &result);
+        return Ok(());
+    }
+
+    let ranges: Vec<Range> = positional
+        .iter()
+        .map(|s| parse_range_or_hand(s))
+        .collect::<Result<_, String>>()?;
+    let labels: Vec<String> = positional
+        .iter()
+        .zip(&ranges)
+        .map(|(label, range)| format!("{} [{} combos]", label, range.combos().len()))
+        .collect();
+    let result =
+        range_equity(&ranges, &board, &dead, samples, &mut rng).map_err(|e| e.to_string())?;
+    print_equity(&labels, &board, &result);
+    Ok(())
+}
+
+/// Parses an exact two-card hand, or failing that range notation.
+fn parse_range_or_hand(s: &str) -> Result<Range, String> {
+    match parse_hole_cards(s) {
+        Ok(hand) => Ok(Range::from_hand(hand)),
+        Err(_) => s.parse().map_err(|e| format!("'{}': {}", s, e)),
+    }
+}
+
+fn print_equity(labels: &[String], board: &[Card], result: &EquityResult) {
+    let method = if result.exhaustive {
+        "exhaustive"
+    } else {
+        "sampled"
+    };
+    let board_text = if board.is_empty() {
+        "-".to_string()
+    } else {
+        format_cards(board)
+    };
+    println!(
+        "Board: {} ({}, {} runouts)",
+        board_text, method, result.trials
+    );
+    for (label, eq) in labels.iter().zip(&result.equities) {
+        println!(
+            "{:<6} win {:>6.2}%  tie {:>6.2}%  lose {:>6.2}%  equity {:>6.2}%",
+            label,
+            eq.win_pct(),
+            eq.tie_pct(),
+            eq.lose_pct(),
+            eq.share() * 100.0
+        );
+    }
+}
+
+/// Trains CFR strategy tables and writes them to `--out`.
+fn train_cfr_command(args: &[String]) -> Result<(), String> {
+    let (positional, options) = parse_options(args)?;
+    if let Some(extra) = positional.first() {
+        return Err(format!("unexpected argument '{}'", extra));
+    }
+    let iterations = numeric_option(&options, "iterations", AI_CFR_ITERATIONS)?;
+    let buckets = numeric_option(&options, "buckets", AI_CFR_BUCKETS)?;
+    if buckets == 0 {
+        return Err("--buckets must be at least 1".to_string());
+    }
+    let samples = numeric_option(&options, "samples", AI_CFR_BUCKET_SAMPLES)?;
+    let variant = match options.get("variant").map(String::as_str) {
+        None | Some("cfr+") => CfrVariant::Plus,
+        Some("cfr") => CfrVariant::Vanilla,
+        Some(other) => return Err(format!("unknown variant '{}' (cfr or cfr+)", other)),
+    };
+    let out = options
+        .get("out")
+        .map_or(AI_CFR_STRATEGY_PATH, String::as_str);
+    let mut rng = rng_option(&options)?;
+
+    let abstraction = Abstraction::build(
+        &GameConfig::default(),
+        buckets,
+        samples,
+        AI_CFR_PREFLOP_SAMPLES,
+        &mut rng,
+    );
+    let mut trainer = Trainer::new(abstraction, variant);
+    let chunk = (iterations / 10).max(1);
+    let mut done = 0;
+    while done < iterations {
+        let step = chunk.min(iterations - done);
+        trainer.run(step, &mut rng);
+        done += step;
+        println!("{} {}/{} iterations", variant, done, iterations);
+    }
+
+    let table = trainer.strategy_table();
+    table.save(out).map_err(|e| e.to_string())?;
+    println!(
+        "Saved {} information sets to {}",
+        table.strategies.len(),
+        out
+    );
+    Ok(())
+}
+
+/// Solves push/fold charts, printing one stack depth or saving them all.
+fn push_fold_command(args: &[String]) -> Result<(), String> {
+    let (positional, options) = parse_options(args)?;
+    if let Some(extra) = positional.first() {
+        return Err(format!("unexpected argument '{}'", extra));
+    }
+    let stack_bb: f64 = numeric_option(&options, "stack", AI_PUSH_FOLD_DEFAULT_STACK_BB)?;
+    if stack_bb <= 0.0 {
+        return Err("--stack must be positive".to_string());
+    }
+    let samples = numeric_option(&options, "samples", AI_PUSH_FOLD_SAMPLES)?;
+    let iterations = numeric_option(&options, "iterations", AI_PUSH_FOLD_ITERATIONS)?;
+    let mut rng = rng_option(&options)?;
+
+    let matrix = HandMatrix::build(samples, &mut rng);
+    if let Some(out) = options.get("out") {
+        let charts = PushFoldCharts {
+            charts: (1..=AI_PUSH_FOLD_MAX_BB)
+                .map(|depth| matrix.solve(f64::from(depth), iterations))
+                .collect(),
+        };
+        charts.save(out).map_err(|e| e.to_string())?;
+        println!("Saved {} push/fold charts to {}", charts.charts.len(), out);
+        if !options.contains_key("stack") {
+            return Ok(());
+        }
+    }
+
+    let chart = matrix.solve(stack_bb, iterations);
+    println!(
+        "Small blind push, {} BB ({:.1}% of hands):",
+        stack_bb,
+        range_share(&chart.push) * 100.0
+    );
+    println!("{}\n", format_chart(&chart.push));
+    println!(
+        "Big blind call, {} BB ({:.1}% of hands):",
+        stack_bb,
+        range_share(&chart.call) * 100.0
+    );
+    println!("{}", format_chart(&chart.call));
+    Ok(())
+}
+
+/// Splits a `PROFILE:TIER` AI entry into its profile and difficulty names.
+fn parse_entry(entry: &str) -> Result<(String, String), String> {
+    match entry.split_once(':') {
+        Some((profile, tier)) => Ok((profile.trim().to_string(), tier.trim().to_string())),
+        None => Err(format!("expected PROFILE:TIER, got '{}'", entry.trim())),
+    }
+}
+
+/// Looks up the profile and difficulty of an AI entry.
+fn lookup_entry(
+    ai_config: &AiConfig,
+    profile_name: &str,
+    tier_name: &str,
+) -> Result<(AiProfile, Difficulty), String> {
+    let profile = ai_config
+        .profile(profile_name)
+        .cloned()
+        .ok_or_else(|| format!("unknown profile '{}'", profile_name))?;
+    let difficulty = ai_config
+        .difficulty(tier_name)
+        .cloned()
+        .ok_or_else(|| format!("unknown difficulty '{}'", tier_name))?;
+    Ok((profile, difficulty))
+}
+
+/// Plays a round robin between AI entries and prints the leaderboard.
+fn arena_command(args: &[String]) -> Result<(), String> {
+    let (positional, options) = parse_options(args)?;
+    if let Some(extra) = positional.first() {
+        return Err(format!("unexpected argument '{}'", extra));
+    }
+    let deals = numeric_option(&options, "deals", ARENA_DEALS)?;
+    if deals == 0 {
+        return Err("--deals must be at least 1".to_string());
+    }
+    let ai_config = AiConfig::load_with_tuned(AI_PROFILES_PATH, AI_TUNED_PROFILES_PATH);
+    let entries: Vec<(String, String)> = match options.get("entries") {
+        Some(list) => list.split(',').map(parse_entry).collect::<Result<_, _>>()?,
+        None => ai_config
+            .difficulties
+            .iter()
+            .map(|tier| ("Balanced".to_string(), tier.name.clone()))
+            .collect(),
+    };
+    if entries.len() < 2 {
+        return Err("the arena needs at least two entries".to_string());
+    }
+    let mut rng = rng_option(&options)?;
+
+    let config = GameConfig::default();
+    let mut arena = Arena::new(config);
+    for (profile_name, tier_name) in entries {
+        let (profile, difficulty) = lookup_entry(&ai_config, &profile_name, &tier_name)?;
+        let name = format!("{}/{}", profile.name, difficulty.name);
+        arena.register(name, Box::new(move || build_agent(&profile, &difficulty)));
+    }
+
+    let report = arena.round_robin(deals, &mut rng, |result| {
+        println!(
+            "{} vs {}: {} hands played",
+            arena.name(result.first),
+            arena.name(result.second),
+            result.hands()
+        );
+    });
+    print!("\n{}", report);
+    Ok(())
+}
+
+/// Plays a sit-and-go between AI seats and prints the finishing order.
+fn tournament_command(args: &[String]) -> Result<(), String> {
+    let (positional, options) = parse_options(args)?;
+    if let Some(extra) = positional.first() {
+        return Err(format!("unexpected argument '{}'", extra));
+    }
+    let ai_config = AiConfig::load_with_tuned(AI_PROFILES_PATH, AI_TUNED_PROFILES_PATH);
+    let mut config = match options.get("entries") {
+        Some(list) => {
+            let entries: Vec<(String, String)> =
+                list.split(',').map(parse_entry).collect::<Result<_, _>>()?;
+            let mut config = GameConfig::with_seat_count(entries.len());
+            for (seat, (profile_name, tier_name)) in entries.iter().enumerate() {
+                lookup_entry(&ai_config, profile_name, tier_name)?;
+                config.seats[seat] = SeatSetup::new(profile_name, tier_name);
+            }
+            config
+        }
+        None => GameConfig::with_seat_count(seats_option(&options, TOURNAMENT_SEATS)?),
+    };
+    if !(MIN_SEATS..=MAX_SEATS).contains(&config.seats.len()) {
+        return Err(format!(
+            "a tournament needs between {} and {} entries",
+            MIN_SEATS, MAX_SEATS
+        ));
+    }
+    config.starting_chips = stack_option(&options)?;
+    let clock = match options.get("levels") {
+        Some(clock) => LevelClock::parse(clock)?,
+        None => LevelClock::Hands(TOURNAMENT_HANDS_PER_LEVEL),
+    };
+    let payouts = payouts_option(&options, config.seats.len())?;
+    let mut rng = rng_option(&options)?;
+
+    let mut tournament = Tournament::new(BlindSchedule::standard(clock)).with_payouts(payouts);
+    let mut agents = SeatAgents::from_config(&config, &ai_config);
+    let standings = simulate(
+        &mut config,
+        &mut tournament,
+        &mut agents,
+        &mut rng,
+        |event| println!("{}", event),
+    );
+    println!("\nFinishing order:");
+    for (place, &seat) in standings.iter().enumerate() {
+        let setup = &config.seats[seat];
+        let prize = match tournament.payouts.get(place) {
+            Some(share) => format!("  {:.0}%", share * 100.0),
+            None => String::new(),
+        };
+        println!(
+            "{:>5}  P{} ({}/{}){}",
+            ordinal(place + 1),
+            seat + 1,
+            setup.profile,
+            setup.difficulty,
+            prize
+        );
+    }
+    Ok(())
+}
+
+/// Prints each player's bankroll and their latest cash game sessions.
+fn bankroll_command(args: &[String]) -> Result<(), String> {
+    let (positional, options) = parse_options(args)?;
+    if let Some(extra) = positional.first() {
+        return Err(format!("unexpected argument '{}'", extra));
+    }
+    let shown = numeric_option(&options, "sessions", CASH_HISTORY_SESSIONS)?;
+    let bankroll = Bankroll::load_or_new(CASH_BANKROLL_PATH).map_err(|e| e.to_string())?;
+    if bankroll.accounts.is_empty() {
+        println!("No cash game sessions yet; start one with play --cash BUY_IN");
+        return Ok(());
+    }
+    for (name, account) in &bankroll.accounts {
+        println!(
+            "{}: bankroll {}, {:+} over {} sessions",
+            name,
+            account.balance,
+            account.lifetime_profit(),
+            account.sessions.len()
+        );
+        println!(
+            "  {:>7}  {:>9}  {:>10}  {:>8}  {:>9}",
+            "hands", "bought in", "cashed out", "profit", "bankroll"
+        );
+        let skipped = account.sessions.len().saturating_sub(shown);
+        for session in &account.sessions[skipped..] {
+            println!(
+                "  {:>7}  {:>9}  {:>10}  {:>+8}  {:>9}",
+                session.hands,
+                session.bought_in,
+                session.cashed_out,
+                session.profit(),
+                session.balance
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Evolves a threshold profile and adds it to the tuned profiles file.
+fn tune_command(args: &[String]) -> Result<(), String> {
+    let (positional, options) = parse_options(args)?;
+    if let Some(extra) = positional.first() {
+        return Err(format!("unexpected argument '{}'", extra));
+    }
+    let defaults = TunerSettings::default();
+    let settings = TunerSettings {
+        generations: numeric_option(&options, "generations", defaults.generations)?,
+        population: numeric_option(&options, "population", defaults.population)?,
+        deals: numeric_option(&options, "deals", defaults.deals)?,
+        ..defaults
+    };
+    if settings.generations == 0 || settings.deals == 0 || settings.population < 2 {
+        return Err(
+            "--generations and --deals must be at least 1, --population at least 2".to_string(),
+        );
+    }
+    let ai_config = AiConfig::load_or_built_in(AI_PROFILES_PATH);
+    let base_name = options.get("base").map_or("Balanced", String::as_str);
+    let mut base = ai_config
+        .profile(base_name)
+        .cloned()
+        .ok_or_else(|| format!("unknown profile '{}'", base_name))?;
+    base.name = options
+        .get("name")
+        .map_or(TUNE_PROFILE_NAME, String::as_str)
+        .to_string();
+    let out = options
+        .get("out")
+        .map_or(AI_TUNED_PROFILES_PATH, String::as_str);
+    let mut rng = rng_option(&options)?;
+
+    let tuner = Tuner::new(base.clone(), ai_config.profiles.clone(), settings);
+    let (tuned, fitness) = tuner.run(&mut rng, |generation| {
+        println!(
+            "generation {}/{}: best {:+.1} mb/hand, mean {:+.1}",
+            generation.index + 1,
+            settings.generations,
+            generation.best,
+            generation.mean
+        );
+    });
+
+    println!(
+        "\n{} ({:+.1} mb/hand vs the baselines):",
+        tuned.name, fitness
+    );
+    for parameter in &PARAMETERS {
+        println!(
+            "  {:<28} {:.3} -> {:.3}",
+            parameter.name,
+            parameter.get(&base),
+            parameter.get(&tuned)
+        );
+    }
+    let mut profiles = if std::path::Path::new(out).exists() {
+        load_profiles(out).map_err(|e| e.to_string())?
+    } else {
+        Vec::new()
+    };
+    upsert_profile(&mut profiles, tuned);
+    save_profiles(out, &profiles).map_err(|e| e.to_string())?;
+    println!("Saved to {}", out);
+    Ok(())
+}
+
+/// Hosts a networked table until the hand limit or a player leaves.
+fn serve_command(args: &[String]) -> Result<(), String> {
+    let (positional, options) = parse_options(args)?;
+    if let Some(extra) = positional.first() {
+        return Err(format!("unexpected argument '{}'", extra));
+    }
+    let hands = match options.get("hands") {
+        Some(_) => Some(numeric_option(&options, "hands", 0)?),
+        None => None,
+    };
+    if hands == Some(0) {
+        return Err("--hands must be at least 1".to_string());
+    }
+    let opponent = match options.get("opponent") {
+        Some(entry) => {
+            let ai_config = AiConfig::load_with_tuned(AI_PROFILES_PATH, AI_TUNED_PROFILES_PATH);
+            let (profile, tier) = parse_entry(entry)?;
+            let (profile, difficulty) = lookup_entry(&ai_config, &profile, &tier)?;
+            Some(build_agent(&profile, &difficulty))
+        }
+        None => None,
+    };
+    let bind = options.get("bind").map_or(NET_DEFAULT_BIND, String::as_str);
+    let mut rng = rng_option(&options)?;
+
+    let spectator_delay = match options.get("spectator-delay") {
+        Some(_) => Some(seconds_option(&options, "spectator-delay", Duration::ZERO)?),
+        None => None,
+    };
+
+    let defaults = ServerSettings::default();
+    let settings = ServerSettings {
+        hands,
+        action_timeout: seconds_option(&options, "action-time", defaults.action_timeout)?,
+        time_bank: seconds_option(&options, "time-bank", defaults.time_bank)?,
+        spectator_delay,
+        ..defaults
+    };
+    let server = Server::bind(bind, GameConfig::default(), settings)
+        .map_err(|e| format!("cannot listen on {}: {}", bind, e))?;
+    server
+        .run([None, opponent], &mut rng, |event| println!("{}", event))
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -3,18 +3,21 @@
 //! This module contains all constant values used throughout the game,
 //! organized by category for easy maintenance.
 
-/// Number of players in the game (heads-up = 2)
-pub const PLAYER_COUNT: usize = 2;
+/// Seats at a heads-up table, the size the solver, push/fold charts, arena
+/// and networked play are built for
+pub const HEADS_UP_SEATS: usize = 2;
+/// Fewest seats a table can have
+pub const MIN_SEATS: usize = 2;
+/// Most seats a table can have (a full ring)
+pub const MAX_SEATS: usize = 9;
 
 // Font sizes for UI text elements
 /// Font size for the pot display
 pub const POT_FONT_SIZE: f32 = 22.0;
+/// Font size for the main and side pot list
+pub const SIDE_POTS_FONT_SIZE: f32 = 13.0;
 /// Font size for hand number display
 pub const HAND_NUMBER_FONT_SIZE: f32 = 14.0;
-/// Font size for player chip count
-pub const PLAYER_CHIPS_FONT_SIZE: f32 = 16.0;
-/// Font size for opponent chip count
-pub const OPPONENT_CHIPS_FONT_SIZE: f32 = 14.0;
 /// Font size for round indicator
 pub const ROUND_FONT_SIZE: f32 = 18.0;
 /// Font size for action text
@@ -27,6 +30,12 @@ pub const PLAYER_LABEL_FONT_SIZE: f32 = 20.0;
 pub const CHIP_LABEL_FONT_SIZE: f32 = 18.0;
 /// Font size for hole card text (player's private cards)
 pub const HOLE_CARD_FONT_SIZE: f32 = 16.0;
+/// Font size for the equity overlay
+pub const EQUITY_FONT_SIZE: f32 = 13.0;
+/// Font size for the AI settings menu
+pub const SETTINGS_FONT_SIZE: f32 = 14.0;
+/// Font size for the AI decision panel
+pub const DECISION_PANEL_FONT_SIZE: f32 = 11.0;
 
 // Animation and timing constants
 /// Initial delay before betting actions begin (seconds)
@@ -37,6 +46,14 @@ pub const BETTING_INITIAL_DELAY: f32 = 1.0;
 pub const PLAYER_Y_TOP_RATIO: f32 = 0.25;
 /// Y position ratio for bottom player (as fraction of screen height)
 pub const PLAYER_Y_BOTTOM_RATIO: f32 = -0.32;
+/// Horizontal radius of the ellipse the seats sit on (as fraction of screen width)
+pub const SEAT_X_RADIUS_RATIO: f32 = 0.36;
+/// Size of a seat's cards and labels at tables of more than two seats
+pub const MULTIWAY_SEAT_SCALE: f32 = 0.6;
+/// Seats the equity overlay lists on each line
+pub const EQUITY_SEATS_PER_LINE: usize = 3;
+/// Pots the side pot list shows on each line
+pub const SIDE_POTS_PER_LINE: usize = 2;
 
 // Table rendering positions
 /// Z-index for dark table layer
@@ -58,11 +75,10 @@ pub const CARD_TEXT_BOTTOM_OFFSET_X: f32 = -8.0;
 /// Y offset for bottom-right card text
 pub const CARD_TEXT_BOTTOM_OFFSET_Y: f32 = 12.0;
 
-// Chip display positions
-/// Y position for player chips display
-pub const PLAYER_CHIPS_Y: f32 = -260.0;
-/// Y position for opponent chips display
-pub const OPPONENT_CHIPS_Y: f32 = 60.0;
+/// Y position for the AI settings menu
+pub const SETTINGS_MENU_Y: f32 = -330.0;
+/// Y position for the AI decision panel
+pub const DECISION_PANEL_Y: f32 = -40.0;
 
 // Table dimension ratios (relative to screen size)
 /// Height ratio for dark table background
@@ -85,6 +101,10 @@ pub const COMMUNITY_CARD_Z_POSITION: f32 = 0.5;
 pub const CARD_TARGET_Z: f32 = 1.0;
 /// Z position for UI text elements
 pub const UI_TEXT_Z_POSITION: f32 = 1.0;
+/// Z position for the settings menu (above everything else)
+pub const SETTINGS_MENU_Z_POSITION: f32 = 5.0;
+/// Z position for the AI decision panel (above the table and cards)
+pub const DECISION_PANEL_Z_POSITION: f32 = 4.0;
 
 // Card counts revealed per betting round
 /// Number of cards revealed on the flop
@@ -118,13 +138,19 @@ pub const AI_STRENGTH_CALL_THRESHOLD: f32 = 0.5;
 /// Minimum hand strength for AI to raise/bet
 /// Range: 0.0-1.0, should be higher than CALL_THRESHOLD
 pub const AI_STRENGTH_RAISE_THRESHOLD: f32 = 0.7;
-/// Pot odds threshold considered unfavorable (fold if strength < 0.4)
+/// Minimum hand strength for AI to check or call marginal hands at good pot odds
+/// Range: 0.0-1.0, should be lower than CALL_THRESHOLD
+pub const AI_STRENGTH_MARGINAL_THRESHOLD: f32 = 0.3;
+/// Hand strength below which AI folds when the pot odds are bad
+/// Range: 0.0-1.0
+pub const AI_STRENGTH_BAD_ODDS_FOLD_THRESHOLD: f32 = 0.4;
+/// Pot odds threshold considered unfavorable (fold if strength < BAD_ODDS_FOLD_THRESHOLD)
 /// Pot odds = call_amount / (pot + call_amount)
 pub const AI_POT_ODDS_BAD_THRESHOLD: f32 = 0.3;
 /// Maximum pot odds for calling with medium strength (strength >= 0.5)
 pub const AI_POT_ODDS_CALL_THRESHOLD: f32 = 0.25;
 /// Pot odds threshold considered favorable for calling with marginal hands
-/// Used when hand strength is between 0.3 and CALL_THRESHOLD
+/// Used when hand strength is between MARGINAL_THRESHOLD and CALL_THRESHOLD
 pub const AI_POT_ODDS_GOOD_THRESHOLD: f32 = 0.2;
 /// Bonus to hand strength when in dealer position (acts last)
 /// Applied to all betting rounds
@@ -135,3 +161,192 @@ pub const AI_PREFLOP_DEALER_BONUS: f32 = 0.05;
 /// Penalty to hand strength preflop when not in dealer position
 /// Applied during preflop only to discourage early position play
 pub const AI_PREFLOP_NON_DEALER_PENALTY: f32 = -0.05;
+
+// AI mixed strategies
+/// Default scale on the balanced bluffing frequency (1.0 = fully balanced)
+pub const AI_BLUFF_FREQUENCY: f64 = 0.6;
+/// Default probability of betting or raising a flush or straight draw
+pub const AI_SEMI_BLUFF_FREQUENCY: f64 = 0.35;
+/// Default probability of slow-playing a monster
+pub const AI_SLOW_PLAY_FREQUENCY: f64 = 0.2;
+/// Default probability of checking a monster out of position to check-raise
+pub const AI_CHECK_RAISE_FREQUENCY: f64 = 0.25;
+/// Post-flop strength from which the threshold bot treats its hand as a monster
+/// (0.6 = flush or better)
+pub const AI_MONSTER_STRENGTH: f32 = 0.6;
+/// Equity from which the equity bot treats its hand as a monster
+pub const AI_MONSTER_EQUITY: f64 = 0.85;
+
+// Adaptive AI
+/// Hands of history after which opponent adjustments reach full strength
+pub const AI_ADAPT_FULL_HANDS: u32 = 30;
+/// Largest shift applied to a threshold by one opponent tendency
+pub const AI_ADAPT_STEP: f64 = 0.05;
+/// VPIP above which an opponent counts as loose
+pub const AI_ADAPT_LOOSE_VPIP: f64 = 0.6;
+/// VPIP below which an opponent counts as tight
+pub const AI_ADAPT_TIGHT_VPIP: f64 = 0.3;
+/// Aggression factor above which an opponent counts as aggressive
+pub const AI_ADAPT_AGGRESSIVE_AF: f64 = 2.0;
+/// Aggression factor below which an opponent counts as passive
+pub const AI_ADAPT_PASSIVE_AF: f64 = 0.75;
+/// Preflop raise rate above which an opponent's raises are treated as light
+pub const AI_ADAPT_HIGH_PFR: f64 = 0.5;
+/// 3-bet rate above which an opponent's re-raises are treated as light
+pub const AI_ADAPT_HIGH_THREE_BET: f64 = 0.2;
+/// Went-to-showdown rate above which an opponent counts as a calling station
+pub const AI_ADAPT_STATION_WTSD: f64 = 0.45;
+
+// CFR solver
+/// Equity buckets per street in the solver's card abstraction
+pub const AI_CFR_BUCKETS: usize = 8;
+/// Runouts sampled to place a post-flop hand in its bucket
+pub const AI_CFR_BUCKET_SAMPLES: u32 = 48;
+/// Runouts sampled per starting hand when building the preflop buckets
+pub const AI_CFR_PREFLOP_SAMPLES: u32 = 2_000;
+/// Bets plus raises allowed per street in the abstract game
+pub const AI_CFR_MAX_BETS: u32 = 2;
+/// Training iterations when none are given on the command line
+pub const AI_CFR_ITERATIONS: u64 = 100_000;
+/// Strategy tables written by `train-cfr` and read by the CFR bot
+pub const AI_CFR_STRATEGY_PATH: &str = "config/cfr_strategy.json";
+
+// Push/fold charts
+/// Push/fold charts written by the `push-fold` command and read by the agents
+pub const AI_PUSH_FOLD_PATH: &str = "config/push_fold.json";
+/// Deepest effective stack, in big blinds, that charts are solved for
+pub const AI_PUSH_FOLD_MAX_BB: u32 = 20;
+/// Random deals sampled per starting-hand matchup for the equity table
+pub const AI_PUSH_FOLD_SAMPLES: u32 = 300;
+/// Rounds of fictitious play per stack depth
+pub const AI_PUSH_FOLD_ITERATIONS: u32 = 2_000;
+/// Stack depth printed by `push-fold` when none is given
+pub const AI_PUSH_FOLD_DEFAULT_STACK_BB: f64 = 10.0;
+
+// Arena
+/// Duplicate deal pairs per match when none are given on the command line
+pub const ARENA_DEALS: u32 = 200;
+/// z-score of the reported confidence intervals (95%)
+pub const ARENA_CONFIDENCE_Z: f64 = 1.96;
+/// Elo rating of an average entry
+pub const ARENA_ELO_BASE: f64 = 1500.0;
+/// Elo points moved per unit of score above expectation in each fitting pass
+pub const ARENA_ELO_STEP: f64 = 400.0;
+/// Fitting passes over the match results when computing Elo ratings
+pub const ARENA_ELO_ITERATIONS: u32 = 500;
+
+// External bots
+/// Milliseconds an external bot has to answer when its tier sets no limit
+pub const EXTERNAL_BOT_TIMEOUT_MS: u64 = 2_000;
+/// Times a bot program that exits is started again before its seat only folds
+pub const EXTERNAL_BOT_MAX_RESTARTS: u32 = 3;
+
+// Networked play
+/// Address the server listens on when none is given
+pub const NET_DEFAULT_BIND: &str = "0.0.0.0:7878";
+/// Server the client connects to when none is given
+pub const NET_DEFAULT_SERVER: &str = "127.0.0.1:7878";
+/// Milliseconds a networked player has for each decision before drawing on their time bank
+pub const NET_ACTION_TIMEOUT_MS: u64 = 30_000;
+/// Milliseconds of extra thinking time each networked player has for the whole match
+pub const NET_TIME_BANK_MS: u64 = 60_000;
+/// Milliseconds a dropped player's seat is held for them to reconnect
+pub const NET_RECONNECT_TIMEOUT_MS: u64 = 30_000;
+/// Milliseconds between the client's attempts to reconnect
+pub const NET_RECONNECT_INTERVAL_MS: u64 = 1_000;
+/// Milliseconds the server waits after each hand so players can see the result
+pub const NET_HAND_PAUSE_MS: u64 = 3_000;
+/// Milliseconds a new connection has to say whether it plays or watches
+pub const NET_JOIN_TIMEOUT_MS: u64 = 5_000;
+
+// Sit-and-go tournaments
+/// Chips each player starts a tournament with when none are given
+pub const TOURNAMENT_STARTING_STACK: u32 = 1500;
+/// Seats at a tournament table when none are given on the command line
+pub const TOURNAMENT_SEATS: usize = 6;
+/// Small blind, big blind and ante of each level of the standard schedule
+pub const TOURNAMENT_BLIND_LEVELS: [(u32, u32, u32); 12] = [
+    (10, 20, 0),
+    (15, 30, 0),
+    (25, 50, 0),
+    (50, 100, 10),
+    (75, 150, 15),
+    (100, 200, 25),
+    (150, 300, 25),
+    (200, 400, 50),
+    (300, 600, 75),
+    (400, 800, 100),
+    (600, 1200, 150),
+    (1000, 2000, 250),
+];
+/// Hands played at each blind level when none are given
+pub const TOURNAMENT_HANDS_PER_LEVEL: u32 = 10;
+/// Seconds of play a hand counts for when a headless tournament runs a timed schedule
+pub const TOURNAMENT_SIM_SECONDS_PER_HAND: f32 = 30.0;
+/// Font size for the blind level and tournament results
+pub const TOURNAMENT_FONT_SIZE: f32 = 13.0;
+/// Font size of the results once the tournament is over
+pub const TOURNAMENT_RESULTS_FONT_SIZE: f32 = 22.0;
+/// JSON file with the tournament payout structures, relative to the working directory
+pub const TOURNAMENT_PAYOUTS_PATH: &str = "config/payouts.json";
+/// Payout structure used when none is given on the command line
+pub const TOURNAMENT_DEFAULT_PAYOUTS: &str = "Standard";
+
+// Cash games
+/// JSON file with every player's bankroll and session history, relative to the working directory
+pub const CASH_BANKROLL_PATH: &str = "config/bankroll.json";
+/// Chips in a player's bankroll the first time they sit down
+pub const CASH_STARTING_BANKROLL: u64 = 20_000;
+/// Most recent sessions listed for each player by the `bankroll` command
+pub const CASH_HISTORY_SESSIONS: usize = 10;
+/// Font size for the session profit and loss
+pub const CASH_FONT_SIZE: f32 = 13.0;
+/// Font size of the bankrolls once the table closes
+pub const CASH_RESULTS_FONT_SIZE: f32 = 22.0;
+
+// Parameter tuning
+/// Generations the tuner evolves when none are given on the command line
+pub const TUNE_GENERATIONS: u32 = 20;
+/// Candidate profiles per generation
+pub const TUNE_POPULATION: usize = 16;
+/// Best candidates carried unchanged into the next generation
+pub const TUNE_ELITES: usize = 4;
+/// Duplicate deal pairs each candidate plays against each baseline profile
+pub const TUNE_DEALS: u32 = 100;
+/// Initial mutation as a share of each parameter's range
+pub const TUNE_MUTATION: f64 = 0.15;
+/// Share of the initial mutation still applied in the last generations
+pub const TUNE_MIN_MUTATION_SHARE: f64 = 0.1;
+/// Name given to the tuned profile when none is given on the command line
+pub const TUNE_PROFILE_NAME: &str = "Tuned";
+
+// AI profiles
+/// JSON file with the AI profiles and difficulty tiers, relative to the working directory
+pub const AI_PROFILES_PATH: &str = "config/ai_profiles.json";
+/// Profiles written by the `tune` command, added on top of `AI_PROFILES_PATH`
+pub const AI_TUNED_PROFILES_PATH: &str = "config/tuned_profiles.json";
+
+// Equity-based AI
+/// Opponent range assumed while they have only checked or called this hand
+pub const AI_EQUITY_PASSIVE_RANGE: &str =
+    "22+, A2+, K2+, Q2s+, Q5o+, J6s+, J8o+, T7s+, T8o+, 97s+, 98o, 86s+, 75s+, 65s";
+/// Opponent range assumed after they have bet or raised once this hand
+pub const AI_EQUITY_AGGRESSIVE_RANGE: &str = "22+, A2s+, A7o+, K9s+, KTo+, QTs+, QJo, JTs";
+/// Opponent range assumed after they have bet or raised two or more times
+pub const AI_EQUITY_VERY_AGGRESSIVE_RANGE: &str = "77+, ATs+, AJo+, KQs";
+/// Runouts sampled per decision by the equity agent
+pub const AI_EQUITY_SAMPLES: u32 = 1_000;
+/// Equity above which the equity agent bets into an unopened pot
+pub const AI_EQUITY_BET_THRESHOLD: f64 = 0.55;
+/// Equity above which the equity agent raises a bet
+pub const AI_EQUITY_RAISE_THRESHOLD: f64 = 0.65;
+/// Share of each later street's call amount the agent expects to win when it hits
+/// Range: 0.0-1.0, 0.0 disables implied odds
+pub const AI_IMPLIED_ODDS_FACTOR: f64 = 0.5;
+
+// Equity calculation
+/// Number of random runouts sampled when the board is too incomplete to enumerate
+pub const EQUITY_DEFAULT_SAMPLES: u32 = 5_000;
+/// Largest number of missing board cards for which every runout is enumerated
+/// (2 = from the flop onwards, when only the turn and river are still to come)
+pub const EQUITY_EXHAUSTIVE_MAX_MISSING: usize = 2;
@@ -0,0 +1,357 @@
+//! Equity calculation for two or more known hands.
+//!
+//! Equity is the share of the pot a hand wins on average over every possible
+//! way the board can run out. When only the turn and/or river are still to
+//! come the runouts are enumerated exhaustively; otherwise they are sampled
+//! (Monte Carlo) with a caller-provided random number generator.
+
+use crate::constants::{
+    EQUITY_DEFAULT_SAMPLES, EQUITY_EXHAUSTIVE_MAX_MISSING, MAX_COMMUNITY_CARDS,
+};
+use crate::poker_logic::{full_deck, rank_players, Board, Card, HoleCards};
+use rand::{seq::SliceRandom, thread_rng, Rng};
+use std::fmt;
+
+/// Win/tie/lose tallies for one hand over all evaluated runouts.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Equity {
+    /// Runouts where this hand won the whole pot.
+    pub wins: u64,
+    /// Runouts where this hand split the pot with at least one other hand.
+    pub ties: u64,
+    /// Runouts where this hand won nothing.
+    pub losses: u64,
+    /// Sum of pot fractions won, e.g. a two-way split adds 0.5.
+    pub pot_share: f64,
+}
+
+#[allow(clippy::cast_precision_loss)]
+impl Equity {
+    fn trials(&self) -> f64 {
+        (self.wins + self.ties + self.losses).max(1) as f64
+    }
+
+    /// Percentage of runouts won outright (0-100).
+    pub fn win_pct(&self) -> f64 {
+        self.wins as f64 * 100.0 / self.trials()
+    }
+
+    /// Percentage of runouts split (0-100).
+    pub fn tie_pct(&self) -> f64 {
+        self.ties as f64 * 100.0 / self.trials()
+    }
+
+    /// Percentage of runouts lost (0-100).
+    pub fn lose_pct(&self) -> f64 {
+        self.losses as f64 * 100.0 / self.trials()
+    }
+
+    /// Expected share of the pot (0.0-1.0), counting split pots fractionally.
+    pub fn share(&self) -> f64 {
+        self.pot_share / self.trials()
+    }
+}
+
+/// Result of an equity calculation, one entry per input hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityResult {
+    pub equities: Vec<Equity>,
+    /// Number of board runouts evaluated.
+    pub trials: u64,
+    /// Whether every possible runout was evaluated rather than a sample.
+    pub exhaustive: bool,
+}
+
+/// Reasons an equity calculation cannot be performed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EquityError {
+    /// At least two hands are needed to compare.
+    NotEnoughHands,
+    /// More than five board cards were given.
+    TooManyBoardCards(usize),
+    /// A card appears more than once among hands, board and dead cards.
+    DuplicateCard(Card),
+    /// Not enough cards are left in the deck to complete the board.
+    NotEnoughCards,
+    /// The range at this position has no combos left after card removal.
+    EmptyRange(usize),
+}
+
+impl fmt::Display for EquityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EquityError::NotEnoughHands => write!(f, "at least two hands are required"),
+            EquityError::TooManyBoardCards(count) => {
+                write!(f, "a board has at most 5 cards, got {}", count)
+            }
+            EquityError::DuplicateCard(card) => write!(f, "card {} is used twice", card),
+            EquityError::NotEnoughCards => write!(f, "not enough cards left to complete the board"),
+            EquityError::EmptyRange(index) => {
+                write!(
+                    f,
+                    "range {} has no combos left after card removal",
+                    index + 1
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for EquityError {}
+
+/// Calculates equity using the default sample count and the thread RNG.
+///
+/// See [`equity_with_rng`] for details.
+pub fn equity(
+    hands: &[HoleCards],
+    board: &[Card],
+    dead_cards: &[Card],
+) -> Result<EquityResult, EquityError> {
+    equity_with_rng(
+        hands,
+        board,
+        dead_cards,
+        EQUITY_DEFAULT_SAMPLES,
+        &mut thread_rng(),
+    )
+}
+
+/// Calculates the win/tie/lose percentages of each hand.
+///
+/// `board` holds the community cards already dealt (0-5) and `dead_cards`
+/// any cards known to be out of play. If at most
+/// `EQUITY_EXHAUSTIVE_MAX_MISSING` board cards are still to come, every runout
+/// is enumerated and `samples` is ignored; otherwise `samples` random runouts
+/// are drawn from `rng`, so a seeded RNG gives reproducible results.
+pub fn equity_with_rng<R: Rng + ?Sized>(
+    hands: &[HoleCards],
+    board: &[Card],
+    dead_cards: &[Card],
+    samples: u32,
+    rng: &mut R,
+) -> Result<EquityResult, EquityError> {
+    if hands.len() < 2 {
+        return Err(EquityError::NotEnoughHands);
+    }
+
+    let board: Vec<Card> = board
+        .iter()
+        .copied()
+        .filter(|c| !c.is_placeholder)
+        .collect();
+    if board.len() > MAX_COMMUNITY_CARDS {
+        return Err(EquityError::TooManyBoardCards(board.len()));
+    }
+
+    let mut known: Vec<Card> = Vec::with_capacity(hands.len() * 2 + board.len() + dead_cards.len());
+    for &card in hands.iter().flatten().chain(&board).chain(dead_cards) {
+        if known.contains(&card) {
+            return Err(EquityError::DuplicateCard(card));
+        }
+        known.push(card);
+    }
+
+    let remaining: Vec<Card> = full_deck()
+        .into_iter()
+        .filter(|c| !known.contains(c))
+        .collect();
+    let missing = MAX_COMMUNITY_CARDS - board.len();
+    if remaining.len() < missing {
+        return Err(EquityError::NotEnoughCards);
+    }
+
+    let mut tally = Tally::new(hands, &board);
+    let exhaustive = missing <= EQUITY_EXHAUSTIVE_MAX_MISSING;
+    if exhaustive {
+        for_each_combination(&remaining, missing, &mut |runout| tally.record(runout));
+    } else {
+        let mut deck = remaining;
+        for _ in 0..samples {
+            let (runout, _) = deck.partial_shuffle(rng, missing);
+            tally.record(runout);
+        }
+    }
+
+    Ok(EquityResult {
+        equities: tally.equities,
+        trials: tally.trials,
+        exhaustive,
+    })
+}
+
+/// Accumulates showdown results for a fixed set of hands and partial board.
+struct Tally<'a> {
+    hands: &'a [HoleCards],
+    board: Board,
+    known_board: usize,
+    equities: Vec<Equity>,
+    trials: u64,
+}
+
+impl<'a> Tally<'a> {
+    fn new(hands: &'a [HoleCards], known_board: &[Card]) -> Self {
+        let mut board = Board::default();
+        board[..known_board.len()].copy_from_slice(known_board);
+        Self {
+            hands,
+            board,
+            known_board: known_board.len(),
+            equities: vec![Equity::default(); hands.len()],
+            trials: 0,
+        }
+    }
+
+    fn record(&mut self, runout: &[Card]) {
+        self.board[self.known_board..].copy_from_slice(runout);
+        record_showdown(self.hands, &self.board, &mut self.equities);
+        self.trials += 1;
+    }
+}
+
+/// Adds the result of one showdown between `hands` on a complete board.
+#[allow(clippy::cast_precision_loss)]
+pub fn record_showdown(hands: &[HoleCards], board: &Board, equities: &mut [Equity]) {
+    let tiers = rank_players(hands, board);
+    let winners = &tiers[0];
+    let share = 1.0 / winners.len() as f64;
+
+    for (seat, equity) in equities.iter_mut().enumerate() {
+        if !winners.contains(&seat) {
+            equity.losses += 1;
+        } else if winners.len() == 1 {
+            equity.wins += 1;
+            equity.pot_share += 1.0;
+        } else {
+            equity.ties += 1;
+            equity.pot_share += share;
+        }
+    }
+}
+
+/// Calls `f` with every `k`-card combination of `cards`.
+fn for_each_combination(cards: &[Card], k: usize, f: &mut dyn FnMut(&[Card])) {
+    let mut chosen = Vec::with_capacity(k);
+    combine(cards, k, &mut chosen, f);
+}
+
+fn combine(cards: &[Card], k: usize, chosen: &mut Vec<Card>, f: &mut dyn FnMut(&[Card])) {
+    if chosen.len() == k {
+        f(chosen);
+        return;
+    }
+    let needed = k - chosen.len();
+    for i in 0..=(cards.len() - needed) {
+        chosen.push(cards[i]);
+        combine(&cards[i + 1..], k, chosen, f);
+        chosen.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker_logic::parse_cards;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn hole(s: &str) -> HoleCards {
+        let cards = parse_cards(s).unwrap();
+        [cards[0], cards[1]]
+    }
+
+    #[test]
+    fn test_river_is_single_exhaustive_trial() {
+        let board = parse_cards("Ah Kd 7c 4s 2h").unwrap();
+        let result = equity(&[hole("AsQc"), hole("KhKc")], &board, &[]).unwrap();
+
+        assert!(result.exhaustive);
+        assert_eq!(result.trials, 1);
+        assert_eq!(result.equities[0].losses, 1);
+        assert_eq!(result.equities[1].wins, 1);
+    }
+
+    #[test]
+    fn test_turn_enumerates_every_river() {
+        let board = parse_cards("Kh 7h 2c 4d").unwrap();
+        let result = equity(&[hole("AhQh"), hole("KsKc")], &board, &[]).unwrap();
+
+        // 52 - 4 hole cards - 4 board cards
+        assert!(result.exhaustive);
+        assert_eq!(result.trials, 44);
+        // Nine hearts remain, but 2h and 4h pair the board and fill up the kings
+        assert_eq!(result.equities[0].wins, 7);
+        assert_eq!(result.equities[1].wins, 37);
+    }
+
+    #[test]
+    fn test_flop_enumerates_turn_and_river() {
+        let board = parse_cards("Ah Kd 7c").unwrap();
+        let result = equity(&[hole("AsQc"), hole("KhKc")], &board, &[]).unwrap();
+
+        // C(45, 2) runouts
+        assert!(result.exhaustive);
+        assert_eq!(result.trials, 990);
+        let total: f64 = result.equities.iter().map(Equity::share).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_dead_cards_are_removed_from_runouts() {
+        let board = parse_cards("Kh 7h 2c 4d").unwrap();
+        let dead = parse_cards("3h 5h").unwrap();
+        let result = equity(&[hole("AhQh"), hole("KsKc")], &board, &dead).unwrap();
+
+        assert_eq!(result.trials, 42);
+        assert_eq!(result.equities[0].wins, 5);
+    }
+
+    #[test]
+    fn test_identical_hands_split() {
+        let board = parse_cards("2c 7d 9h Jc").unwrap();
+        let result = equity(&[hole("AsKs"), hole("AhKh")], &board, &[]).unwrap();
+        let eq = result.equities[0];
+
+        assert!(eq.tie_pct() > 90.0);
+        assert!((eq.win_pct() + eq.tie_pct() + eq.lose_pct() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_preflop_sampling_is_reproducible() {
+        let hands = [hole("AsAd"), hole("7h2c")];
+        let first = equity_with_rng(&hands, &[], &[], 2000, &mut StdRng::seed_from_u64(7)).unwrap();
+        let second =
+            equity_with_rng(&hands, &[], &[], 2000, &mut StdRng::seed_from_u64(7)).unwrap();
+
+        assert!(!first.exhaustive);
+        assert_eq!(first.trials, 2000);
+        assert_eq!(first, second);
+        // Aces are roughly 88% against seven-deuce offsuit
+        let share = first.equities[0].share();
+        assert!((0.84..0.92).contains(&share), "unexpected equity {}", share);
+    }
+
+    #[test]
+    fn test_three_way_equity_sums_to_one() {
+        let hands = [hole("AsAd"), hole("KsKd"), hole("QsQd")];
+        let result =
+            equity_with_rng(&hands, &[], &[], 1000, &mut StdRng::seed_from_u64(3)).unwrap();
+        let total: f64 = result.equities.iter().map(Equity::share).sum();
+
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(result.equities[0].share() > result.equities[2].share());
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let aces = hole("AsAd");
+        assert_eq!(equity(&[aces], &[], &[]), Err(EquityError::NotEnoughHands));
+        assert_eq!(
+            equity(&[aces, hole("AsKd")], &[], &[]),
+            Err(EquityError::DuplicateCard(parse_cards("As").unwrap()[0]))
+        );
+        let six = parse_cards("2c 3c 4c 5c 6c 7c").unwrap();
+        assert_eq!(
+            equity(&[aces, hole("KsKd")], &six, &[]),
+            Err(EquityError::TooManyBoardCards(6))
+        );
+    }
+}
@@ -0,0 +1,562 @@
+//! Bots that run as separate programs and talk to the engine over their
+//! standard input and output, so they can be written in any language.
+//!
+//! # Protocol
+//!
+//! The engine starts the program once per seat and writes one JSON object
+//! per line to its stdin. When the seat has to act it sends an `act`
+//! message:
+//!
+//! ```text
+//! {"type":"act","id":7,"seat":0,"hole_cards":["Ah","Kd"],"board":["7c","8d","9h"],
+//!  "round":"flop","dealer":1,"stacks":[925,950],"bets":[0,50],
+//!  "folded":[false,false],"current_bet":50,"to_call":50,"pot":150,"big_blind":50,"bet_amount":50,"raise_amount":100,
+//!  "history":[{"seat":1,"round":"preflop","action":"call","amount":25}, ...],
+//!  "legal_actions":["call","raise","all_in","fold"]}
+//! ```
+//!
+//! and the bot answers with one line naming a legal action, optionally
+//! echoing the `id` and giving a reason for the hand log:
+//!
+//! ```text
+//! {"action":"raise","id":7,"reason":"open-ended straight draw"}
+//! ```
+//!
+//! Actions are `check`, `bet`, `call`, `raise`, `all_in` and `fold`; rounds
+//! are `preflop`, `flop`, `turn` and `river`. When a hand ends every bot
+//! receives `{"type":"hand_end","history":[...],"showdown":true}`, which
+//! needs no answer. Anything the bot writes to stderr is passed through.
+//!
+//! A bot that does not answer within its time limit, answers with
+//! something unreadable, or picks an illegal action folds. Replies carrying
+//! the `id` of an earlier request are ignored, so a late answer cannot be
+//! applied to the next decision. A bot that exits is started again for its
+//! next decision, up to `EXTERNAL_BOT_MAX_RESTARTS` times.
+//!
+//! # Configuration
+//!
+//! A bot is a difficulty tier with the `external` strategy:
+//!
+//! ```text
+//! {"name":"Python","strategy":"external",
+//!  "bot_command":["python3","bots/example_bot.py"],"bot_timeout_ms":2000}
+//! ```
+//!
+//! `bots/example_bot.py` is a minimal bot to start from.
+
+use crate::agent::{DecisionBasis, Explanation, PokerAgent};
+use crate::constants::*;
+use crate::game::{ActionRecord, PlayerView, PokerAction};
+use crate::poker_logic::{PokerRound, SeatIndex};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Protocol name of an action.
+pub fn action_name(action: PokerAction) -> &'static str {
+    match action {
+        PokerAction::Check => "check",
+        PokerAction::Bet => "bet",
+        PokerAction::Call => "call",
+        PokerAction::Raise => "raise",
+        PokerAction::AllIn => "all_in",
+        PokerAction::Fold => "fold",
+    }
+}
+
+/// The action a protocol name stands for, ignoring case.
+pub fn parse_action(name: &str) -> Option<PokerAction> {
+    [
+        PokerAction::Check,
+        PokerAction::Bet,
+        PokerAction::Call,
+        PokerAction::Raise,
+        PokerAction::AllIn,
+        PokerAction::Fold,
+    ]
+    .into_iter()
+    .find(|&action| action_name(action).eq_ignore_ascii_case(name.trim()))
+}
+
+/// Protocol name of a betting round.
+pub fn round_name(round: PokerRound) -> &'static str {
+    match round {
+        PokerRound::PreFlop => "preflop",
+        PokerRound::Flop => "flop",
+        PokerRound::Turn => "turn",
+        PokerRound::River => "river",
+        PokerRound::Showdown => "showdown",
+    }
+}
+
+/// One action of the hand as it is sent to bots.
+#[derive(Debug, Serialize)]
+pub struct HistoryEntry {
+    seat: SeatIndex,
+    round: &'static str,
+    action: &'static str,
+    amount: u32,
+}
+
+pub fn history(records: &[ActionRecord]) -> Vec<HistoryEntry> {
+    records
+        .iter()
+        .map(|record| HistoryEntry {
+            seat: record.seat,
+            round: round_name(record.round),
+            action: action_name(record.action),
+            amount: record.amount,
+        })
+        .collect()
+}
+
+/// Everything the acting seat may know, as sent in an `act` message.
+#[derive(Debug, Serialize)]
+pub struct Observation {
+    id: u64,
+    seat: SeatIndex,
+    hole_cards: Vec<String>,
+    board: Vec<String>,
+    round: &'static str,
+    dealer: SeatIndex,
+    stacks: Vec<u32>,
+    bets: Vec<u32>,
+    /// Seats out of the hand, having folded or sat down busted
+    folded: Vec<bool>,
+    current_bet: u32,
+    to_call: u32,
+    pot: u32,
+    big_blind: u32,
+    bet_amount: u32,
+    raise_amount: u32,
+    history: Vec<HistoryEntry>,
+    legal_actions: Vec<&'static str>,
+}
+
+impl Observation {
+    pub fn new(id: u64, view: &PlayerView, actions: &[PokerAction]) -> Self {
+        Self {
+            id,
+            seat: view.seat,
+            hole_cards: view.hole_cards.iter().map(ToString::to_string).collect(),
+            board: view.board.iter().map(ToString::to_string).collect(),
+            round: round_name(view.round),
+            dealer: view.dealer_position,
+            stacks: view.stacks.clone(),
+            bets: view.bets.clone(),
+            folded: view.folded.clone(),
+            current_bet: view.current_bet,
+            to_call: view.to_call(),
+            pot: view.pot,
+            big_blind: view.big_blind,
+            bet_amount: view.bet_amount,
+            raise_amount: view.raise_amount,
+            history: history(&view.history),
+            legal_actions: actions.iter().map(|&a| action_name(a)).collect(),
+        }
+    }
+}
+
+/// A line sent to the bot.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    Act(Box<Observation>),
+    HandEnd {
+        history: Vec<HistoryEntry>,
+        showdown: bool,
+    },
+}
+
+/// A line read from the bot.
+#[derive(Debug, Deserialize)]
+pub struct Reply {
+    action: String,
+    #[serde(default)]
+    id: Option<u64>,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// Why a decision was folded instead of taken from the bot.
+#[derive(Debug)]
+pub enum Failure {
+    Timeout(Duration),
+    Crashed,
+    CannotStart(String),
+    Unreadable(String),
+    Illegal(String),
+}
+
+impl Failure {
+    fn note(&self) -> String {
+        match self {
+            Failure::Timeout(limit) => format!("no answer within {} ms", limit.as_millis()),
+            Failure::Crashed => "bot exited".to_string(),
+            Failure::CannotStart(err) => format!("bot cannot be started: {}", err),
+            Failure::Unreadable(line) => format!("unreadable answer '{}'", line),
+            Failure::Illegal(action) => format!("illegal action '{}'", action),
+        }
+    }
+}
+
+/// Lines written by a bot, read on a separate thread so the engine can wait
+/// for them with a timeout.
+pub struct LineReader {
+    lines: Mutex<Receiver<String>>,
+}
+
+impl LineReader {
+    pub fn spawn(source: impl Read + Send + 'static) -> Self {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            let mut lines = BufReader::new(source).lines().map_while(Result::ok);
+            // Stops at end of output or once the reader is gone
+            let _ = lines.try_for_each(|line| sender.send(line));
+        });
+        Self {
+            lines: Mutex::new(lines),
+        }
+    }
+
+    /// The bot's answer to request `id`, skipping answers to earlier ones.
+    pub fn receive(&self, id: u64, timeout: Duration) -> Result<Reply, Failure> {
+        let lines = self.lines.lock().expect("bot output lock poisoned");
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match lines.recv_timeout(remaining) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => return Err(Failure::Timeout(timeout)),
+                Err(RecvTimeoutError::Disconnected) => return Err(Failure::Crashed),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let reply: Reply =
+                serde_json::from_str(&line).map_err(|_| Failure::Unreadable(line.clone()))?;
+            if reply.id.is_none_or(|reply_id| reply_id == id) {
+                return Ok(reply);
+            }
+        }
+    }
+
+    /// The next line, waiting at most `timeout` for it.
+    pub fn next_line(&self, timeout: Duration) -> Option<String> {
+        self.lines
+            .lock()
+            .expect("bot output lock poisoned")
+            .recv_timeout(timeout)
+            .ok()
+    }
+
+    /// The next line if one has arrived, without waiting.
+    pub fn try_next(&self) -> Result<String, TryRecvError> {
+        self.lines
+            .lock()
+            .expect("bot output lock poisoned")
+            .try_recv()
+    }
+
+    /// Throws away anything written since the last request.
+    pub fn discard_pending(&self) {
+        while self.try_next().is_ok() {}
+    }
+}
+
+/// The legal action a reply names, or a fold noting what went wrong, each
+/// with the explanation for the hand log.
+pub fn reply_action(
+    reply: Result<Reply, Failure>,
+    actions: &[PokerAction],
+) -> (PokerAction, Explanation) {
+    let outcome = reply.and_then(|reply| {
+        match parse_action(&reply.action).filter(|action| actions.contains(action)) {
+            Some(action) => Ok((action, reply.reason)),
+            None => Err(Failure::Illegal(reply.action)),
+        }
+    });
+    match outcome {
+        Ok((action, reason)) => (action, Explanation::new(DecisionBasis::External { reason })),
+        Err(failure) => {
+            let mut explanation = Explanation::new(DecisionBasis::External { reason: None });
+            explanation
+                .notes
+                .push(format!("{}: auto-fold", failure.note()));
+            (PokerAction::Fold, explanation)
+        }
+    }
+}
+
+/// A running bot program and the lines it has written.
+struct BotProcess {
+    child: Child,
+    stdin: ChildStdin,
+    lines: LineReader,
+}
+
+impl BotProcess {
+    fn spawn(command: &[String]) -> io::Result<Self> {
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty bot command"))?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        Ok(Self {
+            child,
+            stdin,
+            lines: LineReader::spawn(stdout),
+        })
+    }
+
+    fn send(&mut self, message: &Message) -> io::Result<()> {
+        let json = serde_json::to_string(message).map_err(io::Error::other)?;
+        writeln!(self.stdin, "{}", json)?;
+        self.stdin.flush()
+    }
+}
+
+impl Drop for BotProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// A seat played by an external program speaking the protocol above.
+pub struct ExternalAgent {
+    name: String,
+    command: Vec<String>,
+    timeout: Duration,
+    process: Option<BotProcess>,
+    /// Times the program has been started, including the first.
+    launches: u32,
+    next_id: u64,
+    last: Option<Explanation>,
+}
+
+impl ExternalAgent {
+    /// Starts `command` (program and arguments), giving it `timeout` to
+    /// answer each decision.
+    pub fn spawn(command: Vec<String>, timeout: Duration) -> io::Result<Self> {
+        let process = BotProcess::spawn(&command)?;
+        let program = command.last().map_or("", String::as_str);
+        let file_name = Path::new(program).file_name().map_or_else(
+            || program.to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        Ok(Self {
+            name: format!("External ({})", file_name),
+            command,
+            timeout,
+            process: Some(process),
+            launches: 1,
+            next_id: 0,
+            last: None,
+        })
+    }
+
+    /// The running program, restarting it if it has exited and restarts
+    /// are left.
+    fn process(&mut self) -> Result<&mut BotProcess, Failure> {
+        if self.process.is_none() {
+            if self.launches > EXTERNAL_BOT_MAX_RESTARTS {
+                return Err(Failure::Crashed);
+            }
+            self.launches += 1;
+            let process = BotProcess::spawn(&self.command)
+                .map_err(|err| Failure::CannotStart(err.to_string()))?;
+            self.process = Some(process);
+        }
+        Ok(self.process.as_mut().expect("process was just started"))
+    }
+
+    fn request(&mut self, message: &Message, id: u64) -> Result<Reply, Failure> {
+        let timeout = self.timeout;
+        let process = self.process()?;
+        process.lines.discard_pending();
+        let result = match process.send(message) {
+            Ok(()) => process.lines.receive(id, timeout),
+            Err(_) => Err(Failure::Crashed),
+        };
+        if matches!(result, Err(Failure::Crashed)) {
+            self.process = None;
+        }
+        result
+    }
+
+    fn decide(&mut self, view: &PlayerView, actions: &[PokerAction]) -> Result<Reply, Failure> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let message = Message::Act(Box::new(Observation::new(id, view, actions)));
+        self.request(&message, id)
+    }
+}
+
+impl PokerAgent for ExternalAgent {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        actions: &[PokerAction],
+        _rng: &mut dyn RngCore,
+    ) -> PokerAction {
+        let (action, explanation) = reply_action(self.decide(view, actions), actions);
+        self.last = Some(explanation);
+        action
+    }
+
+    fn observe_hand_end(&mut self, records: &[ActionRecord], went_to_showdown: bool) {
+        let message = Message::HandEnd {
+            history: history(records),
+            showdown: went_to_showdown,
+        };
+        // Only a running bot is told; a crashed one restarts at its next decision
+        if let Some(process) = self.process.as_mut() {
+            if process.send(&message).is_err() {
+                self.process = None;
+            }
+        }
+    }
+
+    fn explain(&self) -> Option<Explanation> {
+        self.last.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker_logic::parse_cards;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const UNOPENED: [PokerAction; 3] = [PokerAction::Check, PokerAction::Bet, PokerAction::Fold];
+    const FACING_BET: [PokerAction; 3] = [PokerAction::Call, PokerAction::Raise, PokerAction::Fold];
+
+    fn view() -> PlayerView {
+        let hole = parse_cards("AhKd").unwrap();
+        PlayerView {
+            seat: 0,
+            hole_cards: [hole[0], hole[1]],
+            board: parse_cards("7c 8d 9h").unwrap(),
+            round: PokerRound::Flop,
+            hand_number: 1,
+            dealer_position: 1,
+            stacks: vec![925, 950],
+            bets: vec![0, 0],
+            folded: vec![false; 2],
+            current_bet: 0,
+            pot: 100,
+            big_blind: 50,
+            bet_amount: 50,
+            raise_amount: 100,
+            history: Vec::new(),
+            payouts: Vec::new(),
+        }
+    }
+
+    /// A bot written as a shell script.
+    fn shell_bot(script: &str, timeout_ms: u64) -> ExternalAgent {
+        let command = vec!["sh".to_string(), "-c".to_string(), script.to_string()];
+        ExternalAgent::spawn(command, Duration::from_millis(timeout_ms)).unwrap()
+    }
+
+    fn act(agent: &mut ExternalAgent, actions: &[PokerAction]) -> PokerAction {
+        agent.choose_action(&view(), actions, &mut StdRng::seed_from_u64(42))
+    }
+
+    fn notes(agent: &ExternalAgent) -> Vec<String> {
+        agent.explain().unwrap().notes
+    }
+
+    #[test]
+    fn test_observation_matches_the_protocol() {
+        let mut view = view();
+        view.folded[1] = true;
+        let message = Message::Act(Box::new(Observation::new(7, &view, &FACING_BET)));
+        let json: serde_json::Value = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["type"], "act");
+        assert_eq!(json["id"], 7);
+        assert_eq!(json["hole_cards"], serde_json::json!(["Ah", "Kd"]));
+        assert_eq!(json["round"], "flop");
+        assert_eq!(json["folded"], serde_json::json!([false, true]));
+        assert_eq!(
+            json["legal_actions"],
+            serde_json::json!(["call", "raise", "fold"])
+        );
+        assert_eq!(parse_action(" All_In "), Some(PokerAction::AllIn));
+        assert_eq!(parse_action("shove"), None);
+    }
+
+    #[test]
+    fn test_bot_answers_are_played_and_illegal_ones_fold() {
+        let script =
+            r#"while read line; do echo '{"action":"check","reason":"pot control"}'; done"#;
+        let mut agent = shell_bot(script, 2000);
+        assert_eq!(act(&mut agent, &UNOPENED), PokerAction::Check);
+        assert_eq!(
+            agent.explain().unwrap().basis,
+            DecisionBasis::External {
+                reason: Some("pot control".to_string())
+            }
+        );
+        assert_eq!(act(&mut agent, &FACING_BET), PokerAction::Fold);
+        assert!(notes(&agent)[0].starts_with("illegal action 'check'"));
+    }
+
+    #[test]
+    fn test_slow_bots_time_out_and_stale_answers_are_ignored() {
+        // Answers every request one second late, echoing its id
+        let script = r#"while read line; do
+            id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+            sleep 1; echo "{\"action\":\"bet\",\"id\":$id}"
+        done"#;
+        let mut agent = shell_bot(script, 100);
+        let started = Instant::now();
+        assert_eq!(act(&mut agent, &UNOPENED), PokerAction::Fold);
+        assert!(started.elapsed() < Duration::from_millis(900));
+        assert!(notes(&agent)[0].starts_with("no answer within 100 ms"));
+
+        // The late answer to request 0 must not be taken as the answer to 1
+        agent.timeout = Duration::from_millis(2500);
+        assert_eq!(act(&mut agent, &UNOPENED), PokerAction::Bet);
+    }
+
+    #[test]
+    fn test_crashed_bots_are_restarted_a_limited_number_of_times() {
+        // Reads one request and exits without answering
+        let mut agent = shell_bot("read line; exit 1", 2000);
+        assert_eq!(act(&mut agent, &UNOPENED), PokerAction::Fold);
+        assert!(notes(&agent)[0].starts_with("bot exited"));
+        assert_eq!(agent.launches, 1);
+
+        for _ in 0..EXTERNAL_BOT_MAX_RESTARTS + 2 {
+            assert_eq!(act(&mut agent, &UNOPENED), PokerAction::Fold);
+        }
+        assert_eq!(agent.launches, EXTERNAL_BOT_MAX_RESTARTS + 1);
+        assert!(agent.process.is_none());
+    }
+
+    #[test]
+    fn test_missing_programs_fail_to_spawn() {
+        let command = vec!["does/not/exist".to_string()];
+        assert!(ExternalAgent::spawn(command, Duration::from_millis(10)).is_err());
+        assert!(ExternalAgent::spawn(Vec::new(), Duration::from_millis(10)).is_err());
+    }
+}
@@ -1,17 +1,27 @@
+use crate::agent::{Explanation, PokerAgent};
 use crate::constants::*;
-use crate::poker_logic::{determine_winner, Card, Deck, HandRank, PokerRound};
+use crate::poker_logic::{
+    evaluate_hand, rank_players, Card, Deck, HandRank, HoleCards, PokerRound, Rank, SeatIndex,
+};
+use crate::profile::{AiProfile, SeatSetup};
 use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
 
 /// Configuration resource for game settings including display dimensions,
 /// animation timing, betting amounts, and UI layout positions.
 #[derive(Resource, Clone, Copy)]
 pub struct UIPositioning {
     pub pot_display_y: f32,
+    pub side_pots_display_y: f32,
     pub hand_number_x: f32,
     pub hand_number_y: f32,
     pub round_display_x: f32,
     pub round_display_y: f32,
     pub action_display_y: f32,
+    pub equity_display_y: f32,
+    pub tournament_display_y: f32,
+    pub session_display_y: f32,
     pub player_label_offset: f32,
     pub chip_label_offset: f32,
 }
@@ -20,11 +30,15 @@ impl Default for UIPositioning {
     fn default() -> Self {
         Self {
             pot_display_y: 130.0,
+            side_pots_display_y: 146.0,
             hand_number_x: -160.0,
             hand_number_y: 360.0,
             round_display_x: 140.0,
             round_display_y: 360.0,
             action_display_y: -180.0,
+            equity_display_y: -100.0,
+            tournament_display_y: 385.0,
+            session_display_y: 385.0,
             player_label_offset: 20.0,
             chip_label_offset: -5.0,
         }
@@ -67,6 +81,13 @@ pub struct GameConfig {
     pub showdown_duration: f32,
     pub fold_showdown_duration: f32,
     pub starting_chips: u32,
+    /// Posted by the first seat after the button, or by the button itself
+    /// heads-up, before the cards are dealt
+    pub small_blind: u32,
+    /// Posted by the seat after the small blind before the cards are dealt
+    pub big_blind: u32,
+    /// Posted by every seat in the hand before the blinds
+    pub ante: u32,
     pub bet_amount: u32,
     pub raise_amount: u32,
     pub screen_width: f32,
@@ -74,6 +95,14 @@ pub struct GameConfig {
     pub ui_positions: UIPositioning,
     pub animations: AnimationConfig,
     pub min_cards_for_reshuffle: usize,
+    /// AI profile and difficulty chosen for each seat; one entry per seat,
+    /// from `MIN_SEATS` to `MAX_SEATS` of them.
+    pub seats: Vec<SeatSetup>,
+    /// Seed for shuffling and AI decisions; `None` picks a random seed.
+    pub rng_seed: Option<u64>,
+    /// Shares of the prize pool by finishing place while a tournament is
+    /// played; empty when chips are worth their face value.
+    pub payouts: Vec<f64>,
 }
 
 impl Default for GameConfig {
@@ -90,6 +119,9 @@ impl Default for GameConfig {
             showdown_duration: 5.0,
             fold_showdown_duration: 3.0,
             starting_chips: 1000,
+            small_blind: 25,
+            big_blind: 50,
+            ante: 0,
             bet_amount: 50,
             raise_amount: 100,
             screen_width: 375.0,
@@ -97,6 +129,24 @@ impl Default for GameConfig {
             ui_positions: UIPositioning::default(),
             animations: AnimationConfig::default(),
             min_cards_for_reshuffle: 9,
+            seats: vec![
+                SeatSetup::new("Balanced", "Medium"),
+                SeatSetup::new("Balanced", "Hard"),
+            ],
+            rng_seed: None,
+            payouts: Vec::new(),
+        }
+    }
+}
+
+impl GameConfig {
+    /// The default configuration with `seats` seats, the extra seats
+    /// taking turns with the default AI setups.
+    pub fn with_seat_count(seats: usize) -> Self {
+        let defaults = Self::default();
+        Self {
+            seats: defaults.seats.iter().cycle().take(seats).cloned().collect(),
+            ..defaults
         }
     }
 }
@@ -166,16 +216,213 @@ pub struct GameStateResource {
     pub action_tick: u32,
     pub hand_number: i32,
     pub animation_start_time: f32,
-    pub player_chips: [u32; 2],
-    pub player_bets: [u32; 2],
+    pub player_chips: Vec<u32>,
+    pub player_bets: Vec<u32>,
+    /// Chips each seat has put into the pot this hand, across all streets.
+    pub contributions: Vec<u32>,
     pub current_bet: u32,
     pub needs_cleanup: bool,
     pub winner: Option<usize>,
     pub last_winner_message: String,
-    pub p1_hole: [Card; 2],
-    pub p2_hole: [Card; 2],
+    pub hole_cards: Vec<HoleCards>,
+    /// Seats out of the current hand, having folded or sat down busted.
+    pub folded: Vec<bool>,
     pub community_cards: [Card; 5],
     pub needs_hand_restart: bool,
+    pub action_history: Vec<ActionRecord>,
+    /// Why each seat made its most recent decision this hand.
+    pub last_explanations: Vec<Option<Explanation>>,
+    /// One line per decision this hand, with its explanation.
+    pub hand_log: Vec<String>,
+}
+
+impl GameStateResource {
+    /// A table with one seat per stack, before its first hand.
+    pub fn with_stacks(stacks: Vec<u32>) -> Self {
+        let seats = stacks.len();
+        Self {
+            player_chips: stacks,
+            player_bets: vec![0; seats],
+            contributions: vec![0; seats],
+            hole_cards: vec![HoleCards::default(); seats],
+            folded: vec![false; seats],
+            last_explanations: vec![None; seats],
+            ..Default::default()
+        }
+    }
+
+    pub fn seat_count(&self) -> usize {
+        self.player_chips.len()
+    }
+
+    /// Seats that have not folded this hand.
+    pub fn seats_in_hand(&self) -> impl Iterator<Item = SeatIndex> + '_ {
+        (0..self.seat_count()).filter(|&seat| !self.folded[seat])
+    }
+
+    /// The main pot and any side pots, from what each seat has put in.
+    pub fn pots(&self) -> Vec<Pot> {
+        build_pots(&self.contributions, &self.player_chips, &self.folded)
+    }
+
+    /// The next seat after `seat` that is still in the hand.
+    fn next_in_hand(&self, seat: SeatIndex) -> SeatIndex {
+        let seats = self.seat_count();
+        (1..=seats)
+            .map(|step| (seat + step) % seats)
+            .find(|&next| !self.folded[next])
+            .unwrap_or(seat)
+    }
+
+    /// The next seat after `seat` that is still in the hand and has chips
+    /// to bet with, or `seat` itself when there is none.
+    pub fn next_to_act(&self, seat: SeatIndex) -> SeatIndex {
+        let seats = self.seat_count();
+        (1..=seats)
+            .map(|step| (seat + step) % seats)
+            .find(|&next| !self.folded[next] && self.player_chips[next] > 0)
+            .unwrap_or(seat)
+    }
+}
+
+/// One betting action taken during the current hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActionRecord {
+    pub seat: SeatIndex,
+    pub round: PokerRound,
+    pub action: PokerAction,
+    /// Chips the seat put into the pot with this action.
+    pub amount: u32,
+}
+
+/// One pot of the hand and the seats that can win it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pot {
+    pub amount: u32,
+    pub eligible: Vec<SeatIndex>,
+}
+
+/// Splits what each seat has put in this hand into the main pot and side
+/// pots. Only an all-in caps a pot: each all-in amount of a seat still in
+/// the hand closes a pot that only seats which put in at least as much can
+/// win, and everything above the deepest all-in is one pot for the seats
+/// with chips behind. Folded seats' chips stay in the pots they reached but
+/// make them eligible for none.
+pub fn build_pots(contributions: &[u32], stacks: &[u32], folded: &[bool]) -> Vec<Pot> {
+    let seats = contributions.len();
+    let mut levels: Vec<u32> = (0..seats)
+        .filter(|&seat| !folded[seat] && stacks[seat] == 0 && contributions[seat] > 0)
+        .map(|seat| contributions[seat])
+        .collect();
+    levels.sort_unstable();
+    levels.dedup();
+
+    let mut pots = Vec::new();
+    let mut floor = 0;
+    for level in levels {
+        let amount = contributions
+            .iter()
+            .map(|&put_in| put_in.clamp(floor, level) - floor)
+            .sum();
+        let eligible = (0..seats)
+            .filter(|&seat| !folded[seat] && contributions[seat] >= level)
+            .collect();
+        pots.push(Pot { amount, eligible });
+        floor = level;
+    }
+
+    let amount = contributions
+        .iter()
+        .map(|&put_in| put_in.saturating_sub(floor))
+        .sum();
+    let eligible: Vec<SeatIndex> = (0..seats)
+        .filter(|&seat| !folded[seat] && stacks[seat] > 0)
+        .collect();
+    if amount == 0 {
+        return pots;
+    }
+    match pots.last_mut() {
+        // Folded chips above the deepest all-in with nobody left to win
+        // them go to the last pot
+        Some(last) if eligible.is_empty() => last.amount += amount,
+        _ => pots.push(Pot { amount, eligible }),
+    }
+    pots
+}
+
+/// "Main pot" for the first pot and "Side pot N" for the rest.
+pub fn pot_name(index: usize) -> String {
+    match index {
+        0 => "Main pot".to_string(),
+        _ => format!("Side pot {}", index),
+    }
+}
+
+/// Everything one seat is allowed to know when it has to act: its own hole
+/// cards, the revealed board, stacks, pot and the betting so far. Agents
+/// receive this instead of [`GameStateResource`], which also holds the
+/// opponents' cards and the undealt deck.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerView {
+    pub seat: SeatIndex,
+    pub hole_cards: HoleCards,
+    pub board: Vec<Card>,
+    pub round: PokerRound,
+    pub hand_number: i32,
+    pub dealer_position: SeatIndex,
+    pub stacks: Vec<u32>,
+    pub bets: Vec<u32>,
+    /// Seats out of the hand, having folded or sat down busted.
+    pub folded: Vec<bool>,
+    pub current_bet: u32,
+    pub pot: u32,
+    pub big_blind: u32,
+    /// Size of an opening bet
+    pub bet_amount: u32,
+    /// Size of a raise on top of the current bet
+    pub raise_amount: u32,
+    pub history: Vec<ActionRecord>,
+    /// Tournament prize shares by place, empty outside a tournament
+    pub payouts: Vec<f64>,
+}
+
+impl PlayerView {
+    /// Builds the view of the hand as seen from `seat`.
+    pub fn new(game_state: &GameStateResource, config: &GameConfig, seat: SeatIndex) -> Self {
+        Self {
+            seat,
+            hole_cards: game_state.hole_cards[seat],
+            board: visible_community_cards(game_state).to_vec(),
+            round: game_state.current_round,
+            hand_number: game_state.hand_number,
+            dealer_position: game_state.dealer_position,
+            stacks: game_state.player_chips.clone(),
+            bets: game_state.player_bets.clone(),
+            folded: game_state.folded.clone(),
+            current_bet: game_state.current_bet,
+            pot: game_state.pot + game_state.pot_remainder,
+            big_blind: config.big_blind,
+            bet_amount: config.bet_amount,
+            raise_amount: config.raise_amount,
+            history: game_state.action_history.clone(),
+            payouts: config.payouts.clone(),
+        }
+    }
+
+    /// Chips this seat must add to match the current bet.
+    pub fn to_call(&self) -> u32 {
+        self.current_bet.saturating_sub(self.bets[self.seat])
+    }
+
+    /// Whether this seat has the dealer button.
+    pub fn is_dealer(&self) -> bool {
+        self.seat == self.dealer_position
+    }
+
+    /// The other seats still in the hand.
+    pub fn opponents(&self) -> impl Iterator<Item = SeatIndex> + '_ {
+        (0..self.stacks.len()).filter(|&seat| seat != self.seat && !self.folded[seat])
+    }
 }
 
 #[derive(Component)]
@@ -187,14 +434,19 @@ pub struct CommunityCard {
 #[derive(Component)]
 pub struct PotDisplay;
 
+/// The main pot and side pots, listed above [`PotDisplay`] once a hand
+/// has more than one pot.
 #[derive(Component)]
-pub struct HandNumberDisplay;
+pub struct SidePotsDisplay;
 
 #[derive(Component)]
-pub struct PlayerChipsDisplay;
+pub struct HandNumberDisplay;
 
+/// The chip count shown under a seat's label.
 #[derive(Component)]
-pub struct OpponentChipsDisplay;
+pub struct SeatChipsDisplay {
+    pub seat: SeatIndex,
+}
 
 #[derive(Component)]
 pub struct RoundDisplay;
@@ -202,14 +454,65 @@ pub struct RoundDisplay;
 #[derive(Component, Default)]
 pub struct ActionDisplay;
 
+#[derive(Component)]
+pub struct EquityDisplay;
+
+/// The blind level during a tournament and the results once it is over.
+#[derive(Component)]
+pub struct TournamentDisplay;
+
+/// Each player's profit during a cash game session and the bankrolls once
+/// the table closes.
+#[derive(Component)]
+pub struct SessionDisplay;
+
+#[derive(Component)]
+pub struct SettingsMenuDisplay;
+
+#[derive(Component)]
+pub struct DecisionPanelDisplay;
+
+/// The single source of randomness for dealing and AI decisions, seeded
+/// from [`GameConfig::rng_seed`] so a seeded game replays exactly.
+#[derive(Resource)]
+pub struct GameRng(pub StdRng);
+
+impl GameRng {
+    pub fn new(seed: Option<u64>) -> Self {
+        Self(seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64))
+    }
+}
+
+impl FromWorld for GameRng {
+    fn from_world(world: &mut World) -> Self {
+        Self::new(world.resource::<GameConfig>().rng_seed)
+    }
+}
+
+/// State of the AI settings menu: whether it is open and which row
+/// (profile or difficulty of a seat) is selected.
+#[derive(Resource, Default)]
+pub struct SettingsMenu {
+    pub open: bool,
+    pub selected_row: usize,
+}
+
+/// Whether the AI decision panel is shown.
+#[derive(Resource, Default)]
+pub struct DecisionPanel {
+    pub open: bool,
+}
+
 /// Represents all possible poker actions a player can take during a betting round.
 /// The derived `Ord` implementation follows standard poker action ordering.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum PokerAction {
     Check,
     Bet,
     Call,
     Raise,
+    /// Put every remaining chip in the pot.
+    AllIn,
     Fold,
 }
 
@@ -239,25 +542,29 @@ fn evaluate_hand_rank_score(hand_rank: HandRank, primary_value: u8) -> f32 {
     base_score + normalized
 }
 
+/// Returns the community cards that have been revealed in the current round.
+pub fn visible_community_cards(game_state: &GameStateResource) -> &[Card] {
+    community_cards_in(game_state, game_state.current_round)
+}
+
+/// Returns the community cards revealed by the time `round` is played.
+pub fn community_cards_in(game_state: &GameStateResource, round: PokerRound) -> &[Card] {
+    let revealed = match round {
+        PokerRound::PreFlop => 0,
+        PokerRound::Flop => FLOP_CARD_COUNT,
+        PokerRound::Turn => TURN_CARD_COUNT,
+        PokerRound::River | PokerRound::Showdown => RIVER_CARD_COUNT,
+    };
+    &game_state.community_cards[..revealed]
+}
+
 /// Evaluates hand strength as a value between 0.0 and 1.0
 /// where 1.0 is the strongest possible hand (royal flush).
-pub fn evaluate_current_hand_strength(game_state: &GameStateResource) -> f32 {
-    let player_idx = game_state.current_player;
-    let hole_cards = if player_idx == 0 {
-        game_state.p1_hole
-    } else {
-        game_state.p2_hole
-    };
-    let mut cards: Vec<Card> = hole_cards.to_vec();
+pub fn evaluate_current_hand_strength(view: &PlayerView) -> f32 {
+    let mut cards: Vec<Card> = view.hole_cards.to_vec();
 
     // Add community cards
-    cards.extend(
-        game_state
-            .community_cards
-            .iter()
-            .copied()
-            .filter(|card| !card.is_placeholder),
-    );
+    cards.extend(view.board.iter().copied());
 
     if cards.len() < 5 {
         // Preflop: simple evaluation based on card ranks
@@ -280,85 +587,152 @@ pub fn evaluate_current_hand_strength(game_state: &GameStateResource) -> f32 {
     }
 }
 
+/// Which rule in [`choose_action_based_on_strength`] produced the action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrengthBranch {
+    /// Below the fold threshold while facing a bet.
+    FoldWeak,
+    /// Too weak for the price the pot is offering.
+    FoldBadOdds,
+    /// Above the raise threshold.
+    Raise,
+    /// Above the raise threshold with no bet to raise.
+    Bet,
+    /// Above the call threshold and checking is free.
+    CheckMedium,
+    /// Above the call threshold with pot odds good enough to call.
+    CallMedium,
+    /// Marginal strength and checking is free.
+    CheckMarginal,
+    /// Marginal strength with very good pot odds.
+    CallMarginal,
+    /// No rule matched, so the first legal action was taken.
+    FirstAvailable,
+}
+
+impl StrengthBranch {
+    /// A short human-readable description of the rule.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::FoldWeak => "fold: below fold threshold",
+            Self::FoldBadOdds => "fold: weak hand, bad pot odds",
+            Self::Raise => "raise: above raise threshold",
+            Self::Bet => "bet: above raise threshold",
+            Self::CheckMedium => "check: above call threshold",
+            Self::CallMedium => "call: above call threshold, good pot odds",
+            Self::CheckMarginal => "check: marginal hand",
+            Self::CallMarginal => "call: marginal hand, very good pot odds",
+            Self::FirstAvailable => "no rule matched: first legal action",
+        }
+    }
+}
+
+/// The action [`choose_action_based_on_strength`] picked, together with
+/// every input that went into the choice.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrengthDecision {
+    pub action: PokerAction,
+    /// Strength from [`evaluate_current_hand_strength`] before adjustments.
+    pub raw_strength: f32,
+    pub position_bonus: f32,
+    pub preflop_adjustment: f32,
+    /// Strength compared against the profile's thresholds.
+    pub final_strength: f32,
+    /// Share of the pot after calling that the call costs.
+    pub pot_odds: f32,
+    pub branch: StrengthBranch,
+}
+
 /// Chooses an action based on hand strength, position, and pot odds.
 /// Uses a more sophisticated strategy considering multiple factors.
-pub fn choose_action_based_on_strength<'a>(
-    actions: &'a [PokerAction],
+#[allow(clippy::cast_precision_loss)]
+pub fn choose_action_based_on_strength(
+    actions: &[PokerAction],
     strength: f32,
-    game_state: &GameStateResource,
-) -> &'a PokerAction {
-    let current_bet = game_state.current_bet;
-    let player_bet = game_state.player_bets[game_state.current_player];
-    let to_call = current_bet.saturating_sub(player_bet);
-    let pot_size = game_state.pot + game_state.pot_remainder;
+    view: &PlayerView,
+    profile: &AiProfile,
+) -> StrengthDecision {
+    let to_call = view.to_call();
+    let pot_size = view.pot;
 
     // Calculate pot odds: ratio of call amount to total pot after call
     // Used for AI decision making, minor precision loss is acceptable
     let pot_odds = if to_call > 0 {
-        f32::from(to_call) / (f32::from(pot_size) + f32::from(to_call))
+        to_call as f32 / (pot_size as f32 + to_call as f32)
     } else {
         0.0
     };
 
     // Position advantage (dealer acts last)
-    let is_dealer = game_state.current_player == game_state.dealer_position;
-    let position_bonus = if is_dealer { AI_POSITION_BONUS } else { 0.0 };
+    let is_dealer = view.is_dealer();
+    let position_bonus = if is_dealer {
+        profile.position_bonus
+    } else {
+        0.0
+    };
 
     // Adjust strength based on position and pot odds
     let adjusted_strength = (strength + position_bonus).min(1.0);
 
     // Preflop adjustments
-    let preflop_adjustment = if game_state.current_round == PokerRound::PreFlop {
+    let preflop_adjustment = if view.round == PokerRound::PreFlop {
         // Be more aggressive preflop with position
         if is_dealer {
-            AI_PREFLOP_DEALER_BONUS
+            profile.preflop_dealer_bonus
         } else {
-            AI_PREFLOP_NON_DEALER_PENALTY
+            profile.preflop_non_dealer_penalty
         }
     } else {
         0.0
     };
     let final_strength = (adjusted_strength + preflop_adjustment).clamp(0.0, 1.0);
 
+    let decide = |action: PokerAction, branch: StrengthBranch| StrengthDecision {
+        action,
+        raw_strength: strength,
+        position_bonus,
+        preflop_adjustment,
+        final_strength,
+        pot_odds,
+        branch,
+    };
+    let available = |action: PokerAction| actions.contains(&action);
+
     // Decision thresholds based on strength and pot odds
-    if final_strength < AI_STRENGTH_FOLD_THRESHOLD
-        || (final_strength < 0.4 && pot_odds > AI_POT_ODDS_BAD_THRESHOLD)
-    {
-        if to_call > 0 {
-            if let Some(fold_action) = actions.iter().find(|a| matches!(a, PokerAction::Fold)) {
-                return fold_action;
-            }
+    if to_call > 0 && available(PokerAction::Fold) {
+        if final_strength < profile.fold_threshold {
+            return decide(PokerAction::Fold, StrengthBranch::FoldWeak);
+        }
+        if final_strength < profile.bad_odds_fold_threshold && pot_odds > profile.pot_odds_bad {
+            return decide(PokerAction::Fold, StrengthBranch::FoldBadOdds);
         }
     }
 
-    if final_strength >= AI_STRENGTH_RAISE_THRESHOLD {
-        if let Some(raise_action) = actions.iter().find(|a| matches!(a, PokerAction::Raise)) {
-            return raise_action;
+    if final_strength >= profile.raise_threshold {
+        if available(PokerAction::Raise) {
+            return decide(PokerAction::Raise, StrengthBranch::Raise);
         }
-        if let Some(bet_action) = actions.iter().find(|a| matches!(a, PokerAction::Bet)) {
-            return bet_action;
+        if available(PokerAction::Bet) {
+            return decide(PokerAction::Bet, StrengthBranch::Bet);
         }
-    } else if final_strength >= AI_STRENGTH_CALL_THRESHOLD {
-        if let Some(check_action) = actions.iter().find(|a| matches!(a, PokerAction::Check)) {
-            return check_action;
+    } else if final_strength >= profile.call_threshold {
+        if available(PokerAction::Check) {
+            return decide(PokerAction::Check, StrengthBranch::CheckMedium);
         }
-        if pot_odds < AI_POT_ODDS_CALL_THRESHOLD {
-            if let Some(call_action) = actions.iter().find(|a| matches!(a, PokerAction::Call)) {
-                return call_action;
-            }
+        if pot_odds < profile.pot_odds_call && available(PokerAction::Call) {
+            return decide(PokerAction::Call, StrengthBranch::CallMedium);
         }
-    } else if final_strength >= 0.3 {
-        if let Some(check_action) = actions.iter().find(|a| matches!(a, PokerAction::Check)) {
-            return check_action;
+    } else if final_strength >= profile.marginal_threshold {
+        if available(PokerAction::Check) {
+            return decide(PokerAction::Check, StrengthBranch::CheckMarginal);
         }
-        if pot_odds < AI_POT_ODDS_GOOD_THRESHOLD {
-            if let Some(call_action) = actions.iter().find(|a| matches!(a, PokerAction::Call)) {
-                return call_action;
-            }
+        if pot_odds < profile.pot_odds_good && available(PokerAction::Call) {
+            return decide(PokerAction::Call, StrengthBranch::CallMarginal);
         }
     }
 
-    actions.first().unwrap_or(&PokerAction::Check)
+    let first = actions.first().copied().unwrap_or(PokerAction::Check);
+    decide(first, StrengthBranch::FirstAvailable)
 }
 
 /// Returns all valid actions for the current player given the game state.
@@ -368,17 +742,18 @@ pub fn get_valid_actions(game_state: &GameStateResource, config: &GameConfig) ->
     let player_chips = game_state.player_chips[player_idx];
     let player_bet = game_state.player_bets[player_idx];
     let current_bet = game_state.current_bet;
+    let call_amount = current_bet.saturating_sub(player_bet);
 
-    // Check is only available when no bet has been made this round
-    if current_bet == 0 {
+    // Checking is free once the player has matched the current bet, which
+    // also gives the big blind its option preflop
+    if call_amount == 0 {
         actions.push(PokerAction::Check);
+    } else if player_chips > 0 {
+        // A short stack may call all-in for less
+        actions.push(PokerAction::Call);
     }
 
     if current_bet > 0 {
-        let call_amount = current_bet - player_bet;
-        if player_chips >= call_amount && call_amount > 0 {
-            actions.push(PokerAction::Call);
-        }
         let raise_cost = call_amount + config.raise_amount;
         if player_chips >= raise_cost {
             actions.push(PokerAction::Raise);
@@ -387,6 +762,10 @@ pub fn get_valid_actions(game_state: &GameStateResource, config: &GameConfig) ->
         actions.push(PokerAction::Bet);
     }
 
+    if player_chips > call_amount {
+        actions.push(PokerAction::AllIn);
+    }
+
     actions.push(PokerAction::Fold);
     actions
 }
@@ -400,7 +779,7 @@ pub fn place_bet(
     new_current_bet: u32,
 ) {
     let player_idx = game_state.current_player;
-    if player_idx >= PLAYER_COUNT {
+    if player_idx >= game_state.seat_count() {
         error!("Invalid player index: {}", player_idx);
         return;
     }
@@ -409,6 +788,7 @@ pub fn place_bet(
     let actual_amount = amount.min(available_chips);
     game_state.player_chips[player_idx] -= actual_amount;
     game_state.player_bets[player_idx] += actual_amount;
+    game_state.contributions[player_idx] += actual_amount;
     game_state.pot += actual_amount;
 
     if is_raise {
@@ -416,49 +796,245 @@ pub fn place_bet(
     }
 }
 
-/// Advances to the next betting round when both players have matched bets.
+/// Advances to the next betting round once every player still in the hand
+/// has acted and matched the bet. A player who is all-in counts as done;
+/// once at most one player can still bet, the remaining streets are skipped
+/// and the hand goes straight to showdown.
 pub fn advance_street(game_state: &mut GameStateResource, config: &GameConfig) {
-    let both_players_matched_bet = game_state.player_bets[0] == game_state.current_bet
-        && game_state.player_bets[1] == game_state.current_bet;
+    // A street only ends once every seat has had a turn on it, so a check
+    // gives the opponents the chance to bet.
+    let round = game_state.current_round;
+    let settled = |seat: SeatIndex| {
+        let acted = game_state
+            .action_history
+            .iter()
+            .any(|r| r.round == round && r.seat == seat);
+        let all_in = game_state.player_chips[seat] == 0;
+        game_state.folded[seat]
+            || all_in
+            || (acted && game_state.player_bets[seat] == game_state.current_bet)
+    };
+    if !(0..game_state.seat_count()).all(settled) {
+        return;
+    }
 
-    let can_check = game_state.current_bet == 0;
+    return_uncalled_bet(game_state);
+    let can_bet = game_state
+        .seats_in_hand()
+        .filter(|&seat| game_state.player_chips[seat] > 0)
+        .count();
+    let next_round = match game_state.current_round {
+        _ if can_bet < 2 => PokerRound::Showdown,
+        PokerRound::PreFlop => PokerRound::Flop,
+        PokerRound::Flop => PokerRound::Turn,
+        PokerRound::Turn => PokerRound::River,
+        PokerRound::River | PokerRound::Showdown => PokerRound::Showdown,
+    };
+    if next_round == PokerRound::Showdown && round != PokerRound::Showdown {
+        game_state.showdown_timer = config.showdown_duration;
+    }
+    game_state.current_round = next_round;
 
-    if both_players_matched_bet || can_check {
-        match game_state.current_round {
-            PokerRound::PreFlop => game_state.current_round = PokerRound::Flop,
-            PokerRound::Flop => {
-                game_state.current_round = PokerRound::Turn;
-            }
-            PokerRound::Turn => {
-                game_state.current_round = PokerRound::River;
-            }
-            PokerRound::River => {
-                game_state.current_round = PokerRound::Showdown;
-                game_state.showdown_timer = config.showdown_duration;
-            }
-            PokerRound::Showdown => {}
-        }
+    if game_state.current_round != PokerRound::Showdown {
+        game_state.current_bet = 0;
+        game_state.player_bets.fill(0);
+        // The first player left of the button acts first after the flop
+        game_state.current_player = game_state.next_to_act(game_state.dealer_position);
+        game_state.pot_remainder = 0;
+    }
+}
+
+/// Gives back the part of the largest bet that no other player matched.
+fn return_uncalled_bet(game_state: &mut GameStateResource) {
+    let bets = &game_state.player_bets;
+    let Some(seat) = (0..bets.len()).max_by_key(|&seat| bets[seat]) else {
+        return;
+    };
+    let matched = (0..bets.len())
+        .filter(|&other| other != seat)
+        .map(|other| bets[other])
+        .max()
+        .unwrap_or(0);
+    let excess = bets[seat] - matched;
+    if excess == 0 {
+        return;
+    }
+    game_state.player_bets[seat] -= excess;
+    game_state.contributions[seat] -= excess;
+    game_state.player_chips[seat] += excess;
+    game_state.pot -= excess;
+    game_state.current_bet = game_state.player_bets[seat];
+}
 
-        if game_state.current_round != PokerRound::Showdown {
-            game_state.current_bet = 0;
-            game_state.player_bets = [0; PLAYER_COUNT];
-            game_state.current_player = game_state.dealer_position;
-            game_state.pot_remainder = 0;
+/// Posts the antes and blinds for a new hand. Every seat in the hand antes.
+/// Heads-up the dealer posts the small blind and acts first preflop; at a
+/// bigger table the two seats left of the button post the blinds and the
+/// seat after the big blind opens. Seats out of the hand are skipped, and
+/// short stacks post what they have.
+pub fn post_blinds(game_state: &mut GameStateResource, config: &GameConfig) {
+    let dealer = game_state.dealer_position;
+    let small_blind = if game_state.seats_in_hand().count() == HEADS_UP_SEATS {
+        dealer
+    } else {
+        game_state.next_in_hand(dealer)
+    };
+    let big_blind = game_state.next_in_hand(small_blind);
+    let seats: Vec<SeatIndex> = game_state.seats_in_hand().collect();
+    for seat in seats {
+        // Antes are dead money, not part of the seat's bet
+        let ante = config.ante.min(game_state.player_chips[seat]);
+        game_state.player_chips[seat] -= ante;
+        game_state.contributions[seat] += ante;
+        game_state.pot += ante;
+    }
+    let blinds = [
+        (small_blind, config.small_blind),
+        (big_blind, config.big_blind),
+    ];
+    for (seat, blind) in blinds {
+        let posted = blind.min(game_state.player_chips[seat]);
+        game_state.player_chips[seat] -= posted;
+        game_state.player_bets[seat] += posted;
+        game_state.contributions[seat] += posted;
+        game_state.pot += posted;
+    }
+    game_state.current_bet = game_state.player_bets.iter().copied().max().unwrap_or(0);
+    game_state.current_player = game_state.next_to_act(big_blind);
+    // The antes and blinds may have left nobody able to bet
+    advance_street(game_state, config);
+}
+
+/// Resets the table for the next hand: refills the stacks once only one
+/// player has chips left, moves the button past busted seats, posts the
+/// blinds and deals the cards.
+pub fn begin_hand(game_state: &mut GameStateResource, config: &GameConfig, rng: &mut dyn RngCore) {
+    game_state.pot = 0;
+    game_state.pot_remainder = 0;
+    game_state.current_round = PokerRound::PreFlop;
+    game_state.last_action = "New hand".to_string();
+    if game_state.hand_number > 0 {
+        game_state.hand_number += 1;
+    } else {
+        game_state.hand_number = 1;
+    }
+    game_state.showdown_timer = 0.0;
+    let seats = game_state.seat_count();
+    game_state.player_bets = vec![0; seats];
+    game_state.contributions = vec![0; seats];
+    game_state.hole_cards = vec![HoleCards::default(); seats];
+    game_state.last_explanations = vec![None; seats];
+    game_state.current_bet = 0;
+    game_state.winner = None;
+    game_state.last_winner_message = String::new();
+    game_state.action_history.clear();
+    game_state.hand_log.clear();
+    if game_state
+        .player_chips
+        .iter()
+        .filter(|&&chips| chips > 0)
+        .count()
+        < 2
+    {
+        // One player has all the chips: start a new match with fresh stacks
+        game_state.player_chips.fill(config.starting_chips);
+        game_state.last_action = "New match".to_string();
+    }
+    game_state.folded = game_state
+        .player_chips
+        .iter()
+        .map(|&chips| chips == 0)
+        .collect();
+    game_state.dealer_position = game_state.next_in_hand(game_state.dealer_position);
+    post_blinds(game_state, config);
+
+    // Enough for every hole card plus the board
+    let cards_needed = config.min_cards_for_reshuffle.max(2 * seats + 5);
+    if game_state.deck.cards_remaining() < cards_needed {
+        game_state.deck = Deck::with_rng(rng);
+    }
+    deal_cards(game_state);
+}
+
+/// Deals hole cards to every seat in the hand and then the whole board
+/// from the deck.
+fn deal_cards(game_state: &mut GameStateResource) {
+    let next_card = |game_state: &mut GameStateResource| {
+        draw_card(game_state).unwrap_or_else(|e| {
+            error!("Critical: Failed to draw card: {}", e);
+            Card::default()
+        })
+    };
+    for seat in 0..game_state.seat_count() {
+        if !game_state.folded[seat] {
+            game_state.hole_cards[seat] = [next_card(game_state), next_card(game_state)];
         }
     }
+    game_state.community_cards = std::array::from_fn(|_| next_card(game_state));
 }
 
-/// Performs the AI's betting action based on hand strength evaluation.
-pub fn perform_validated_action(game_state: &mut GameStateResource, config: &GameConfig) {
+/// Asks `agent` for the current player's action and applies it.
+/// An action outside the legal set is replaced by a check when checking is
+/// free, and by a fold otherwise.
+pub fn perform_validated_action(
+    game_state: &mut GameStateResource,
+    config: &GameConfig,
+    agent: &mut dyn PokerAgent,
+    rng: &mut dyn RngCore,
+) {
     let actions = get_valid_actions(game_state, config);
     if actions.is_empty() {
         return;
     }
 
-    let hand_strength = evaluate_current_hand_strength(game_state);
-    let action = choose_action_based_on_strength(&actions, hand_strength, game_state);
+    let seat = game_state.current_player;
+    let view = PlayerView::new(game_state, config, seat);
+    let mut action = agent.choose_action(&view, &actions, rng);
+    let mut explanation = agent.explain();
+    if !actions.contains(&action) {
+        let fallback = if actions.contains(&PokerAction::Check) {
+            PokerAction::Check
+        } else {
+            PokerAction::Fold
+        };
+        warn!(
+            "{} agent chose an illegal action for P{}; using {:?}",
+            agent.name(),
+            seat + 1,
+            fallback
+        );
+        if let Some(explanation) = explanation.as_mut() {
+            explanation
+                .notes
+                .push(format!("illegal {:?} replaced by {:?}", action, fallback));
+        }
+        action = fallback;
+    }
 
+    let reason = explanation
+        .as_ref()
+        .map_or_else(|| "no explanation".to_string(), Explanation::to_string);
+    let entry = format!(
+        "Hand {} {} P{} ({}) {:?}: {}",
+        game_state.hand_number,
+        game_state.current_round,
+        seat + 1,
+        agent.name(),
+        action,
+        reason
+    );
+    info!("{}", entry);
+    game_state.hand_log.push(entry);
+    game_state.last_explanations[seat] = explanation;
+
+    apply_action(game_state, config, action);
+}
+
+/// Applies a legal `action` for the current player and passes the turn,
+/// advancing the street when the betting round is complete.
+pub fn apply_action(game_state: &mut GameStateResource, config: &GameConfig, action: PokerAction) {
     let player_idx = game_state.current_player;
+    let round = game_state.current_round;
+    let chips_before = game_state.player_chips[player_idx];
     match action {
         PokerAction::Check => {
             game_state.last_action = format!("P{}: Check", player_idx + 1);
@@ -505,25 +1081,42 @@ pub fn perform_validated_action(game_state: &mut GameStateResource, config: &Gam
                 game_state.last_action = format!("P{}: Check (no chips)", player_idx + 1);
             }
         }
+        PokerAction::AllIn => {
+            let all_in_amount = game_state.player_chips[player_idx];
+            let total_bet = game_state.player_bets[player_idx] + all_in_amount;
+            let is_raise = total_bet > game_state.current_bet;
+            place_bet(game_state, all_in_amount, is_raise, total_bet);
+            game_state.last_action = format!("P{}: All-in ${}", player_idx + 1, total_bet);
+        }
         PokerAction::Fold => {
-            let winner = (game_state.current_player + 1) % 2;
-            game_state.winner = Some(winner);
-            let total_pot = game_state.pot + game_state.pot_remainder;
-            game_state.player_chips[winner] += total_pot;
-            game_state.last_winner_message = format!(
-                "P{} folds - P{} wins",
-                game_state.current_player + 1,
-                winner + 1
-            );
-            game_state.pot = 0;
-            game_state.pot_remainder = 0;
-            game_state.current_round = PokerRound::Showdown;
-            game_state.showdown_timer = config.fold_showdown_duration;
-            return;
+            game_state.folded[player_idx] = true;
+            game_state.last_action = format!("P{}: Fold", player_idx + 1);
+            let remaining: Vec<SeatIndex> = game_state.seats_in_hand().collect();
+            if let [winner] = remaining[..] {
+                game_state.winner = Some(winner);
+                let total_pot = game_state.pot + game_state.pot_remainder;
+                game_state.player_chips[winner] += total_pot;
+                game_state.last_winner_message =
+                    format!("P{} folds - P{} wins", player_idx + 1, winner + 1);
+                game_state.pot = 0;
+                game_state.pot_remainder = 0;
+                game_state.current_round = PokerRound::Showdown;
+                game_state.showdown_timer = config.fold_showdown_duration;
+            }
         }
     }
 
-    game_state.current_player = (game_state.current_player + 1) % PLAYER_COUNT;
+    game_state.action_history.push(ActionRecord {
+        seat: player_idx,
+        round,
+        action,
+        amount: chips_before - game_state.player_chips[player_idx],
+    });
+    if game_state.winner.is_some() {
+        return;
+    }
+
+    game_state.current_player = game_state.next_to_act(player_idx);
     advance_street(game_state, config);
 }
 
@@ -539,51 +1132,279 @@ pub fn draw_card(game_state: &mut GameStateResource) -> Result<Card, &'static st
     }
 }
 
-/// Distributes the pot to the winning player and clears the pot.
-pub fn distribute_pot(game_state: &mut GameStateResource, winner: usize) {
-    let total_pot = game_state.pot + game_state.pot_remainder;
-    game_state.player_chips[winner] += total_pot;
-    game_state.last_winner_message = if winner == 0 { "P1 wins" } else { "P2 wins" }.to_string();
-    game_state.pot = 0;
-    game_state.pot_remainder = 0;
-}
+/// Splits `amount` evenly between the winners. Any odd chips that cannot
+/// be split go one at a time to the winners closest to the dealer, starting
+/// with the dealer (following standard poker rules).
+fn split_pot(game_state: &mut GameStateResource, amount: u32, winners: &[SeatIndex]) {
+    if winners.is_empty() {
+        return;
+    }
+
+    // Safe: a table never has more than a handful of seats
+    let share_count = winners.len() as u32;
+    let share = amount / share_count;
+    let mut odd_chips = amount % share_count;
 
-/// Splits the pot between both players in case of a tie and clears the pot.
-/// In case of an odd pot, the extra chip goes to the dealer (following standard poker rules).
-pub fn split_pot(game_state: &mut GameStateResource) {
-    let total_pot = game_state.pot + game_state.pot_remainder;
-    let split_amount = total_pot / 2;
-    let remainder = total_pot % 2;
     let dealer = game_state.dealer_position;
-    let other_player = (dealer + 1) % PLAYER_COUNT;
+    let mut by_position: Vec<SeatIndex> = winners.to_vec();
+    let seats = game_state.seat_count();
+    by_position.sort_by_key(|&seat| (seat + seats - dealer) % seats);
+    for seat in by_position {
+        let bonus = u32::from(odd_chips > 0);
+        odd_chips -= bonus;
+        game_state.player_chips[seat] += share + bonus;
+    }
+}
+
+/// Describes who won each pot, e.g. "P2 wins" or "Main pot: P1, Side pot
+/// 1: P2 and P3".
+fn winner_message(results: &[Vec<SeatIndex>]) -> String {
+    let names = |winners: &[SeatIndex]| {
+        let names: Vec<String> = winners
+            .iter()
+            .map(|seat| format!("P{}", seat + 1))
+            .collect();
+        names.join(" and ")
+    };
+    match results {
+        [winners] => match winners.as_slice() {
+            [winner] => format!("P{} wins", winner + 1),
+            _ => "Split pot".to_string(),
+        },
+        _ => results
+            .iter()
+            .enumerate()
+            .map(|(index, winners)| format!("{}: {}", pot_name(index), names(winners)))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+/// Splits the pot into the main pot and side pots and awards each to the
+/// best hands among the seats eligible for it.
+pub fn process_showdown_result(game_state: &mut GameStateResource) {
+    let mut results = Vec::new();
+    for pot in game_state.pots() {
+        let hands: Vec<HoleCards> = pot
+            .eligible
+            .iter()
+            .map(|&seat| game_state.hole_cards[seat])
+            .collect();
+        let tiers = rank_players(&hands, &game_state.community_cards);
+        let Some(best) = tiers.first() else {
+            continue;
+        };
+        let winners: Vec<SeatIndex> = best.iter().map(|&index| pot.eligible[index]).collect();
+        split_pot(game_state, pot.amount, &winners);
+        results.push(winners);
+    }
+    if results.is_empty() {
+        return;
+    }
 
-    // Dealer gets the remainder chip (if any) per standard poker rules
-    game_state.player_chips[dealer] += split_amount + remainder;
-    game_state.player_chips[other_player] += split_amount;
     game_state.pot = 0;
     game_state.pot_remainder = 0;
-    game_state.last_winner_message = "Split pot".to_string();
+    // One winner of every pot is the hand's winner
+    game_state.winner = match results[0].as_slice() {
+        [winner] if results.iter().all(|winners| winners == &[*winner]) => Some(*winner),
+        _ => None,
+    };
+    game_state.last_winner_message = winner_message(&results);
 }
 
-/// Processes the showdown result and awards the pot to the winner(s).
-pub fn process_showdown_result(game_state: &mut GameStateResource) {
-    let result = determine_winner(
-        &game_state.p1_hole,
-        &game_state.p2_hole,
-        &game_state.community_cards,
-    );
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poker_logic::parse_cards;
 
-    match result {
-        0 => {
-            game_state.winner = Some(0);
-            distribute_pot(game_state, 0);
-        }
-        1 => {
-            game_state.winner = Some(1);
-            distribute_pot(game_state, 1);
-        }
-        _ => {
-            split_pot(game_state);
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(33)
+    }
+
+    #[test]
+    fn test_blinds_and_called_all_in_run_out_to_showdown() {
+        let config = GameConfig::default();
+        let mut game_state = GameStateResource::with_stacks(vec![1000, 400]);
+        post_blinds(&mut game_state, &config);
+        assert_eq!(game_state.player_bets, [25, 50]);
+        assert_eq!(game_state.current_player, 0);
+        assert!(get_valid_actions(&game_state, &config).contains(&PokerAction::AllIn));
+
+        apply_action(&mut game_state, &config, PokerAction::Call);
+        // The big blind gets its option after the small blind completes
+        assert_eq!(game_state.current_round, PokerRound::PreFlop);
+        assert_eq!(
+            get_valid_actions(&game_state, &config)[..2],
+            [PokerAction::Check, PokerAction::Raise]
+        );
+        apply_action(&mut game_state, &config, PokerAction::Check);
+        assert_eq!(game_state.current_round, PokerRound::Flop);
+        assert_eq!(game_state.current_player, 1);
+
+        // A check does not end the street: it only does once every seat
+        // has had a turn on it
+        apply_action(&mut game_state, &config, PokerAction::Check);
+        assert_eq!(game_state.current_round, PokerRound::Flop);
+        apply_action(&mut game_state, &config, PokerAction::AllIn);
+        apply_action(&mut game_state, &config, PokerAction::Call);
+        // The uncalled part of the shove goes back and the board runs out
+        assert_eq!(game_state.current_round, PokerRound::Showdown);
+        assert_eq!(game_state.pot, 800);
+        assert_eq!(game_state.player_chips, [600, 0]);
+    }
+
+    #[test]
+    fn test_button_and_blinds_skip_busted_seats() {
+        let config = GameConfig::default();
+        let mut game_state = GameStateResource::with_stacks(vec![1000, 0, 1000, 1000]);
+        game_state.dealer_position = 3;
+        begin_hand(&mut game_state, &config, &mut rng());
+
+        assert_eq!(game_state.dealer_position, 0);
+        assert_eq!(game_state.folded, [false, true, false, false]);
+        assert_eq!(game_state.player_bets, [0, 0, 25, 50]);
+        assert_eq!(game_state.current_player, 0);
+
+        // With only two players left the button posts the small blind
+        let mut game_state = GameStateResource::with_stacks(vec![0, 1000, 1000]);
+        begin_hand(&mut game_state, &config, &mut rng());
+        assert_eq!(game_state.dealer_position, 1);
+        assert_eq!(game_state.player_bets, [0, 25, 50]);
+        assert_eq!(game_state.current_player, 1);
+    }
+
+    #[test]
+    fn test_antes_go_in_the_pot_but_not_the_bets() {
+        let config = GameConfig {
+            ante: 10,
+            ..GameConfig::default()
+        };
+        let mut game_state = GameStateResource::with_stacks(vec![1000, 5, 1000]);
+        post_blinds(&mut game_state, &config);
+        assert_eq!(game_state.player_bets, [0, 0, 50]);
+        assert_eq!(game_state.contributions, [10, 5, 60]);
+        assert_eq!(game_state.pot, 75);
+        assert_eq!(game_state.current_bet, 50);
+    }
+
+    #[test]
+    fn test_multiway_fold_passes_the_action_on() {
+        let config = GameConfig::default();
+        let mut game_state = GameStateResource::with_stacks(vec![1000; 3]);
+        post_blinds(&mut game_state, &config);
+        assert_eq!(game_state.player_bets, [0, 25, 50]);
+        assert_eq!(game_state.current_player, 0);
+
+        apply_action(&mut game_state, &config, PokerAction::Fold);
+        assert_eq!(game_state.winner, None);
+        assert_eq!(game_state.current_round, PokerRound::PreFlop);
+        assert_eq!(game_state.current_player, 1);
+
+        apply_action(&mut game_state, &config, PokerAction::Call);
+        apply_action(&mut game_state, &config, PokerAction::Check);
+        assert_eq!(game_state.current_round, PokerRound::Flop);
+        assert_eq!(game_state.current_player, 1);
+
+        apply_action(&mut game_state, &config, PokerAction::Bet);
+        apply_action(&mut game_state, &config, PokerAction::Fold);
+        assert_eq!(game_state.winner, Some(1));
+        assert_eq!(game_state.last_winner_message, "P3 folds - P2 wins");
+        assert_eq!(game_state.player_chips, [1000, 1050, 950]);
+    }
+
+    #[test]
+    fn test_folded_seats_cannot_win_the_showdown() {
+        let mut game_state = GameStateResource::with_stacks(vec![1000; 3]);
+        let cards = parse_cards("AsAd 7h7c KhQd 2c 7d 9s Td 3h").unwrap();
+        game_state.hole_cards = vec![
+            [cards[0], cards[1]],
+            [cards[2], cards[3]],
+            [cards[4], cards[5]],
+        ];
+        game_state.community_cards.copy_from_slice(&cards[6..]);
+        game_state.folded[1] = true;
+        game_state.contributions = vec![100; 3];
+        game_state.pot = 300;
+
+        process_showdown_result(&mut game_state);
+        assert_eq!(game_state.winner, Some(0));
+        assert_eq!(game_state.player_chips, [1300, 1000, 1000]);
+    }
+
+    #[test]
+    fn test_all_ins_of_different_sizes_make_side_pots() {
+        // Seat 0 is all-in for 100, seat 1 for 300, seat 2 covers and seat 3
+        // folded after putting in 50
+        let pots = build_pots(
+            &[100, 300, 500, 50],
+            &[0, 0, 500, 950],
+            &[false, false, false, true],
+        );
+        assert_eq!(
+            pots,
+            [
+                Pot {
+                    amount: 350,
+                    eligible: vec![0, 1, 2]
+                },
+                Pot {
+                    amount: 400,
+                    eligible: vec![1, 2]
+                },
+                Pot {
+                    amount: 200,
+                    eligible: vec![2]
+                },
+            ]
+        );
+        assert_eq!(build_pots(&[200, 200], &[0, 0], &[false, false]).len(), 1);
+    }
+
+    #[test]
+    fn test_unequal_bets_without_an_all_in_make_one_pot() {
+        // The blinds, and a raise nobody has answered yet
+        assert_eq!(
+            build_pots(&[25, 50], &[975, 950], &[false, false]),
+            [Pot {
+                amount: 75,
+                eligible: vec![0, 1]
+            }]
+        );
+        assert_eq!(
+            build_pots(&[150, 50, 0], &[850, 950, 1000], &[false, false, false]),
+            [Pot {
+                amount: 200,
+                eligible: vec![0, 1, 2]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_each_pot_goes_to_the_best_eligible_hand() {
+        let config = GameConfig::default();
+        let mut game_state = GameStateResource::with_stacks(vec![100, 300, 1000]);
+        let cards = parse_cards("AsAd KhKc 7h2c Qd 9s 5c 3h 4d").unwrap();
+        game_state.hole_cards = vec![
+            [cards[0], cards[1]],
+            [cards[2], cards[3]],
+            [cards[4], cards[5]],
+        ];
+        game_state.community_cards.copy_from_slice(&cards[6..]);
+        post_blinds(&mut game_state, &config);
+
+        for action in [PokerAction::AllIn, PokerAction::AllIn, PokerAction::Call] {
+            apply_action(&mut game_state, &config, action);
         }
+        assert_eq!(game_state.current_round, PokerRound::Showdown);
+        assert_eq!(game_state.pot, 700);
+
+        process_showdown_result(&mut game_state);
+        // Aces take the main pot, kings the side pot the aces could not match
+        assert_eq!(game_state.player_chips, [300, 400, 700]);
+        assert_eq!(game_state.winner, None);
+        assert_eq!(
+            game_state.last_winner_message,
+            "Main pot: P1, Side pot 1: P2"
+        );
     }
 }
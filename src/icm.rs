@@ -0,0 +1,287 @@
+//! Independent Chip Model and tournament payout structures.
+//!
+//! In a tournament chips are not worth their face value: the winner cannot
+//! collect more than first prize, and busting costs every prize still to
+//! come. ICM turns stacks into shares of the prize pool by assuming a
+//! player finishes first with probability equal to their share of the
+//! chips, then repeating the same for the next place among the players
+//! left (the Malmuth-Harville model).
+//!
+//! Payouts are given as tables of prize percentages by finishing place,
+//! one per field size, grouped into named structures. They are read from
+//! `config/payouts.json`, and the copy compiled into the binary is used
+//! when that file is missing or invalid.
+
+use crate::poker_logic::SeatIndex;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// The payout structures compiled into the binary.
+const BUILT_IN_PAYOUTS: &str = include_str!("../config/payouts.json");
+
+/// Each player's expected share of the prize pool. `payouts` lists the
+/// share paid for each place from first down; places past its end pay
+/// nothing. Players without chips finish below everyone with chips, so a
+/// busted player still listed collects the prize for the place they went
+/// out in only when they are the sole player without chips.
+#[allow(clippy::cast_precision_loss)]
+pub fn icm_equity(stacks: &[u32], payouts: &[f64]) -> Vec<f64> {
+    let players = stacks.len();
+    assert!(players <= 16, "ICM is computed for at most 16 players");
+    let full = (1usize << players) - 1;
+    // equity[mask][player]: what each player in `mask` collects from the
+    // places left once everyone outside `mask` has finished above them
+    let mut equity = vec![vec![0.0; players]; full + 1];
+    for mask in 1..=full {
+        let members: Vec<usize> = (0..players).filter(|&p| mask & (1 << p) != 0).collect();
+        let prize = payouts.get(players - members.len()).copied().unwrap_or(0.0);
+        let chips: u64 = members.iter().map(|&p| u64::from(stacks[p])).sum();
+        for &first in &members {
+            let chance = if chips == 0 {
+                1.0 / members.len() as f64
+            } else {
+                f64::from(stacks[first]) / chips as f64
+            };
+            if chance == 0.0 {
+                continue;
+            }
+            // `first` takes this place and the rest play on for the next;
+            // `first` is not in `rest`, so collects nothing more there
+            let rest = mask & !(1 << first);
+            let (smaller, this) = equity.split_at_mut(mask);
+            for &player in &members {
+                this[0][player] += chance * smaller[rest][player];
+            }
+            this[0][first] += chance * prize;
+        }
+    }
+    equity.swap_remove(full)
+}
+
+/// How much more equity than the pot odds ask for `seat` needs to call
+/// `to_call` into `pot` against `opponent`, once chips are valued by ICM.
+///
+/// The call is treated as the last chips to go in: either `seat` wins the
+/// pot or `opponent` collects it, and everyone else's stack stays as it
+/// is. Near the bubble losing costs more prize equity than winning gains,
+/// so the premium is positive; it can be negative for a short stack with
+/// little left to lose.
+#[allow(clippy::cast_precision_loss)]
+pub fn call_risk_premium(
+    stacks: &[u32],
+    seat: SeatIndex,
+    opponent: SeatIndex,
+    pot: u32,
+    to_call: u32,
+    payouts: &[f64],
+) -> f64 {
+    let to_call = to_call.min(stacks[seat]);
+    if to_call == 0 || payouts.is_empty() {
+        return 0.0;
+    }
+    let equity_after = |ours: u32, theirs: u32| {
+        let mut after = stacks.to_vec();
+        after[seat] = ours;
+        after[opponent] = theirs;
+        icm_equity(&after, payouts)[seat]
+    };
+    let fold = equity_after(stacks[seat], stacks[opponent] + pot);
+    let win = equity_after(stacks[seat] + pot, stacks[opponent]);
+    let lose = equity_after(stacks[seat] - to_call, stacks[opponent] + pot + to_call);
+    if win <= lose {
+        return 0.0;
+    }
+    let icm_required = ((fold - lose) / (win - lose)).clamp(0.0, 1.0);
+    let chip_required = f64::from(to_call) / f64::from(pot + to_call);
+    icm_required - chip_required
+}
+
+/// Prize percentages for fields of up to `max_entrants` players.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayoutTable {
+    pub max_entrants: usize,
+    /// Percentage of the prize pool for each place from first down
+    pub places: Vec<f64>,
+}
+
+/// A named set of payout tables covering different field sizes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayoutStructure {
+    pub name: String,
+    pub tables: Vec<PayoutTable>,
+}
+
+impl PayoutStructure {
+    /// Shares of the prize pool by place for a field of `entrants`, from
+    /// the smallest table that fits (or the largest table). The shares add
+    /// up to 1 and there are never more paid places than entrants.
+    pub fn payouts(&self, entrants: usize) -> Vec<f64> {
+        let Some(table) = self
+            .tables
+            .iter()
+            .filter(|table| table.max_entrants >= entrants)
+            .min_by_key(|table| table.max_entrants)
+            .or_else(|| self.tables.iter().max_by_key(|table| table.max_entrants))
+        else {
+            return vec![1.0];
+        };
+        let places = &table.places[..table.places.len().min(entrants.max(1))];
+        let total: f64 = places.iter().sum();
+        places.iter().map(|share| share / total).collect()
+    }
+}
+
+/// Error returned when the payout structures cannot be loaded.
+#[derive(Debug)]
+pub enum PayoutConfigError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    /// The file lists no payout structures.
+    Empty,
+    /// A structure is missing tables, or a table pays no places or a
+    /// place a share that is not positive.
+    Invalid(String),
+}
+
+impl fmt::Display for PayoutConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayoutConfigError::Io(err) => write!(f, "cannot access payout config: {}", err),
+            PayoutConfigError::Parse(err) => write!(f, "invalid payout config: {}", err),
+            PayoutConfigError::Empty => write!(f, "payout config needs at least one structure"),
+            PayoutConfigError::Invalid(name) => {
+                write!(f, "payout structure '{}' needs positive prizes", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PayoutConfigError {}
+
+/// All payout structures a tournament can choose from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayoutConfig {
+    pub structures: Vec<PayoutStructure>,
+}
+
+impl PayoutConfig {
+    /// Parses a configuration from JSON.
+    pub fn from_json(json: &str) -> Result<Self, PayoutConfigError> {
+        let config: Self = serde_json::from_str(json).map_err(PayoutConfigError::Parse)?;
+        if config.structures.is_empty() {
+            return Err(PayoutConfigError::Empty);
+        }
+        for structure in &config.structures {
+            let valid = !structure.tables.is_empty()
+                && structure.tables.iter().all(|table| {
+                    !table.places.is_empty() && table.places.iter().all(|&share| share > 0.0)
+                });
+            if !valid {
+                return Err(PayoutConfigError::Invalid(structure.name.clone()));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Reads a configuration file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PayoutConfigError> {
+        let json = std::fs::read_to_string(path).map_err(PayoutConfigError::Io)?;
+        Self::from_json(&json)
+    }
+
+    /// The payout structures compiled into the binary.
+    pub fn built_in() -> Self {
+        Self::from_json(BUILT_IN_PAYOUTS).expect("built-in payout config is valid")
+    }
+
+    /// Loads `path`, falling back to the built-in structures if the file is
+    /// missing or invalid.
+    pub fn load_or_built_in(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Self::built_in();
+        }
+        Self::load(path).unwrap_or_else(|err| {
+            warn!("{} ({}); using built-in payouts", err, path.display());
+            Self::built_in()
+        })
+    }
+
+    /// Finds a structure by name, ignoring case.
+    pub fn structure(&self, name: &str) -> Option<&PayoutStructure> {
+        self.structures
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_icm_matches_known_results() {
+        // Three left, 50/30/20: the chip leader's 50% of the chips is worth
+        // well under 50% of the prizes
+        let equity = icm_equity(&[5000, 3000, 2000], &[0.5, 0.3, 0.2]);
+        assert_close(&equity, &[0.38393, 0.3275, 0.28857]);
+
+        // Heads-up the remaining prize money splits in proportion to chips
+        let equity = icm_equity(&[3000, 1000], &[0.65, 0.35]);
+        assert_close(&equity, &[0.575, 0.425]);
+
+        // Four left with three paid: the bubble
+        let equity = icm_equity(&[4000, 3000, 2000, 1000], &[0.5, 0.3, 0.2]);
+        assert_close(&equity, &[0.33603, 0.29488, 0.23587, 0.13321]);
+    }
+
+    #[test]
+    fn test_icm_shares_equal_stacks_equally_and_pays_out_the_pool() {
+        let equity = icm_equity(&[1500; 6], &[0.65, 0.35]);
+        assert_close(&equity, &[1.0 / 6.0; 6]);
+
+        let equity = icm_equity(&[2200, 0, 700, 5100], &[0.5, 0.3, 0.2]);
+        assert!((equity.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(equity[1], 0.0);
+    }
+
+    #[test]
+    fn test_calls_near_the_bubble_need_more_than_pot_odds() {
+        // Calling off a medium stack against the chip leader with the short
+        // stack waiting to bust
+        let stacks = [6000, 3000, 600, 2400];
+        let bubble = call_risk_premium(&stacks, 1, 0, 3000, 3000, &[0.5, 0.3, 0.2]);
+        assert!(bubble > 0.05, "premium {}", bubble);
+        // Winner takes all is a chip-EV game
+        let chip_ev = call_risk_premium(&stacks, 1, 0, 3000, 3000, &[1.0]);
+        assert!(chip_ev.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_payout_tables_pick_the_field_size_and_normalize() {
+        let config = PayoutConfig::built_in();
+        let standard = config.structure("standard").unwrap();
+        assert_close(&standard.payouts(2), &[1.0]);
+        assert_close(&standard.payouts(6), &[0.65, 0.35]);
+        assert_close(&standard.payouts(9), &[0.5, 0.3, 0.2]);
+        assert_close(&standard.payouts(12), &[0.5, 0.3, 0.2]);
+
+        assert!(PayoutConfig::from_json(
+            r#"{"structures": [{"name": "Broken", "tables": [{"max_entrants": 6, "places": []}]}]}"#
+        )
+        .is_err());
+        assert!(matches!(
+            PayoutConfig::from_json(r#"{"structures": []}"#),
+            Err(PayoutConfigError::Empty)
+        ));
+    }
+}
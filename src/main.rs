@@ -1,34 +1,118 @@
 //! # Poker Simulation Game
 //!
-//! A heads-up Texas Hold'em poker simulation built with Bevy.
+//! A Texas Hold'em poker simulation built with Bevy, heads-up or at up to
+//! nine seats.
 //! Features AI opponents, smooth animations, and a complete poker rule implementation.
 
 use bevy::prelude::*;
 
+mod agent;
 mod ai;
 mod animation;
+mod arena;
+mod cash;
+mod cfr;
+mod cli;
 mod constants;
+mod equity;
+mod external;
 mod game;
+mod icm;
+mod net;
 mod poker_logic;
+mod profile;
+mod pushfold;
+mod range;
+mod stats;
+mod strategy;
+mod tournament;
+mod tuner;
 mod ui;
 
 use crate::game::*;
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Heads-Up Poker".into(),
-                resolution: (375.0, 812.0).into(),
-                resizable: false,
-                ..default()
-            }),
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => run_game(GameConfig::default(), cli::PlayMode::Match),
+        Some("play") => match cli::play_config(&args[1..]) {
+            Ok((config, mode)) => run_game(config, mode),
+            Err(message) => {
+                eprintln!("error: {}", message);
+                std::process::exit(1);
+            }
+        },
+        Some("connect") => match connect(&args[1..]) {
+            Ok(client) => run_client(client),
+            Err(message) => {
+                eprintln!("error: {}", message);
+                std::process::exit(1);
+            }
+        },
+        Some(_) => std::process::exit(cli::run(&args)),
+    }
+}
+
+/// Joins the server named in the `connect` arguments, as a spectator when
+/// `--spectate` is given.
+fn connect(args: &[String]) -> Result<net::NetClient, String> {
+    let address = args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .map_or(constants::NET_DEFAULT_SERVER, String::as_str);
+    let role = if args.iter().any(|arg| arg == "--spectate") {
+        net::Role::Spectator
+    } else {
+        net::Role::Player
+    };
+    net::NetClient::connect(address, role)
+        .map_err(|err| format!("cannot connect to {}: {}", address, err))
+}
+
+fn window_plugins() -> bevy::app::PluginGroupBuilder {
+    DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            title: "Texas Hold'em Poker".into(),
+            resolution: (375.0, 812.0).into(),
+            resizable: false,
             ..default()
-        }))
-        .init_resource::<GameConfig>()
+        }),
+        ..default()
+    })
+}
+
+/// Runs the local game between the configured AI seats, as a sit-and-go
+/// or a cash game if `mode` says so.
+fn run_game(config: GameConfig, mode: cli::PlayMode) {
+    let mut app = App::new();
+    match mode {
+        cli::PlayMode::Match => {}
+        cli::PlayMode::Tournament(tournament) => {
+            app.insert_resource(tournament);
+        }
+        cli::PlayMode::Cash(cash) => {
+            app.insert_resource(cash);
+        }
+    }
+    app.add_plugins(window_plugins())
+        .insert_resource(config)
         .init_resource::<ColorPalette>()
         .init_resource::<GameStateResource>()
-        .add_systems(Startup, ai::setup_game)
+        .init_resource::<GameRng>()
+        .init_resource::<profile::AiConfig>()
+        .init_resource::<agent::SeatAgents>()
+        .init_resource::<SettingsMenu>()
+        .init_resource::<DecisionPanel>()
+        .add_systems(
+            Startup,
+            (
+                ai::setup_game,
+                ui::spawn_settings_menu,
+                ui::spawn_decision_panel,
+                ui::spawn_tournament_display,
+                ui::spawn_session_display,
+            ),
+        )
         .add_systems(
             Update,
             (
@@ -40,6 +124,37 @@ fn main() {
                 ai::handle_showdown,
                 ui::update_card_visuals,
                 ui::update_ui,
+                ui::update_equity_display,
+                ui::update_tournament_display,
+                ui::update_session_display,
+                ui::handle_settings_input,
+                ui::update_settings_menu,
+                ui::handle_decision_panel_input,
+                ui::update_decision_panel,
+            )
+                .chain(),
+        )
+        .run();
+}
+
+/// Runs the window as a client of a networked table.
+fn run_client(client: net::NetClient) {
+    App::new()
+        .add_plugins(window_plugins())
+        .init_resource::<GameConfig>()
+        .init_resource::<ColorPalette>()
+        .init_resource::<GameStateResource>()
+        .insert_resource(client)
+        .add_systems(Startup, net::setup_client)
+        .add_systems(
+            Update,
+            (
+                net::receive_updates,
+                net::handle_action_input,
+                net::redraw_table,
+                animation::update_animations,
+                ui::update_ui,
+                ui::update_equity_display,
             )
                 .chain(),
         )
@@ -0,0 +1,1651 @@
+//! Heads-up play over TCP, between players on different machines or
+//! against a bot, with spectators watching.
+//!
+//! `poker_simulation serve` hosts the authoritative engine: it waits for a
+//! player on every open seat, deals, enforces the rules and tells each
+//! connection only what it may see. `poker_simulation connect` opens the
+//! game window as a client that draws the table from the server's messages
+//! and sends the player's actions back; with `--spectate` it only watches.
+//! A bot can connect instead of a window; its decisions use the same
+//! messages as the external bot protocol (see [`crate::external`]).
+//!
+//! # Protocol
+//!
+//! Both sides write one JSON object per line. A client opens by saying
+//! whether it plays or watches:
+//!
+//! ```text
+//! {"type":"join","role":"player"}
+//! ```
+//!
+//! The server answers with the seat the connection plays, `null` for a
+//! spectator, or turns a player away with a `goodbye` when the table is full.
+//! A player also gets a session to rejoin with if the connection drops:
+//!
+//! ```text
+//! {"type":"welcome","seat":1,"session":"9f86d081884c7d65"}
+//! ```
+//!
+//! Whenever the table changes the server sends the whole table as the
+//! connection sees it. Hole cards the viewer may not see are `null`:
+//!
+//! ```text
+//! {"type":"state","hand":3,"round":"flop","dealer":0,"to_act":1,
+//!  "stacks":[900,950],"bets":[0,0],"pot":150,"board":["7c","8d","9h"],
+//!  "hole_cards":[null,["Ah","Kd"]],"last_action":"P1: Check","result":null}
+//! ```
+//!
+//! Spectators see no hole cards until showdown. When the server runs with a
+//! spectator delay, for commentary, they receive each state that much later
+//! than the players, and once a hand is over its states show every hole
+//! card. The cards of a hand still being played are never sent, however
+//! short the delay.
+//!
+//! When a seat has to act it receives the `act` message of the external bot
+//! protocol, with two more fields: `time_ms`, the time for this decision,
+//! and `time_bank_ms`, the extra time the player has left for the match.
+//! The player answers with `{"action":"call","id":7}`. Thinking past
+//! `time_ms` draws on the time bank; once both have run out the seat checks
+//! if it can and folds otherwise. An unreadable or illegal answer folds.
+//! Players get a `hand_end` message after every hand.
+//!
+//! A player whose connection drops keeps their seat for a while, checking
+//! or folding when it is their turn. They come back by opening a new
+//! connection with
+//!
+//! ```text
+//! {"type":"rejoin","session":"9f86d081884c7d65"}
+//! ```
+//!
+//! and are sent the `welcome`, the current `state` and, when the seat is
+//! waiting on them, the `act` again. When the hand limit is reached or a
+//! player has not come back in time, everyone receives
+//! `{"type":"goodbye","reason":"..."}` and the match ends.
+
+use crate::agent::{DecisionBasis, Explanation, PokerAgent};
+use crate::constants::*;
+use crate::external::{
+    action_name, history, parse_action, reply_action, round_name, Failure, HistoryEntry,
+    LineReader, Observation, Reply,
+};
+use crate::game::*;
+use crate::poker_logic::{Card, Deck, PokerRound, SeatIndex};
+use bevy::prelude::*;
+use rand::{thread_rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The betting round a protocol name stands for.
+fn parse_round(name: &str) -> Option<PokerRound> {
+    [
+        PokerRound::PreFlop,
+        PokerRound::Flop,
+        PokerRound::Turn,
+        PokerRound::River,
+        PokerRound::Showdown,
+    ]
+    .into_iter()
+    .find(|&round| round_name(round) == name)
+}
+
+/// The table as one seat is allowed to see it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableState {
+    pub hand: i32,
+    pub round: String,
+    pub dealer: SeatIndex,
+    /// The seat whose turn it is, while the hand is being bet.
+    pub to_act: Option<SeatIndex>,
+    pub stacks: Vec<u32>,
+    pub bets: Vec<u32>,
+    pub pot: u32,
+    pub board: Vec<String>,
+    /// Each seat's hole cards, `None` while the viewer may not see them.
+    pub hole_cards: Vec<Option<Vec<String>>>,
+    pub last_action: String,
+    /// Who won, once the hand is over.
+    pub result: Option<String>,
+}
+
+impl TableState {
+    /// The table as seen from `viewer`'s seat, or by a spectator when it is
+    /// `None`; `reveal` shows every seat's cards.
+    pub fn new(game_state: &GameStateResource, viewer: Option<SeatIndex>, reveal: bool) -> Self {
+        let over = game_state.current_round == PokerRound::Showdown;
+        // A fold ends the hand without dealing the rest of the board
+        let board_round = match game_state.action_history.last() {
+            Some(record) if record.action == PokerAction::Fold => record.round,
+            _ => game_state.current_round,
+        };
+        Self {
+            hand: game_state.hand_number,
+            round: round_name(game_state.current_round).to_string(),
+            dealer: game_state.dealer_position,
+            to_act: (!over).then_some(game_state.current_player),
+            stacks: game_state.player_chips.clone(),
+            bets: game_state.player_bets.clone(),
+            pot: game_state.pot + game_state.pot_remainder,
+            board: community_cards_in(game_state, board_round)
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            hole_cards: (0..game_state.seat_count())
+                .map(|seat| {
+                    let hole = game_state.hole_cards[seat];
+                    (viewer == Some(seat) || reveal)
+                        .then(|| hole.iter().map(ToString::to_string).collect())
+                })
+                .collect(),
+            last_action: game_state.last_action.clone(),
+            result: (!game_state.last_winner_message.is_empty())
+                .then(|| game_state.last_winner_message.clone()),
+        }
+    }
+}
+
+/// A line the server sends.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    /// The seat the connection plays, `None` for a spectator, and the
+    /// session a player rejoins with.
+    Welcome {
+        seat: Option<SeatIndex>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        session: Option<String>,
+    },
+    State(TableState),
+    Act {
+        #[serde(flatten)]
+        observation: Observation,
+        time_ms: u128,
+        time_bank_ms: u128,
+    },
+    HandEnd {
+        history: Vec<HistoryEntry>,
+        showdown: bool,
+    },
+    Goodbye {
+        reason: String,
+    },
+}
+
+/// One end of a TCP connection, writing and reading JSON lines.
+struct Connection {
+    stream: TcpStream,
+    lines: LineReader,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        let reader = stream.try_clone()?;
+        Ok(Self {
+            stream,
+            lines: LineReader::spawn(reader),
+        })
+    }
+
+    fn peer(&self) -> String {
+        self.stream.peer_addr().map_or_else(
+            |_| "an unknown address".to_string(),
+            |peer| peer.to_string(),
+        )
+    }
+
+    fn send(&mut self, message: &impl Serialize) -> io::Result<()> {
+        let json = serde_json::to_string(message).map_err(io::Error::other)?;
+        writeln!(self.stream, "{}", json)?;
+        self.stream.flush()
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // Also ends the reader thread, which holds a clone of the stream
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+/// Where the connections of players rejoining their seats go, by session.
+type Sessions = Arc<Mutex<HashMap<String, Sender<Connection>>>>;
+
+/// What a seat plays when its player is out of time or away: a check when
+/// one is allowed, otherwise a fold.
+fn auto_action(actions: &[PokerAction], why: &str) -> (PokerAction, Explanation) {
+    let action = if actions.contains(&PokerAction::Check) {
+        PokerAction::Check
+    } else {
+        PokerAction::Fold
+    };
+    let mut explanation = Explanation::new(DecisionBasis::External { reason: None });
+    explanation
+        .notes
+        .push(format!("{}: auto-{}", why, action_name(action)));
+    (action, explanation)
+}
+
+/// A seat played by whoever is connected to it.
+pub struct RemoteAgent {
+    name: String,
+    seat: SeatIndex,
+    session: String,
+    connection: Connection,
+    /// Connections the player has rejoined on.
+    reconnects: Mutex<Receiver<Connection>>,
+    timeout: Duration,
+    time_bank: Duration,
+    reconnect_timeout: Duration,
+    /// When the connection dropped, while the seat waits for its player.
+    disconnected_at: Option<Instant>,
+    /// The last table sent, to bring a rejoining player up to date.
+    latest: Option<TableState>,
+    next_id: u64,
+    last: Option<Explanation>,
+}
+
+impl RemoteAgent {
+    /// Seats the player on `connection` and registers their session so
+    /// they can rejoin.
+    fn new(
+        connection: Connection,
+        seat: SeatIndex,
+        settings: &ServerSettings,
+        sessions: &Sessions,
+    ) -> Self {
+        let session = format!("{:016x}", thread_rng().next_u64());
+        let (sender, reconnects) = mpsc::channel();
+        sessions
+            .lock()
+            .expect("session lock poisoned")
+            .insert(session.clone(), sender);
+        let mut agent = Self {
+            name: format!("Remote ({})", connection.peer()),
+            seat,
+            session,
+            connection,
+            reconnects: Mutex::new(reconnects),
+            timeout: settings.action_timeout,
+            time_bank: settings.time_bank,
+            reconnect_timeout: settings.reconnect_timeout,
+            disconnected_at: None,
+            latest: None,
+            next_id: 0,
+            last: None,
+        };
+        agent.send(&ServerMessage::Welcome {
+            seat: Some(seat),
+            session: Some(agent.session.clone()),
+        });
+        agent
+    }
+
+    /// Sends `message`, noting a broken connection instead of failing.
+    fn send(&mut self, message: &ServerMessage) {
+        self.take_reconnect();
+        if let ServerMessage::State(state) = message {
+            self.latest = Some(state.clone());
+        }
+        if self.disconnected_at.is_none() && self.connection.send(message).is_err() {
+            self.lost();
+        }
+    }
+
+    fn lost(&mut self) {
+        self.disconnected_at.get_or_insert_with(Instant::now);
+    }
+
+    /// Switches to a connection the player has rejoined on, if there is one.
+    fn take_reconnect(&mut self) {
+        let rejoined = self
+            .reconnects
+            .lock()
+            .expect("reconnect lock poisoned")
+            .try_recv();
+        if let Ok(connection) = rejoined {
+            self.resume(connection);
+        }
+    }
+
+    /// Plays the seat over `connection` from now on, after bringing the
+    /// player up to date with the table.
+    fn resume(&mut self, connection: Connection) {
+        self.connection = connection;
+        self.disconnected_at = None;
+        let welcome = ServerMessage::Welcome {
+            seat: Some(self.seat),
+            session: Some(self.session.clone()),
+        };
+        let mut sent = self.connection.send(&welcome);
+        if let Some(state) = self.latest.clone() {
+            sent = sent.and(self.connection.send(&ServerMessage::State(state)));
+        }
+        if sent.is_err() {
+            self.lost();
+        }
+    }
+
+    /// Whether the player dropped and has not come back in time.
+    fn gone(&mut self) -> bool {
+        self.take_reconnect();
+        self.disconnected_at
+            .is_some_and(|at| at.elapsed() >= self.reconnect_timeout)
+    }
+
+    /// Waits for the player's answer to `act` until `deadline`, sending the
+    /// request again whenever they rejoin.
+    fn await_reply(
+        &mut self,
+        act: &ServerMessage,
+        id: u64,
+        deadline: Instant,
+    ) -> Result<Reply, Failure> {
+        self.send(act);
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if self.disconnected_at.is_some() {
+                self.await_return(remaining)?;
+                self.send(act);
+                continue;
+            }
+            match self.connection.lines.receive(id, remaining) {
+                Err(Failure::Crashed) => self.lost(),
+                reply => return reply,
+            }
+        }
+    }
+
+    /// Waits up to `remaining` for a dropped player to rejoin, failing
+    /// early when their seat is no longer held.
+    fn await_return(&mut self, remaining: Duration) -> Result<(), Failure> {
+        let at = self.disconnected_at.unwrap_or_else(Instant::now);
+        let held = (at + self.reconnect_timeout).saturating_duration_since(Instant::now());
+        let rejoined = self
+            .reconnects
+            .lock()
+            .expect("reconnect lock poisoned")
+            .recv_timeout(remaining.min(held));
+        match rejoined {
+            Ok(connection) => {
+                self.resume(connection);
+                Ok(())
+            }
+            Err(_) if remaining <= held => Err(Failure::Timeout(remaining)),
+            Err(_) => Err(Failure::Crashed),
+        }
+    }
+}
+
+impl PokerAgent for RemoteAgent {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        actions: &[PokerAction],
+        _rng: &mut dyn RngCore,
+    ) -> PokerAction {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.connection.lines.discard_pending();
+        let act = ServerMessage::Act {
+            observation: Observation::new(id, view, actions),
+            time_ms: self.timeout.as_millis(),
+            time_bank_ms: self.time_bank.as_millis(),
+        };
+        let started = Instant::now();
+        let reply = self.await_reply(&act, id, started + self.timeout + self.time_bank);
+        // Time taken past the decision's own limit comes out of the bank
+        let overrun = started.elapsed().saturating_sub(self.timeout);
+        self.time_bank = self.time_bank.saturating_sub(overrun);
+
+        let (action, explanation) = match reply {
+            Err(Failure::Timeout(_)) => auto_action(actions, "out of time"),
+            Err(Failure::Crashed) => auto_action(actions, "disconnected"),
+            reply => reply_action(reply, actions),
+        };
+        self.last = Some(explanation);
+        action
+    }
+
+    fn observe_hand_end(&mut self, records: &[ActionRecord], went_to_showdown: bool) {
+        self.send(&ServerMessage::HandEnd {
+            history: history(records),
+            showdown: went_to_showdown,
+        });
+    }
+
+    fn explain(&self) -> Option<Explanation> {
+        self.last.clone()
+    }
+}
+
+/// Whether a connection plays a seat or only watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Player,
+    Spectator,
+}
+
+/// The first line a client sends.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request {
+    Join { role: Role },
+    Rejoin { session: String },
+}
+
+/// A connection that has said what it joins as.
+struct Joined {
+    role: Role,
+    connection: Connection,
+}
+
+/// Waits for a new connection's join request and turns the connection away
+/// when none arrives in time. A player rejoining is passed straight to their
+/// seat.
+fn handshake(stream: TcpStream, sessions: &Sessions) -> Option<Joined> {
+    let mut connection = Connection::new(stream).ok()?;
+    let line = connection
+        .lines
+        .next_line(Duration::from_millis(NET_JOIN_TIMEOUT_MS));
+    let reason = match line.as_deref().map(serde_json::from_str::<Request>) {
+        Some(Ok(Request::Join { role })) => return Some(Joined { role, connection }),
+        Some(Ok(Request::Rejoin { session })) => {
+            let seat = sessions
+                .lock()
+                .expect("session lock poisoned")
+                .get(&session)
+                .cloned();
+            match seat {
+                Some(seat) => {
+                    let _ = seat.send(connection);
+                    return None;
+                }
+                None => "unknown session",
+            }
+        }
+        _ => "expected a join request",
+    };
+    let _ = connection.send(&ServerMessage::Goodbye {
+        reason: reason.to_string(),
+    });
+    None
+}
+
+/// Accepts connections for as long as the server runs, passing each one on
+/// once it has joined.
+fn accept_connections(listener: TcpListener, joins: Sender<Joined>, sessions: Sessions) {
+    for stream in listener.incoming().filter_map(Result::ok) {
+        let joins = joins.clone();
+        let sessions = Arc::clone(&sessions);
+        // A slow client must not hold up the next one
+        thread::spawn(move || {
+            if let Some(joined) = handshake(stream, &sessions) {
+                let _ = joins.send(joined);
+            }
+        });
+    }
+}
+
+/// Who plays a seat at a served table.
+enum Seat {
+    Remote(Box<RemoteAgent>),
+    Local(Box<dyn PokerAgent>),
+}
+
+impl Seat {
+    fn agent(&mut self) -> &mut dyn PokerAgent {
+        match self {
+            Seat::Remote(agent) => agent.as_mut(),
+            Seat::Local(agent) => agent.as_mut(),
+        }
+    }
+
+    fn send(&mut self, message: &ServerMessage) {
+        if let Seat::Remote(agent) = self {
+            agent.send(message);
+        }
+    }
+
+    fn gone(&mut self) -> bool {
+        match self {
+            Seat::Remote(agent) => agent.gone(),
+            Seat::Local(_) => false,
+        }
+    }
+}
+
+/// A request to the spectator feed.
+enum FeedEvent {
+    Join(Connection),
+    /// A table state to send once its time has come.
+    State(Instant, TableState),
+    Goodbye(String),
+}
+
+/// The spectators of a table and the last state they were sent.
+#[derive(Default)]
+struct Audience {
+    spectators: Vec<Connection>,
+    latest: Option<TableState>,
+    /// The last hand the game has finished
+    finished: Arc<AtomicI32>,
+}
+
+impl Audience {
+    fn handle(&mut self, event: FeedEvent) {
+        match event {
+            FeedEvent::Join(mut connection) => {
+                // Latecomers start from the table as the others see it
+                let mut sent = connection.send(&ServerMessage::Welcome {
+                    seat: None,
+                    session: None,
+                });
+                if let Some(state) = &self.latest {
+                    sent = sent.and(connection.send(&ServerMessage::State(state.clone())));
+                }
+                if sent.is_ok() {
+                    self.spectators.push(connection);
+                }
+            }
+            FeedEvent::State(due, mut state) => {
+                thread::sleep(due.saturating_duration_since(Instant::now()));
+                if state.hand > self.finished.load(Ordering::Acquire) {
+                    state.hole_cards.fill(None);
+                }
+                let message = ServerMessage::State(state.clone());
+                self.spectators.retain_mut(|c| c.send(&message).is_ok());
+                self.latest = Some(state);
+            }
+            FeedEvent::Goodbye(reason) => {
+                let message = ServerMessage::Goodbye { reason };
+                for mut connection in self.spectators.drain(..) {
+                    let _ = connection.send(&message);
+                }
+            }
+        }
+    }
+}
+
+/// Sends the table to spectators from a thread of its own, so a delayed
+/// feed never holds up the game.
+struct SpectatorFeed {
+    events: Sender<FeedEvent>,
+    delay: Option<Duration>,
+    finished: Arc<AtomicI32>,
+    thread: thread::JoinHandle<()>,
+}
+
+impl SpectatorFeed {
+    fn start(delay: Option<Duration>) -> Self {
+        let (events, receiver) = mpsc::channel();
+        let finished = Arc::default();
+        let mut audience = Audience {
+            finished: Arc::clone(&finished),
+            ..Audience::default()
+        };
+        let thread = thread::spawn(move || {
+            receiver
+                .into_iter()
+                .for_each(|event| audience.handle(event));
+        });
+        Self {
+            events,
+            delay,
+            finished,
+            thread,
+        }
+    }
+
+    fn join(&self, connection: Connection) {
+        let _ = self.events.send(FeedEvent::Join(connection));
+    }
+
+    /// Queues the table for the spectators. A live feed hides the hole
+    /// cards until showdown; a delayed one shows them for every state of a
+    /// hand that is over by the time the state goes out.
+    fn publish(&self, game_state: &GameStateResource, showdown: bool) {
+        if game_state.current_round == PokerRound::Showdown {
+            self.finished
+                .store(game_state.hand_number, Ordering::Release);
+        }
+        let state = TableState::new(game_state, None, showdown || self.delay.is_some());
+        let due = Instant::now() + self.delay.unwrap_or_default();
+        let _ = self.events.send(FeedEvent::State(due, state));
+    }
+
+    /// Says goodbye once every queued state has been sent.
+    fn finish(self, reason: String) {
+        let _ = self.events.send(FeedEvent::Goodbye(reason));
+        drop(self.events);
+        let _ = self.thread.join();
+    }
+}
+
+/// How long a served match lasts and how it is paced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ServerSettings {
+    /// Hands to play, or `None` to play until a player leaves.
+    pub hands: Option<u32>,
+    /// Time a player has for each decision.
+    pub action_timeout: Duration,
+    /// Extra time each player may draw on over the match when a decision
+    /// takes longer than `action_timeout`.
+    pub time_bank: Duration,
+    /// How long a dropped player's seat is held for them to rejoin.
+    pub reconnect_timeout: Duration,
+    /// Pause after each hand so the players can see how it ended.
+    pub hand_pause: Duration,
+    /// How far the spectators' view runs behind the game. A delayed view
+    /// shows the hole cards of hands that are over; without a delay they
+    /// stay hidden until showdown.
+    pub spectator_delay: Option<Duration>,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            hands: None,
+            action_timeout: Duration::from_millis(NET_ACTION_TIMEOUT_MS),
+            time_bank: Duration::from_millis(NET_TIME_BANK_MS),
+            reconnect_timeout: Duration::from_millis(NET_RECONNECT_TIMEOUT_MS),
+            hand_pause: Duration::from_millis(NET_HAND_PAUSE_MS),
+            spectator_delay: None,
+        }
+    }
+}
+
+/// How a served match ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchEnd {
+    HandLimit,
+    Disconnected(SeatIndex),
+}
+
+impl MatchEnd {
+    fn reason(self) -> String {
+        match self {
+            MatchEnd::HandLimit => "hand limit reached".to_string(),
+            MatchEnd::Disconnected(seat) => {
+                format!("P{} disconnected and did not return", seat + 1)
+            }
+        }
+    }
+}
+
+/// A match being played: who sits where, who watches, and the cards.
+struct Table<'a> {
+    seats: Vec<Seat>,
+    feed: SpectatorFeed,
+    joins: &'a Receiver<Joined>,
+    game_state: GameStateResource,
+}
+
+impl Table<'_> {
+    /// Sends every seat and the spectators their view of the table, after
+    /// letting in anyone who has joined since the last update.
+    fn broadcast(&mut self, showdown: bool) {
+        while let Ok(joined) = self.joins.try_recv() {
+            self.admit(joined);
+        }
+        for (index, seat) in self.seats.iter_mut().enumerate() {
+            let state = TableState::new(&self.game_state, Some(index), showdown);
+            seat.send(&ServerMessage::State(state));
+        }
+        self.feed.publish(&self.game_state, showdown);
+    }
+
+    /// Lets a spectator in; every seat is already taken by now.
+    fn admit(&mut self, joined: Joined) {
+        let mut connection = joined.connection;
+        match joined.role {
+            Role::Spectator => self.feed.join(connection),
+            Role::Player => {
+                let _ = connection.send(&ServerMessage::Goodbye {
+                    reason: "the table is full".to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Hosts a heads-up table for players connecting over TCP and for
+/// spectators watching them.
+pub struct Server {
+    local_addr: SocketAddr,
+    joins: Receiver<Joined>,
+    sessions: Sessions,
+    config: GameConfig,
+    settings: ServerSettings,
+}
+
+impl Server {
+    /// Listens on `address`, accepting connections from then on.
+    pub fn bind(
+        address: impl ToSocketAddrs,
+        config: GameConfig,
+        settings: ServerSettings,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let local_addr = listener.local_addr()?;
+        let (sender, joins) = mpsc::channel();
+        let sessions = Sessions::default();
+        let accepted_sessions = Arc::clone(&sessions);
+        thread::spawn(move || accept_connections(listener, sender, accepted_sessions));
+        Ok(Self {
+            local_addr,
+            joins,
+            sessions,
+            config,
+            settings,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Waits for a player to join every seat that has no agent, then plays
+    /// the match. `on_event` hears about connections and the result of
+    /// every hand.
+    pub fn run(
+        &self,
+        agents: [Option<Box<dyn PokerAgent>>; HEADS_UP_SEATS],
+        rng: &mut dyn RngCore,
+        mut on_event: impl FnMut(&str),
+    ) -> io::Result<MatchEnd> {
+        let feed = SpectatorFeed::start(self.settings.spectator_delay);
+        let seats = self.seat_players(agents, &feed, &mut on_event)?;
+        let table = Table {
+            seats,
+            feed,
+            joins: &self.joins,
+            game_state: GameStateResource {
+                deck: Deck::with_rng(rng),
+                ..GameStateResource::with_stacks(vec![self.config.starting_chips; HEADS_UP_SEATS])
+            },
+        };
+        Ok(self.play(table, rng, &mut on_event))
+    }
+
+    /// Waits until every seat without an agent has a player. Spectators
+    /// may join while the table fills up.
+    fn seat_players(
+        &self,
+        agents: [Option<Box<dyn PokerAgent>>; HEADS_UP_SEATS],
+        feed: &SpectatorFeed,
+        on_event: &mut impl FnMut(&str),
+    ) -> io::Result<Vec<Seat>> {
+        let mut seats: Vec<Option<Seat>> = agents
+            .into_iter()
+            .map(|agent| agent.map(Seat::Local))
+            .collect();
+        if seats.iter().any(Option::is_none) {
+            on_event(&format!("Waiting for players on {}", self.local_addr()));
+        }
+        while let Some(index) = seats.iter().position(Option::is_none) {
+            let joined = self
+                .joins
+                .recv()
+                .map_err(|_| io::Error::other("no longer accepting connections"))?;
+            let peer = joined.connection.peer();
+            match joined.role {
+                Role::Spectator => {
+                    on_event(&format!("Spectator joined from {}", peer));
+                    feed.join(joined.connection);
+                }
+                Role::Player => {
+                    let agent =
+                        RemoteAgent::new(joined.connection, index, &self.settings, &self.sessions);
+                    on_event(&format!("P{} joined from {}", index + 1, peer));
+                    seats[index] = Some(Seat::Remote(Box::new(agent)));
+                }
+            }
+        }
+        Ok(seats.into_iter().flatten().collect())
+    }
+
+    fn play(
+        &self,
+        mut table: Table,
+        rng: &mut dyn RngCore,
+        on_event: &mut impl FnMut(&str),
+    ) -> MatchEnd {
+        let config = &self.config;
+        let mut played = 0;
+        let end = loop {
+            begin_hand(&mut table.game_state, config, rng);
+            table.broadcast(false);
+            while table.game_state.current_round != PokerRound::Showdown {
+                let seat = table.game_state.current_player;
+                let agent = table.seats[seat].agent();
+                perform_validated_action(&mut table.game_state, config, agent, rng);
+                table.broadcast(false);
+            }
+
+            // A winner is already set when the hand ended with a fold
+            let game_state = &mut table.game_state;
+            let went_to_showdown = game_state.winner.is_none();
+            if went_to_showdown {
+                process_showdown_result(game_state);
+            }
+            table.broadcast(went_to_showdown);
+            let game_state = &table.game_state;
+            for seat in table.seats.iter_mut() {
+                seat.agent()
+                    .observe_hand_end(&game_state.action_history, went_to_showdown);
+            }
+            on_event(&format!(
+                "Hand {}: {} (P1 ${}, P2 ${})",
+                game_state.hand_number,
+                game_state.last_winner_message,
+                game_state.player_chips[0],
+                game_state.player_chips[1]
+            ));
+
+            played += 1;
+            if let Some(seat) = table.seats.iter_mut().position(Seat::gone) {
+                break MatchEnd::Disconnected(seat);
+            }
+            if self.settings.hands.is_some_and(|hands| played >= hands) {
+                break MatchEnd::HandLimit;
+            }
+            thread::sleep(self.settings.hand_pause);
+        };
+
+        let reason = end.reason();
+        on_event(&format!("Match over: {}", reason));
+        for seat in table.seats.iter_mut() {
+            seat.send(&ServerMessage::Goodbye {
+                reason: reason.clone(),
+            });
+        }
+        table.feed.finish(reason);
+        self.sessions.lock().expect("session lock poisoned").clear();
+        end
+    }
+}
+
+/// A line the client reads.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Update {
+    Welcome {
+        seat: Option<SeatIndex>,
+        #[serde(default)]
+        session: Option<String>,
+    },
+    State(TableState),
+    Act {
+        id: u64,
+        legal_actions: Vec<String>,
+        #[serde(default)]
+        time_ms: u64,
+        #[serde(default)]
+        time_bank_ms: u64,
+    },
+    Goodbye {
+        reason: String,
+    },
+    /// Messages the window does not use, such as `hand_end`.
+    #[serde(other)]
+    Other,
+}
+
+/// A decision sent to the server.
+#[derive(Debug, Serialize)]
+struct ActionReply {
+    action: &'static str,
+    id: u64,
+}
+
+/// The key that plays `action` in the client, and its label.
+fn action_key(action: PokerAction) -> (KeyCode, char) {
+    match action {
+        PokerAction::Check | PokerAction::Call => (KeyCode::KeyC, 'C'),
+        PokerAction::Bet => (KeyCode::KeyB, 'B'),
+        PokerAction::Raise => (KeyCode::KeyR, 'R'),
+        PokerAction::AllIn => (KeyCode::KeyA, 'A'),
+        PokerAction::Fold => (KeyCode::KeyF, 'F'),
+    }
+}
+
+/// Tells the player which keys play the legal actions and how long they
+/// have.
+fn action_prompt(actions: &[PokerAction], time_ms: u64, time_bank_ms: u64) -> String {
+    let keys: Vec<String> = actions
+        .iter()
+        .map(|&action| {
+            let name = action_name(action).replace('_', "-");
+            format!("{} {}", action_key(action).1, name)
+        })
+        .collect();
+    format!(
+        "Your turn ({}s + {}s bank): {}",
+        time_ms / 1000,
+        time_bank_ms / 1000,
+        keys.join("  ")
+    )
+}
+
+/// Copies the table as the server describes it into `game_state`, turned
+/// so that `seat` becomes seat 0, the one a player's window shows as "YOU".
+/// Cards the client may not see become placeholders. Returns whether
+/// any card changed, which means the table has to be drawn again. A state
+/// whose seats do not add up is ignored like an unreadable message.
+fn mirror(state: &TableState, seat: SeatIndex, game_state: &mut GameStateResource) -> bool {
+    if state.stacks.is_empty()
+        || state.bets.len() != state.stacks.len()
+        || seat >= state.stacks.len()
+    {
+        warn!(
+            "Ignoring table state with {} stacks and {} bets for seat {}",
+            state.stacks.len(),
+            state.bets.len(),
+            seat
+        );
+        return false;
+    }
+    let before = (
+        game_state.hand_number,
+        game_state.hole_cards.clone(),
+        game_state.community_cards,
+    );
+    let seats = state.stacks.len();
+    let server_seat = |local: usize| (local + seat) % seats;
+    let parse = |card: Option<&String>| card.and_then(|c| c.parse::<Card>().ok());
+    let hole = |local: usize| {
+        let cards = state
+            .hole_cards
+            .get(server_seat(local))
+            .and_then(Option::as_ref);
+        std::array::from_fn(|i| parse(cards.and_then(|c| c.get(i))).unwrap_or_default())
+    };
+
+    game_state.hand_number = state.hand;
+    game_state.current_round = parse_round(&state.round).unwrap_or_default();
+    game_state.dealer_position = (state.dealer + seats - seat) % seats;
+    game_state.player_chips = (0..seats).map(|i| state.stacks[server_seat(i)]).collect();
+    game_state.player_bets = (0..seats).map(|i| state.bets[server_seat(i)]).collect();
+    game_state.folded = vec![false; seats];
+    game_state.pot = state.pot;
+    game_state.pot_remainder = 0;
+    game_state.hole_cards = (0..seats).map(hole).collect();
+    game_state.community_cards =
+        std::array::from_fn(|i| parse(state.board.get(i)).unwrap_or_default());
+    game_state.last_action = state
+        .result
+        .clone()
+        .unwrap_or_else(|| state.last_action.clone());
+
+    before
+        != (
+            game_state.hand_number,
+            game_state.hole_cards.clone(),
+            game_state.community_cards,
+        )
+}
+
+/// Opens a new connection to the server at `address` to rejoin a seat.
+fn rejoin(address: SocketAddr, session: String, timeout: Duration) -> io::Result<Connection> {
+    let mut connection = Connection::new(TcpStream::connect_timeout(&address, timeout)?)?;
+    connection.send(&Request::Rejoin { session })?;
+    Ok(connection)
+}
+
+/// The client side of a networked game: the connection to the server and
+/// the decision it is waiting for.
+#[derive(Resource)]
+pub struct NetClient {
+    connection: Connection,
+    address: SocketAddr,
+    role: Role,
+    /// The seat played, known once the server has welcomed the client.
+    seat: Option<SeatIndex>,
+    /// The session to rejoin the seat with after losing the connection.
+    session: Option<String>,
+    /// When the connection was lost, while the client tries to rejoin.
+    lost_at: Option<Instant>,
+    next_attempt: Instant,
+    /// The id of the pending decision and the actions allowed in it.
+    pending: Option<(u64, Vec<PokerAction>)>,
+    /// Whether the table must be drawn again.
+    redraw: bool,
+    /// The hand whose cards have been dealt on screen.
+    dealt_hand: i32,
+    closed: bool,
+}
+
+impl NetClient {
+    /// Connects to the server at `address` and joins its table as `role`.
+    pub fn connect(address: impl ToSocketAddrs, role: Role) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        let address = stream.peer_addr()?;
+        let mut connection = Connection::new(stream)?;
+        connection.send(&Request::Join { role })?;
+        Ok(Self {
+            connection,
+            address,
+            role,
+            seat: None,
+            session: None,
+            lost_at: None,
+            next_attempt: Instant::now(),
+            pending: None,
+            redraw: false,
+            dealt_hand: 0,
+            closed: false,
+        })
+    }
+
+    /// Applies one line from the server to the mirrored `game_state`.
+    fn apply(&mut self, line: &str, game_state: &mut GameStateResource) {
+        let update = match serde_json::from_str(line) {
+            Ok(update) => update,
+            Err(err) => {
+                warn!("Ignoring unreadable server message '{}': {}", line, err);
+                return;
+            }
+        };
+        match update {
+            Update::Welcome { seat, session } => {
+                self.seat = seat;
+                self.session = session;
+            }
+            Update::State(state) => {
+                self.redraw |= mirror(&state, self.seat.unwrap_or(0), game_state);
+            }
+            Update::Act {
+                id,
+                legal_actions,
+                time_ms,
+                time_bank_ms,
+            } => {
+                let actions: Vec<PokerAction> = legal_actions
+                    .iter()
+                    .filter_map(|a| parse_action(a))
+                    .collect();
+                let prompt = action_prompt(&actions, time_ms, time_bank_ms);
+                game_state.last_action = format!("{}\n{}", game_state.last_action, prompt);
+                self.pending = Some((id, actions));
+            }
+            Update::Goodbye { reason } => self.close(&reason, game_state),
+            Update::Other => {}
+        }
+    }
+
+    /// Answers the pending decision with `action`.
+    fn act(&mut self, action: PokerAction) -> io::Result<()> {
+        let Some((id, _)) = self.pending.take() else {
+            return Ok(());
+        };
+        self.connection.send(&ActionReply {
+            action: action_name(action),
+            id,
+        })
+    }
+
+    /// Tries to rejoin the seat after the connection dropped, every
+    /// `NET_RECONNECT_INTERVAL_MS` until `NET_RECONNECT_TIMEOUT_MS` have
+    /// passed. A spectator, or a player who cannot get back, is closed.
+    fn connection_lost(&mut self, game_state: &mut GameStateResource) {
+        if self.closed {
+            return;
+        }
+        let Some(session) = self.session.clone() else {
+            return self.close("connection to the server lost", game_state);
+        };
+        let now = Instant::now();
+        let lost_at = *self.lost_at.get_or_insert(now);
+        if now - lost_at >= Duration::from_millis(NET_RECONNECT_TIMEOUT_MS) {
+            return self.close("could not reconnect to the server", game_state);
+        }
+        if now < self.next_attempt {
+            return;
+        }
+        let interval = Duration::from_millis(NET_RECONNECT_INTERVAL_MS);
+        self.next_attempt = now + interval;
+        self.pending = None;
+        game_state.last_action = "Connection lost - reconnecting...".to_string();
+        if let Ok(connection) = rejoin(self.address, session, interval) {
+            self.connection = connection;
+            self.lost_at = None;
+            self.redraw = true;
+        }
+    }
+
+    fn close(&mut self, reason: &str, game_state: &mut GameStateResource) {
+        if !self.closed {
+            self.closed = true;
+            self.pending = None;
+            game_state.last_action = format!("Match over: {}", reason);
+        }
+    }
+}
+
+/// Sets up the client window's camera.
+pub fn setup_client(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
+
+/// Applies everything the server has sent since the last frame.
+pub fn receive_updates(mut client: ResMut<NetClient>, mut game_state: ResMut<GameStateResource>) {
+    loop {
+        let line = match client.connection.lines.try_next() {
+            Ok(line) => line,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                return client.connection_lost(&mut game_state);
+            }
+        };
+        client.apply(&line, &mut game_state);
+    }
+}
+
+/// Sends the player's action when the server is waiting for one: C checks
+/// or calls, B bets, R raises, A goes all-in and F folds.
+pub fn handle_action_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut client: ResMut<NetClient>,
+    mut game_state: ResMut<GameStateResource>,
+) {
+    let Some((_, actions)) = client.pending.as_ref() else {
+        return;
+    };
+    let chosen = actions
+        .iter()
+        .copied()
+        .find(|&action| keys.just_pressed(action_key(action).0));
+    let Some(action) = chosen else {
+        return;
+    };
+    if let Err(err) = client.act(action) {
+        client.close(&err.to_string(), &mut game_state);
+    }
+}
+
+/// Draws the table again when the server has dealt or shown cards. A new
+/// hand is dealt with the usual animation; otherwise the cards are placed
+/// straight away.
+pub fn redraw_table(
+    mut commands: Commands,
+    hand_query: Query<Entity, With<HandMarker>>,
+    mut client: ResMut<NetClient>,
+    game_state: Res<GameStateResource>,
+    config: Res<GameConfig>,
+    colors: Res<ColorPalette>,
+    time: Res<Time>,
+) {
+    if !client.redraw {
+        return;
+    }
+    client.redraw = false;
+    for entity in hand_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let animation_start_time = if game_state.hand_number == client.dealt_hand {
+        f32::NEG_INFINITY
+    } else {
+        time.elapsed_seconds()
+    };
+    client.dealt_hand = game_state.hand_number;
+
+    use crate::ui::*;
+    spawn_table(
+        &mut commands,
+        config.screen_width,
+        config.screen_height,
+        *colors,
+    );
+    // A player always sees themselves as seat 0
+    let viewer = (client.role == Role::Player).then_some(0);
+    let labels = seat_labels(game_state.seat_count(), viewer);
+    spawn_all_players(
+        &mut commands,
+        &game_state,
+        &config,
+        *colors,
+        &labels,
+        animation_start_time,
+    );
+    for (i, card) in game_state.community_cards.iter().enumerate() {
+        spawn_community_card(
+            &mut commands,
+            &game_state,
+            &config,
+            &colors,
+            i,
+            card.is_placeholder,
+            animation_start_time,
+        );
+    }
+    spawn_ui(&mut commands, &game_state, &config, &colors);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::ThresholdAgent;
+    use crate::poker_logic::parse_cards;
+    use crate::profile::AiProfile;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::time::Instant;
+
+    fn cards<const N: usize>(s: &str) -> [Card; N] {
+        parse_cards(s).unwrap().try_into().unwrap()
+    }
+
+    fn test_settings(hands: u32) -> ServerSettings {
+        ServerSettings {
+            hands: Some(hands),
+            action_timeout: Duration::from_secs(5),
+            time_bank: Duration::ZERO,
+            reconnect_timeout: Duration::from_secs(5),
+            hand_pause: Duration::ZERO,
+            spectator_delay: None,
+        }
+    }
+
+    fn flop_state() -> GameStateResource {
+        GameStateResource {
+            hand_number: 3,
+            current_round: PokerRound::Flop,
+            current_player: 1,
+            pot: 150,
+            hole_cards: vec![cards("AhKd"), cards("QsQc")],
+            community_cards: cards("7c8d9h2s3s"),
+            last_action: "P1: Check".to_string(),
+            ..GameStateResource::with_stacks(vec![900, 950])
+        }
+    }
+
+    #[test]
+    fn test_table_state_hides_the_opponents_cards_until_showdown() {
+        let game_state = flop_state();
+        let state = TableState::new(&game_state, Some(1), false);
+        assert_eq!(state.hole_cards[0], None);
+        assert_eq!(state.hole_cards[1], Some(vec!["Qs".into(), "Qc".into()]));
+        assert_eq!(state.board, ["7c", "8d", "9h"]);
+        assert_eq!(state.to_act, Some(1));
+
+        let shown = TableState::new(&game_state, Some(1), true);
+        assert_eq!(shown.hole_cards[0], Some(vec!["Ah".into(), "Kd".into()]));
+    }
+
+    #[test]
+    fn test_a_fold_does_not_reveal_the_rest_of_the_board() {
+        let mut game_state = flop_state();
+        apply_action(&mut game_state, &GameConfig::default(), PokerAction::Fold);
+        let state = TableState::new(&game_state, Some(0), false);
+        assert_eq!(state.round, "showdown");
+        assert_eq!(state.board.len(), 3);
+        assert_eq!(state.to_act, None);
+        assert_eq!(state.result.as_deref(), Some("P2 folds - P1 wins"));
+    }
+
+    #[test]
+    fn test_client_mirrors_the_table_from_its_own_seat() {
+        let state = TableState::new(&flop_state(), Some(1), false);
+        let mut game_state = GameStateResource::default();
+        assert!(mirror(&state, 1, &mut game_state));
+        assert_eq!(game_state.hole_cards[0], cards::<2>("QsQc"));
+        assert!(game_state.hole_cards[1].iter().all(|c| c.is_placeholder));
+        assert_eq!(game_state.player_chips, [950, 900]);
+        assert_eq!(game_state.dealer_position, 1);
+        assert_eq!(game_state.current_round, PokerRound::Flop);
+        assert_eq!(&game_state.community_cards[..3], &cards::<3>("7c8d9h"));
+        assert!(game_state.community_cards[3].is_placeholder);
+        assert!(!mirror(&state, 1, &mut game_state), "nothing new to draw");
+    }
+
+    #[test]
+    fn test_client_ignores_malformed_table_states() {
+        let valid = TableState::new(&flop_state(), Some(1), false);
+        let mut game_state = GameStateResource::default();
+        mirror(&valid, 1, &mut game_state);
+
+        let empty = TableState {
+            stacks: Vec::new(),
+            bets: Vec::new(),
+            ..valid.clone()
+        };
+        let short_bets = TableState {
+            bets: vec![0],
+            ..valid.clone()
+        };
+        assert!(!mirror(&empty, 0, &mut game_state));
+        assert!(!mirror(&short_bets, 1, &mut game_state));
+        assert!(!mirror(&valid, 2, &mut game_state));
+        assert_eq!(game_state.player_chips, [950, 900]);
+    }
+
+    /// Plays a client to the end of the match, checking or calling every
+    /// decision, and returns how many decisions it made.
+    fn play_client(address: SocketAddr, game_state: &mut GameStateResource) -> u32 {
+        let client = NetClient::connect(address, Role::Player).unwrap();
+        follow(client, game_state, |_| {})
+    }
+
+    /// Runs `client` to the end of the match, checking or calling every
+    /// decision and handing each table state it receives to `on_state`.
+    /// Returns how many decisions it made.
+    fn follow(
+        mut client: NetClient,
+        game_state: &mut GameStateResource,
+        mut on_state: impl FnMut(&TableState),
+    ) -> u32 {
+        let mut decisions = 0;
+        let deadline = Instant::now() + Duration::from_secs(20);
+        while !client.closed {
+            assert!(Instant::now() < deadline, "match did not finish");
+            let passive = client.pending.as_ref().and_then(|(_, actions)| {
+                actions
+                    .iter()
+                    .copied()
+                    .find(|a| matches!(a, PokerAction::Check | PokerAction::Call))
+            });
+            if let Some(action) = passive {
+                client.act(action).unwrap();
+                decisions += 1;
+            }
+            let line = match client.connection.lines.try_next() {
+                Ok(line) => line,
+                Err(TryRecvError::Empty) => {
+                    thread::sleep(Duration::from_millis(1));
+                    continue;
+                }
+                Err(TryRecvError::Disconnected) => break,
+            };
+            if let Ok(Update::State(state)) = serde_json::from_str(&line) {
+                on_state(&state);
+            }
+            client.apply(&line, game_state);
+        }
+        decisions
+    }
+
+    #[test]
+    fn test_served_match_against_a_bot_over_localhost() {
+        let config = GameConfig::default();
+        let server = Server::bind("127.0.0.1:0", config, test_settings(3)).unwrap();
+        let address = server.local_addr();
+        let host = thread::spawn(move || {
+            let bot: Box<dyn PokerAgent> = Box::new(ThresholdAgent::new(AiProfile::default()));
+            let mut rng = StdRng::seed_from_u64(43);
+            server.run([None, Some(bot)], &mut rng, |_| {})
+        });
+
+        let mut game_state = GameStateResource::default();
+        let decisions = play_client(address, &mut game_state);
+        assert_eq!(host.join().unwrap().unwrap(), MatchEnd::HandLimit);
+        assert!(decisions > 0);
+        assert_eq!(game_state.hand_number, 3);
+        assert_eq!(game_state.last_action, "Match over: hand limit reached");
+        let total: u32 = game_state.player_chips.iter().sum();
+        assert_eq!(total, 2 * GameConfig::default().starting_chips);
+    }
+
+    #[test]
+    fn test_two_clients_play_each_other() {
+        let server = Server::bind("127.0.0.1:0", GameConfig::default(), test_settings(2)).unwrap();
+        let address = server.local_addr();
+        let host = thread::spawn(move || {
+            let mut rng = StdRng::seed_from_u64(44);
+            server.run([None, None], &mut rng, |_| {})
+        });
+
+        let players: Vec<_> = (0..HEADS_UP_SEATS)
+            .map(|_| {
+                thread::spawn(move || {
+                    let mut game_state = GameStateResource::default();
+                    play_client(address, &mut game_state);
+                    game_state.player_chips
+                })
+            })
+            .collect();
+        let chips: Vec<Vec<u32>> = players.into_iter().map(|p| p.join().unwrap()).collect();
+        assert_eq!(host.join().unwrap().unwrap(), MatchEnd::HandLimit);
+        // Each client sees itself as seat 0
+        assert_eq!(chips[0], [chips[1][1], chips[1][0]]);
+    }
+
+    #[test]
+    fn test_a_player_who_does_not_return_ends_the_match() {
+        let settings = ServerSettings {
+            reconnect_timeout: Duration::from_millis(100),
+            ..test_settings(50)
+        };
+        let server = Server::bind("127.0.0.1:0", GameConfig::default(), settings).unwrap();
+        let address = server.local_addr();
+        let host = thread::spawn(move || {
+            let bot: Box<dyn PokerAgent> = Box::new(ThresholdAgent::new(AiProfile::default()));
+            let mut rng = StdRng::seed_from_u64(45);
+            server.run([Some(bot), None], &mut rng, |_| {})
+        });
+
+        drop(NetClient::connect(address, Role::Player).unwrap());
+        assert_eq!(host.join().unwrap().unwrap(), MatchEnd::Disconnected(1));
+    }
+
+    /// Reads what the server sends until the client is asked to act, and
+    /// returns the id of the decision.
+    fn await_decision(client: &mut NetClient, game_state: &mut GameStateResource) -> u64 {
+        loop {
+            if let Some((id, _)) = client.pending {
+                return id;
+            }
+            let line = client.connection.lines.next_line(Duration::from_secs(5));
+            client.apply(&line.expect("the player is asked to act"), game_state);
+        }
+    }
+
+    #[test]
+    fn test_a_dropped_player_rejoins_and_resumes_the_hand() {
+        let server = Server::bind("127.0.0.1:0", GameConfig::default(), test_settings(3)).unwrap();
+        let address = server.local_addr();
+        let host = thread::spawn(move || {
+            let bot: Box<dyn PokerAgent> = Box::new(ThresholdAgent::new(AiProfile::default()));
+            let mut rng = StdRng::seed_from_u64(49);
+            server.run([None, Some(bot)], &mut rng, |_| {})
+        });
+
+        let mut player = NetClient::connect(address, Role::Player).unwrap();
+        let mut game_state = GameStateResource::default();
+        let id = await_decision(&mut player, &mut game_state);
+        let hand = game_state.hand_number;
+        player.connection.stream.shutdown(Shutdown::Both).unwrap();
+        player.connection_lost(&mut game_state);
+        assert_eq!(player.lost_at, None, "rejoined at the first attempt");
+
+        // The same decision of the same hand is asked for again
+        game_state.hand_number = 0;
+        assert_eq!(await_decision(&mut player, &mut game_state), id);
+        assert_eq!(player.seat, Some(0));
+        assert_eq!(game_state.hand_number, hand);
+        follow(player, &mut game_state, |_| {});
+        assert_eq!(host.join().unwrap().unwrap(), MatchEnd::HandLimit);
+
+        let stranger = rejoin(address, "0123".to_string(), Duration::from_secs(1)).unwrap();
+        assert_eq!(
+            stranger.lines.next_line(Duration::from_secs(5)).as_deref(),
+            Some(r#"{"type":"goodbye","reason":"unknown session"}"#)
+        );
+    }
+
+    #[test]
+    fn test_a_silent_player_checks_or_folds_once_the_time_bank_is_spent() {
+        let settings = ServerSettings {
+            action_timeout: Duration::from_millis(50),
+            time_bank: Duration::from_millis(100),
+            ..test_settings(1)
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let player = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let connection = Connection::new(listener.accept().unwrap().0).unwrap();
+        let mut seat = RemoteAgent::new(connection, 1, &settings, &Sessions::default());
+        let view = PlayerView::new(&flop_state(), &GameConfig::default(), 1);
+        let mut rng = StdRng::seed_from_u64(48);
+
+        let started = Instant::now();
+        let unopened = [PokerAction::Check, PokerAction::Bet, PokerAction::Fold];
+        assert_eq!(
+            seat.choose_action(&view, &unopened, &mut rng),
+            PokerAction::Check
+        );
+        assert!(started.elapsed() >= Duration::from_millis(150));
+        assert_eq!(seat.time_bank, Duration::ZERO);
+        assert_eq!(seat.explain().unwrap().notes, ["out of time: auto-check"]);
+
+        let lines = LineReader::spawn(player);
+        let _welcome = lines.next_line(Duration::from_secs(1));
+        let act: serde_json::Value =
+            serde_json::from_str(&lines.next_line(Duration::from_secs(1)).unwrap()).unwrap();
+        assert_eq!(act["type"], "act");
+        assert_eq!(act["id"], 0);
+        assert_eq!(
+            (act["time_ms"].as_u64(), act["time_bank_ms"].as_u64()),
+            (Some(50), Some(100))
+        );
+
+        // With the bank spent only the decision's own time is left
+        let started = Instant::now();
+        let facing_bet = [PokerAction::Call, PokerAction::Raise, PokerAction::Fold];
+        assert_eq!(
+            seat.choose_action(&view, &facing_bet, &mut rng),
+            PokerAction::Fold
+        );
+        assert!(started.elapsed() < Duration::from_millis(140));
+    }
+
+    /// Serves `hands` hands between a bot and a checking client, with a
+    /// spectator watching, and returns every state the spectator saw.
+    fn watch_match(hands: u32, spectator_delay: Option<Duration>) -> Vec<TableState> {
+        let settings = ServerSettings {
+            spectator_delay,
+            ..test_settings(hands)
+        };
+        let server = Server::bind("127.0.0.1:0", GameConfig::default(), settings).unwrap();
+        let address = server.local_addr();
+        let spectator = NetClient::connect(address, Role::Spectator).unwrap();
+        let host = thread::spawn(move || {
+            let bot: Box<dyn PokerAgent> = Box::new(ThresholdAgent::new(AiProfile::default()));
+            let mut rng = StdRng::seed_from_u64(46);
+            server.run([Some(bot), None], &mut rng, |_| {})
+        });
+        let watcher = thread::spawn(move || {
+            let mut seen = Vec::new();
+            let decisions = follow(spectator, &mut GameStateResource::default(), |state| {
+                seen.push(state.clone());
+            });
+            assert_eq!(decisions, 0, "spectators are never asked to act");
+            seen
+        });
+
+        play_client(address, &mut GameStateResource::default());
+        assert_eq!(host.join().unwrap().unwrap(), MatchEnd::HandLimit);
+        watcher.join().unwrap()
+    }
+
+    #[test]
+    fn test_spectators_see_no_hole_cards_before_showdown() {
+        let seen = watch_match(3, None);
+        assert!(seen.iter().any(|state| state.hand == 3));
+        for state in seen.iter().filter(|state| state.round != "showdown") {
+            assert_eq!(state.hole_cards, [None, None]);
+        }
+    }
+
+    #[test]
+    fn test_a_delayed_spectator_feed_shows_the_hole_cards() {
+        let seen = watch_match(2, Some(Duration::from_millis(50)));
+        let ends: Vec<_> = seen
+            .iter()
+            .filter(|state| state.round == "showdown")
+            .collect();
+        assert!(!ends.is_empty(), "the spectator saw the hands end");
+        for state in ends {
+            assert!(state.hole_cards.iter().all(Option::is_some));
+        }
+    }
+
+    #[test]
+    fn test_a_delayed_spectator_feed_hides_the_hand_in_play() {
+        let settings = ServerSettings {
+            spectator_delay: Some(Duration::from_millis(20)),
+            ..test_settings(1)
+        };
+        let server = Server::bind("127.0.0.1:0", GameConfig::default(), settings).unwrap();
+        let address = server.local_addr();
+        let host = thread::spawn(move || {
+            let bot: Box<dyn PokerAgent> = Box::new(ThresholdAgent::new(AiProfile::default()));
+            let mut rng = StdRng::seed_from_u64(48);
+            server.run([None, Some(bot)], &mut rng, |_| {})
+        });
+
+        // Watching from before the deal, the spectator is sent the hand
+        // while the player is still thinking about their first decision
+        let spectator = NetClient::connect(address, Role::Spectator).unwrap();
+        let welcome = spectator.connection.lines.next_line(Duration::from_secs(5));
+        assert!(welcome.is_some_and(|line| line.contains("welcome")));
+        let mut player = NetClient::connect(address, Role::Player).unwrap();
+        let mut game_state = GameStateResource::default();
+        await_decision(&mut player, &mut game_state);
+        let line = spectator.connection.lines.next_line(Duration::from_secs(5));
+        let Some(Update::State(state)) = line.and_then(|line| serde_json::from_str(&line).ok())
+        else {
+            panic!("the spectator saw the hand being dealt");
+        };
+        assert_eq!(state.round, "preflop");
+        assert_eq!(state.hole_cards, [None, None]);
+
+        player.act(PokerAction::Fold).unwrap();
+        follow(player, &mut game_state, |_| {});
+        let mut last = None;
+        follow(spectator, &mut GameStateResource::default(), |state| {
+            last = Some(state.clone());
+        });
+        assert_eq!(host.join().unwrap().unwrap(), MatchEnd::HandLimit);
+        let last = last.expect("the spectator saw the hand end");
+        assert!(last.hole_cards.iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn test_a_full_table_turns_players_away() {
+        let server = Server::bind("127.0.0.1:0", GameConfig::default(), test_settings(1)).unwrap();
+        let address = server.local_addr();
+        let host = thread::spawn(move || {
+            let bot: Box<dyn PokerAgent> = Box::new(ThresholdAgent::new(AiProfile::default()));
+            let mut rng = StdRng::seed_from_u64(47);
+            server.run([None, Some(bot)], &mut rng, |_| {})
+        });
+
+        // The seated player is asked to act first, holding the server up
+        // while the late player joins
+        let mut player = NetClient::connect(address, Role::Player).unwrap();
+        let mut game_state = GameStateResource::default();
+        await_decision(&mut player, &mut game_state);
+        let late = NetClient::connect(address, Role::Player).unwrap();
+        thread::sleep(Duration::from_millis(100));
+        player.act(PokerAction::Fold).unwrap();
+        follow(player, &mut game_state, |_| {});
+
+        let line = late.connection.lines.next_line(Duration::from_secs(5));
+        assert_eq!(
+            line.as_deref(),
+            Some(r#"{"type":"goodbye","reason":"the table is full"}"#)
+        );
+        assert_eq!(host.join().unwrap().unwrap(), MatchEnd::HandLimit);
+    }
+}
@@ -1,8 +1,8 @@
 use crate::constants::MIN_CARDS_FOR_HAND_EVALUATION;
-use rand::{seq::SliceRandom, thread_rng};
-use std::cmp::Reverse;
-use std::collections::{HashMap, HashSet};
+use rand::{seq::SliceRandom, thread_rng, Rng};
+use std::cmp::Ordering;
 use std::fmt;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Suit {
@@ -87,19 +87,181 @@ impl Card {
     }
 }
 
+impl Rank {
+    /// Single-character notation for the rank ("2"-"9", "T", "J", "Q", "K", "A").
+    pub fn to_char(self) -> char {
+        match self {
+            Rank::Two => '2',
+            Rank::Three => '3',
+            Rank::Four => '4',
+            Rank::Five => '5',
+            Rank::Six => '6',
+            Rank::Seven => '7',
+            Rank::Eight => '8',
+            Rank::Nine => '9',
+            Rank::Ten => 'T',
+            Rank::Jack => 'J',
+            Rank::Queen => 'Q',
+            Rank::King => 'K',
+            Rank::Ace => 'A',
+        }
+    }
+
+    /// Parses a single rank character, case-insensitively ("T" for ten).
+    pub fn from_char(c: char) -> Option<Rank> {
+        RANKS
+            .iter()
+            .copied()
+            .find(|rank| rank.to_char() == c.to_ascii_uppercase())
+    }
+}
+
+impl Suit {
+    /// Single-letter notation for the suit ("h", "d", "c", "s").
+    pub fn to_char(self) -> char {
+        match self {
+            Suit::Hearts => 'h',
+            Suit::Diamonds => 'd',
+            Suit::Clubs => 'c',
+            Suit::Spades => 's',
+        }
+    }
+
+    /// Accepts both the letter notation and the suit symbols shown on screen.
+    fn from_char(c: char) -> Option<Suit> {
+        match c {
+            '♥' => Some(Suit::Hearts),
+            '♦' => Some(Suit::Diamonds),
+            '♣' => Some(Suit::Clubs),
+            '♠' => Some(Suit::Spades),
+            _ => SUITS
+                .iter()
+                .copied()
+                .find(|suit| suit.to_char() == c.to_ascii_lowercase()),
+        }
+    }
+}
+
+/// Formats a card in standard notation, e.g. "Ah" or "Td".
+/// The alternate form (`{:#}`) uses the on-screen style, e.g. "A♥" or "10♦".
+/// Placeholder cards are shown as "??".
 impl fmt::Display for Card {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}{}", self.rank_str(), self.suit_str())
+        if self.is_placeholder {
+            write!(f, "??")
+        } else if f.alternate() {
+            write!(f, "{}{}", self.rank_str(), self.suit_str())
+        } else {
+            write!(f, "{}{}", self.rank.to_char(), self.suit.to_char())
+        }
     }
 }
 
+/// Error returned when card notation cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCardError {
+    /// The input did not contain any cards.
+    Empty,
+    /// A rank character was not one of 2-9, T (or 10), J, Q, K, A.
+    InvalidRank(String),
+    /// A suit character was not one of h, d, c, s (or a suit symbol).
+    InvalidSuit(String),
+    /// The same card appears more than once.
+    Duplicate(Card),
+    /// Exactly one card was expected.
+    NotSingleCard(String),
+}
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseCardError::Empty => write!(f, "no cards given"),
+            ParseCardError::InvalidRank(token) => write!(f, "invalid rank in '{}'", token),
+            ParseCardError::InvalidSuit(token) => write!(f, "invalid suit in '{}'", token),
+            ParseCardError::Duplicate(card) => write!(f, "duplicate card {}", card),
+            ParseCardError::NotSingleCard(token) => write!(f, "expected one card, got '{}'", token),
+        }
+    }
+}
+
+impl std::error::Error for ParseCardError {}
+
+/// Reads one card from the front of `chars`, which must not be empty.
+fn parse_next_card(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+) -> Result<Card, ParseCardError> {
+    let rank_char = chars.next().ok_or(ParseCardError::Empty)?;
+    let rank = if rank_char == '1' && chars.peek() == Some(&'0') {
+        chars.next();
+        Some(Rank::Ten)
+    } else {
+        Rank::from_char(rank_char)
+    };
+    let rank = rank.ok_or_else(|| ParseCardError::InvalidRank(rank_char.to_string()))?;
+
+    let suit_char = chars
+        .next()
+        .ok_or_else(|| ParseCardError::InvalidSuit(rank.to_char().to_string()))?;
+    let suit = Suit::from_char(suit_char)
+        .ok_or_else(|| ParseCardError::InvalidSuit(format!("{}{}", rank.to_char(), suit_char)))?;
+    Ok(Card::new(rank, suit))
+}
+
+/// Parses a single card such as "Ah", "Td", "10d" or "2c".
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_cards(s)?.as_slice() {
+            [card] => Ok(*card),
+            _ => Err(ParseCardError::NotSingleCard(s.trim().to_string())),
+        }
+    }
+}
+
+/// Parses a list of cards in standard notation.
+///
+/// Cards may be written back to back or separated by whitespace or commas,
+/// so "AhKh Qs Jd Tc" and "Ah, Kh, Qs, Jd, Tc" both give five cards.
+/// Each card may appear only once.
+pub fn parse_cards(s: &str) -> Result<Vec<Card>, ParseCardError> {
+    let mut chars = s.chars().peekable();
+    let mut cards: Vec<Card> = Vec::new();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+        let card = parse_next_card(&mut chars)?;
+        if cards.contains(&card) {
+            return Err(ParseCardError::Duplicate(card));
+        }
+        cards.push(card);
+    }
+
+    if cards.is_empty() {
+        return Err(ParseCardError::Empty);
+    }
+    Ok(cards)
+}
+
+/// Formats cards in standard notation separated by spaces, the inverse of `parse_cards`.
+pub fn format_cards(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Deck {
     cards: Vec<Card>,
 }
 
-const SUITS: [Suit; 4] = [Suit::Hearts, Suit::Diamonds, Suit::Clubs, Suit::Spades];
-const RANKS: [Rank; 13] = [
+pub const SUITS: [Suit; 4] = [Suit::Hearts, Suit::Diamonds, Suit::Clubs, Suit::Spades];
+pub const RANKS: [Rank; 13] = [
     Rank::Two,
     Rank::Three,
     Rank::Four,
@@ -115,15 +277,26 @@ const RANKS: [Rank; 13] = [
     Rank::Ace,
 ];
 
+/// Returns all 52 cards in a fixed, unshuffled order.
+pub fn full_deck() -> Vec<Card> {
+    let mut cards = Vec::with_capacity(52);
+    for &suit in &SUITS {
+        for &rank in &RANKS {
+            cards.push(Card::new(rank, suit));
+        }
+    }
+    cards
+}
+
 impl Deck {
     pub fn new() -> Self {
-        let mut cards = Vec::with_capacity(52);
-        for &suit in &SUITS {
-            for &rank in &RANKS {
-                cards.push(Card::new(rank, suit));
-            }
-        }
-        cards.shuffle(&mut thread_rng());
+        Self::with_rng(&mut thread_rng())
+    }
+
+    /// Creates a deck shuffled with `rng`, so seeded games deal the same cards.
+    pub fn with_rng<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut cards = full_deck();
+        cards.shuffle(rng);
         Deck { cards }
     }
 
@@ -137,6 +310,12 @@ impl Deck {
     }
 }
 
+impl Default for Deck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // Bit pattern for wheel straight: A,2,3,4,5 (Ace=14, Five=5)
 const WHEEL_BITS: u16 = (1 << 14) | (1 << 2) | (1 << 3) | (1 << 4) | (1 << 5);
 // Mask for 5 consecutive bits
@@ -158,32 +337,66 @@ const STRAIGHT_HIGH_MAP: [(u16, Rank); 10] = [
 /// Finds the highest straight in a set of ranks using bit manipulation.
 /// Returns the high card of the straight (e.g., for A-K-Q-J-10, returns Ace).
 /// Also handles the wheel straight (A-2-3-4-5) where 5 is the high card.
-/// Uses bit masking to efficiently check for consecutive ranks.
-fn find_straight_high(ranks: &HashSet<Rank>) -> Option<Rank> {
-    if ranks.len() < 5 {
-        return None;
-    }
-
-    let mut rank_bits: u16 = 0;
-    for &rank in ranks {
-        rank_bits |= 1 << (rank as u8);
-    }
-
-    let has_wheel = (rank_bits & WHEEL_BITS) == WHEEL_BITS;
-
+/// `rank_bits` has bit `n` set for every rank with value `n` that is present.
+fn find_straight_high(rank_bits: u16) -> Option<Rank> {
     for (mask, rank) in STRAIGHT_HIGH_MAP.iter() {
         if (rank_bits & mask) == *mask {
             return Some(*rank);
         }
     }
 
-    if has_wheel {
+    if (rank_bits & WHEEL_BITS) == WHEEL_BITS {
         Some(Rank::Five)
     } else {
         None
     }
 }
 
+/// Iterates over the ranks present in `rank_bits`, highest first.
+fn ranks_high_to_low(rank_bits: u16) -> impl Iterator<Item = Rank> {
+    RANKS
+        .iter()
+        .rev()
+        .copied()
+        .filter(move |&rank| rank_bits & (1 << (rank as u8)) != 0)
+}
+
+/// Whether `hole` plus `board` is one card away from a flush or a straight
+/// that uses at least one hole card and is not already made.
+pub fn has_draw(hole: &HoleCards, board: &[Card]) -> bool {
+    let suit_count = |cards: &[Card], suit: Suit| cards.iter().filter(|c| c.suit == suit).count();
+    let flush_draw = SUITS.iter().any(|&suit| {
+        let total = suit_count(hole, suit) + suit_count(board, suit);
+        total == HAND_SIZE - 1 && suit_count(hole, suit) > 0
+    });
+    if flush_draw {
+        return true;
+    }
+
+    let all_bits = straight_bits(hole.iter().chain(board));
+    let board_bits = straight_bits(board.iter());
+    if find_straight_high(all_bits).is_some() {
+        return false;
+    }
+    // Every window of five ranks, ace low (bit 1) through ace high (bit 14)
+    (1..=10).any(|low| {
+        let window = 0b1_1111u16 << low;
+        (all_bits & window).count_ones() == 4 && (board_bits & window).count_ones() < 4
+    })
+}
+
+/// Rank bits with the ace also set as bit 1 so wheel draws are found.
+fn straight_bits<'a>(cards: impl Iterator<Item = &'a Card>) -> u16 {
+    cards.fold(0, |bits, card| {
+        let bits = bits | (1 << (card.rank as u8));
+        if card.rank == Rank::Ace {
+            bits | (1 << 1)
+        } else {
+            bits
+        }
+    })
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum PokerRound {
     #[default]
@@ -223,6 +436,9 @@ pub enum HandRank {
     StraightFlush,
 }
 
+/// Number of cards that make up a poker hand; only these take part in tie-breaks.
+const HAND_SIZE: usize = 5;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EvaluatedHand {
     pub hand_rank: HandRank,
@@ -230,9 +446,24 @@ pub struct EvaluatedHand {
     pub kickers: Vec<Rank>,
 }
 
-impl EvaluatedHand {
-    pub fn score(&self) -> (HandRank, &[Rank]) {
-        (self.hand_rank, &self.primary_values)
+/// Total ordering of evaluated hands, highest hand is greatest.
+///
+/// Hands compare by `hand_rank` first, then by `primary_values` (the ranks that
+/// define the hand, highest first) and finally by `kickers`. Both value lists
+/// only ever describe the best five cards, so unused hole or board cards can
+/// never break a tie.
+impl Ord for EvaluatedHand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.hand_rank
+            .cmp(&other.hand_rank)
+            .then_with(|| self.primary_values.cmp(&other.primary_values))
+            .then_with(|| self.kickers.cmp(&other.kickers))
+    }
+}
+
+impl PartialOrd for EvaluatedHand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
@@ -260,222 +491,150 @@ pub fn evaluate_hand(cards: &[Card]) -> EvaluatedHand {
         };
     }
 
-    let mut cards_vec: Vec<Card> = cards
-        .iter()
-        .filter(|c| !c.is_placeholder)
-        .cloned()
-        .collect();
-    cards_vec.sort_by_key(|c| c.rank);
-
-    let suit_counts: HashMap<Suit, usize> = {
-        let mut counts = HashMap::new();
-        for c in cards_vec.iter() {
-            *counts.entry(c.suit).or_insert(0) += 1;
-        }
-        counts
-    };
-    let is_flush = suit_counts.values().any(|&count| count >= 5);
-
-    let ranks: Vec<Rank> = cards_vec.iter().map(|c| c.rank).collect();
-    let unique_ranks: HashSet<Rank> = ranks.iter().copied().collect();
-
-    let straight_high = find_straight_high(&unique_ranks);
-    let is_straight = straight_high.is_some();
-
-    let rank_counts: HashMap<Rank, usize> = {
-        let mut counts = HashMap::new();
-        for &r in &ranks {
-            *counts.entry(r).or_insert(0) += 1;
+    // Fixed-size tallies indexed by rank value (2-14) and suit keep evaluation
+    // allocation-free, which matters for equity simulations.
+    let mut rank_counts = [0u8; 15];
+    let mut suit_counts = [0u8; 4];
+    let mut suit_rank_bits = [0u16; 4];
+    let mut rank_bits: u16 = 0;
+    for card in cards.iter().filter(|c| !c.is_placeholder) {
+        let rank = card.rank as usize;
+        let suit = card.suit as usize;
+        rank_counts[rank] += 1;
+        suit_counts[suit] += 1;
+        suit_rank_bits[suit] |= 1 << rank;
+        rank_bits |= 1 << rank;
+    }
+    let flush_suit = (0..suit_counts.len()).find(|&suit| suit_counts[suit] >= 5);
+
+    // Check for straight flush: the straight must be made within the flush suit
+    if let Some(flush_suit) = flush_suit {
+        if let Some(straight_high) = find_straight_high(suit_rank_bits[flush_suit]) {
+            return EvaluatedHand {
+                hand_rank: HandRank::StraightFlush,
+                primary_values: vec![straight_high],
+                kickers: Vec::new(),
+            };
         }
-        counts
-    };
+    }
 
-    let mut rank_counts_vec: Vec<(Rank, usize)> = rank_counts.into_iter().collect();
-    rank_counts_vec.sort_by_key(|(rank, count)| (*count, Reverse(*rank)));
-
-    let (four_of_kind, three_of_kind, pairs) = {
-        let mut four = None;
-        let mut three = None;
-        let mut pairs = Vec::new();
-        for &(rank, count) in &rank_counts_vec {
-            match count {
-                4 => four = Some(rank),
-                3 => three = Some(rank),
-                2 => pairs.push(rank),
-                _ => {}
-            }
+    let count_of = |rank: Rank| rank_counts[rank as usize];
+    let four_of_kind = ranks_high_to_low(rank_bits).find(|&r| count_of(r) == 4);
+    let three_of_kind = ranks_high_to_low(rank_bits).find(|&r| count_of(r) == 3);
+    // A second, lower set of trips can only fill a full house as the pair.
+    let pairs: Vec<Rank> = ranks_high_to_low(rank_bits)
+        .filter(|&r| count_of(r) == 2 || (count_of(r) == 3 && Some(r) != three_of_kind))
+        .collect();
+    let other_ranks = |excluded: &[Rank]| {
+        let mut bits = rank_bits;
+        for &rank in excluded {
+            bits &= !(1 << (rank as u8));
         }
-        (four, three, pairs)
+        ranks_high_to_low(bits)
     };
 
-    // Check for straight flush: must be both a straight and a flush
-    if is_flush && is_straight {
-        // Find the suit that has 5+ cards (the flush suit)
-        let flush_suit = suit_counts
-            .iter()
-            .find(|(_, &count)| count >= 5)
-            .map(|(suit, _)| *suit);
-
-        if let Some(flush_suit) = flush_suit {
-            let flush_cards: Vec<Card> = cards_vec
-                .iter()
-                .filter(|c| c.suit == flush_suit)
-                .cloned()
-                .collect();
-            let flush_ranks: Vec<Rank> = flush_cards.iter().map(|c| c.rank).collect();
-            let flush_unique: HashSet<Rank> = flush_ranks.iter().copied().collect();
-
-            if let Some(straight_high) = find_straight_high(&flush_unique) {
-                return EvaluatedHand {
-                    hand_rank: HandRank::StraightFlush,
-                    primary_values: vec![straight_high],
-                    kickers: Vec::new(),
-                };
-            }
-        }
-    }
-
     if let Some(four) = four_of_kind {
-        let kicker: Vec<Rank> = ranks
-            .iter()
-            .filter(|&&r| r != four)
-            .copied()
-            .max()
-            .map(|r| vec![r])
-            .unwrap_or_default();
         return EvaluatedHand {
             hand_rank: HandRank::FourOfAKind,
             primary_values: vec![four],
-            kickers: kicker,
+            kickers: other_ranks(&[four]).take(1).collect(),
         };
     }
 
-    if let Some(three) = three_of_kind {
-        if !pairs.is_empty() {
-            let pair = pairs[0];
-            return EvaluatedHand {
-                hand_rank: HandRank::FullHouse,
-                primary_values: vec![three, pair],
-                kickers: Vec::new(),
-            };
-        }
+    if let (Some(three), Some(&pair)) = (three_of_kind, pairs.first()) {
+        return EvaluatedHand {
+            hand_rank: HandRank::FullHouse,
+            primary_values: vec![three, pair],
+            kickers: Vec::new(),
+        };
     }
 
-    if is_flush {
-        let flush_suit = suit_counts
-            .iter()
-            .find(|(_, &count)| count >= 5)
-            .map(|(suit, _)| *suit);
-
-        if let Some(flush_suit) = flush_suit {
-            let flush_values: Vec<Rank> = cards_vec
-                .iter()
-                .filter(|c| c.suit == flush_suit)
-                .map(|c| c.rank)
-                .rev()
-                .collect();
-            return EvaluatedHand {
-                hand_rank: HandRank::Flush,
-                primary_values: flush_values,
-                kickers: Vec::new(),
-            };
-        }
+    if let Some(flush_suit) = flush_suit {
+        return EvaluatedHand {
+            hand_rank: HandRank::Flush,
+            primary_values: ranks_high_to_low(suit_rank_bits[flush_suit])
+                .take(HAND_SIZE)
+                .collect(),
+            kickers: Vec::new(),
+        };
     }
 
-    if is_straight {
+    if let Some(straight_high) = find_straight_high(rank_bits) {
         return EvaluatedHand {
             hand_rank: HandRank::Straight,
-            primary_values: vec![
-                straight_high.expect("straight_high should be Some when is_straight is true")
-            ],
+            primary_values: vec![straight_high],
             kickers: Vec::new(),
         };
     }
 
     if let Some(three) = three_of_kind {
-        let kickers: Vec<Rank> = ranks
-            .iter()
-            .filter(|&&r| r != three)
-            .copied()
-            .rev()
-            .take(2)
-            .collect();
         return EvaluatedHand {
             hand_rank: HandRank::ThreeOfAKind,
             primary_values: vec![three],
-            kickers,
+            kickers: other_ranks(&[three]).take(2).collect(),
         };
     }
 
     if pairs.len() >= 2 {
-        let mut sorted_pairs = pairs;
-        sorted_pairs.sort_by_key(|&r| Reverse(r));
-        let top_two_pairs: Vec<Rank> = sorted_pairs.iter().take(2).copied().collect();
-        let kicker: Vec<Rank> = ranks
-            .iter()
-            .filter(|&&r| !top_two_pairs.contains(&r))
-            .copied()
-            .rev()
-            .take(1)
-            .collect();
+        let top_two_pairs: Vec<Rank> = pairs.iter().take(2).copied().collect();
         return EvaluatedHand {
             hand_rank: HandRank::TwoPair,
+            kickers: other_ranks(&top_two_pairs).take(1).collect(),
             primary_values: top_two_pairs,
-            kickers: kicker,
         };
     }
 
-    if pairs.len() == 1 {
-        let pair = pairs[0];
-        let kickers: Vec<Rank> = ranks
-            .iter()
-            .filter(|&&r| r != pair)
-            .copied()
-            .rev()
-            .take(3)
-            .collect();
+    if let Some(&pair) = pairs.first() {
         return EvaluatedHand {
             hand_rank: HandRank::Pair,
             primary_values: vec![pair],
-            kickers,
+            kickers: other_ranks(&[pair]).take(3).collect(),
         };
     }
 
-    let high_cards: Vec<Rank> = ranks.iter().copied().rev().collect();
     EvaluatedHand {
         hand_rank: HandRank::HighCard,
-        primary_values: high_cards,
+        primary_values: ranks_high_to_low(rank_bits).take(HAND_SIZE).collect(),
         kickers: Vec::new(),
     }
 }
 
-pub fn determine_winner(
-    p1_hole: &[Card; 2],
-    p2_hole: &[Card; 2],
-    community_cards: &[Card; 5],
-) -> i32 {
-    let player1_hand: Vec<Card> = p1_hole
-        .iter()
-        .chain(community_cards.iter())
-        .cloned()
-        .collect();
-    let player2_hand: Vec<Card> = p2_hole
+/// Index of a seat at the table.
+pub type SeatIndex = usize;
+/// A player's two private cards.
+pub type HoleCards = [Card; 2];
+/// The five community cards shared by every player.
+pub type Board = [Card; 5];
+
+/// Ranks every player's best hand against the shared board.
+///
+/// Returns tiers of seats ordered from best to worst hand. Seats within a tier
+/// hold hands of exactly equal value and therefore split whatever they win;
+/// seats inside a tier are listed in ascending seat order.
+///
+/// # Example
+/// Three players where seats 0 and 2 tie for the best hand:
+/// `[[0, 2], [1]]`
+pub fn rank_players(hands: &[HoleCards], board: &Board) -> Vec<Vec<SeatIndex>> {
+    let evaluated: Vec<EvaluatedHand> = hands
         .iter()
-        .chain(community_cards.iter())
-        .cloned()
+        .map(|hole| {
+            let cards: Vec<Card> = hole.iter().chain(board.iter()).copied().collect();
+            evaluate_hand(&cards)
+        })
         .collect();
 
-    let eval1 = evaluate_hand(&player1_hand);
-    let eval2 = evaluate_hand(&player2_hand);
+    let mut seats: Vec<SeatIndex> = (0..hands.len()).collect();
+    seats.sort_by(|&a, &b| evaluated[b].cmp(&evaluated[a]).then(a.cmp(&b)));
 
-    let score1 = eval1.score();
-    let score2 = eval2.score();
-
-    match score1.cmp(&score2) {
-        std::cmp::Ordering::Greater => 0,
-        std::cmp::Ordering::Less => 1,
-        std::cmp::Ordering::Equal => -1,
+    let mut tiers: Vec<Vec<SeatIndex>> = Vec::new();
+    for seat in seats {
+        match tiers.last_mut() {
+            Some(tier) if evaluated[tier[0]] == evaluated[seat] => tier.push(seat),
+            _ => tiers.push(vec![seat]),
+        }
     }
+    tiers
 }
 
 #[cfg(test)]
@@ -486,6 +645,15 @@ mod tests {
         Card::new(rank, suit)
     }
 
+    /// Heads-up shorthand over `rank_players`: 0 or 1 for the winning seat, -1 for a split.
+    fn determine_winner(p1_hole: &HoleCards, p2_hole: &HoleCards, board: &Board) -> i32 {
+        let tiers = rank_players(&[*p1_hole, *p2_hole], board);
+        match tiers[0].as_slice() {
+            [winner] => *winner as i32,
+            _ => -1,
+        }
+    }
+
     #[test]
     fn test_high_card() {
         let hand = [
@@ -1271,4 +1439,701 @@ mod tests {
         assert_eq!(eval.hand_rank, HandRank::Straight);
         assert_eq!(eval.primary_values[0], Rank::Six);
     }
+
+    fn eval_of(cards: &[Card]) -> EvaluatedHand {
+        evaluate_hand(cards)
+    }
+
+    /// Hands listed strictly weakest to strongest, covering kicker and
+    /// secondary-value tie-breaks inside every category.
+    fn ordered_ladder() -> Vec<Vec<Card>> {
+        use Rank::*;
+        use Suit::*;
+        vec![
+            // High card, decided by the fifth card
+            vec![
+                card(Seven, Hearts),
+                card(Five, Spades),
+                card(Four, Diamonds),
+                card(Three, Clubs),
+                card(Two, Hearts),
+            ],
+            vec![
+                card(Seven, Hearts),
+                card(Six, Spades),
+                card(Four, Diamonds),
+                card(Three, Clubs),
+                card(Two, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(King, Spades),
+                card(Queen, Diamonds),
+                card(Jack, Clubs),
+                card(Nine, Hearts),
+            ],
+            // Pair, then each kicker in turn
+            vec![
+                card(Two, Hearts),
+                card(Two, Spades),
+                card(Five, Diamonds),
+                card(Four, Clubs),
+                card(Three, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(Queen, Diamonds),
+                card(Jack, Clubs),
+                card(Nine, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(King, Diamonds),
+                card(Four, Clubs),
+                card(Three, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(King, Diamonds),
+                card(Five, Clubs),
+                card(Three, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(King, Diamonds),
+                card(Five, Clubs),
+                card(Four, Hearts),
+            ],
+            // Two pair: top pair, bottom pair, kicker
+            vec![
+                card(Three, Hearts),
+                card(Three, Spades),
+                card(Two, Diamonds),
+                card(Two, Clubs),
+                card(Four, Hearts),
+            ],
+            vec![
+                card(King, Hearts),
+                card(King, Spades),
+                card(Queen, Diamonds),
+                card(Queen, Clubs),
+                card(Ace, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(Two, Diamonds),
+                card(Two, Clubs),
+                card(Three, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(Two, Diamonds),
+                card(Two, Clubs),
+                card(King, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(King, Diamonds),
+                card(King, Clubs),
+                card(Two, Hearts),
+            ],
+            // Three of a kind and its kickers
+            vec![
+                card(Two, Hearts),
+                card(Two, Spades),
+                card(Two, Diamonds),
+                card(Four, Clubs),
+                card(Three, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(Ace, Diamonds),
+                card(Three, Clubs),
+                card(Two, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(Ace, Diamonds),
+                card(King, Clubs),
+                card(Two, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(Ace, Diamonds),
+                card(King, Clubs),
+                card(Queen, Hearts),
+            ],
+            // Straights, wheel is the lowest
+            vec![
+                card(Ace, Hearts),
+                card(Two, Spades),
+                card(Three, Diamonds),
+                card(Four, Clubs),
+                card(Five, Hearts),
+            ],
+            vec![
+                card(Two, Hearts),
+                card(Three, Spades),
+                card(Four, Diamonds),
+                card(Five, Clubs),
+                card(Six, Hearts),
+            ],
+            vec![
+                card(Ten, Hearts),
+                card(Jack, Spades),
+                card(Queen, Diamonds),
+                card(King, Clubs),
+                card(Ace, Hearts),
+            ],
+            // Flushes compare all five cards
+            vec![
+                card(Seven, Clubs),
+                card(Five, Clubs),
+                card(Four, Clubs),
+                card(Three, Clubs),
+                card(Two, Clubs),
+            ],
+            vec![
+                card(Ace, Clubs),
+                card(King, Clubs),
+                card(Queen, Clubs),
+                card(Jack, Clubs),
+                card(Two, Clubs),
+            ],
+            vec![
+                card(Ace, Clubs),
+                card(King, Clubs),
+                card(Queen, Clubs),
+                card(Jack, Clubs),
+                card(Three, Clubs),
+            ],
+            vec![
+                card(Ace, Clubs),
+                card(King, Clubs),
+                card(Queen, Clubs),
+                card(Jack, Clubs),
+                card(Nine, Clubs),
+            ],
+            // Full houses: trips first, then the pair
+            vec![
+                card(Two, Hearts),
+                card(Two, Spades),
+                card(Two, Diamonds),
+                card(Three, Clubs),
+                card(Three, Hearts),
+            ],
+            vec![
+                card(King, Hearts),
+                card(King, Spades),
+                card(King, Diamonds),
+                card(Ace, Clubs),
+                card(Ace, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(Ace, Diamonds),
+                card(Two, Clubs),
+                card(Two, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(Ace, Diamonds),
+                card(King, Clubs),
+                card(King, Hearts),
+            ],
+            // Four of a kind and its kicker
+            vec![
+                card(Two, Hearts),
+                card(Two, Spades),
+                card(Two, Diamonds),
+                card(Two, Clubs),
+                card(Three, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(Ace, Diamonds),
+                card(Ace, Clubs),
+                card(Two, Hearts),
+            ],
+            vec![
+                card(Ace, Hearts),
+                card(Ace, Spades),
+                card(Ace, Diamonds),
+                card(Ace, Clubs),
+                card(King, Hearts),
+            ],
+            // Straight flushes
+            vec![
+                card(Ace, Spades),
+                card(Two, Spades),
+                card(Three, Spades),
+                card(Four, Spades),
+                card(Five, Spades),
+            ],
+            vec![
+                card(Nine, Spades),
+                card(Ten, Spades),
+                card(Jack, Spades),
+                card(Queen, Spades),
+                card(King, Spades),
+            ],
+            vec![
+                card(Ten, Spades),
+                card(Jack, Spades),
+                card(Queen, Spades),
+                card(King, Spades),
+                card(Ace, Spades),
+            ],
+        ]
+    }
+
+    #[test]
+    fn test_ordering_ladder_is_strict_and_transitive() {
+        let ladder: Vec<EvaluatedHand> = ordered_ladder().iter().map(|h| eval_of(h)).collect();
+        for (i, lower) in ladder.iter().enumerate() {
+            for (j, higher) in ladder.iter().enumerate() {
+                assert_eq!(
+                    lower.cmp(higher),
+                    i.cmp(&j),
+                    "ladder entries {} and {} compare incorrectly: {:?} vs {:?}",
+                    i,
+                    j,
+                    lower,
+                    higher
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_ordering_ignores_suits() {
+        for hand in ordered_ladder() {
+            let recoloured: Vec<Card> = hand
+                .iter()
+                .map(|c| {
+                    let suit = match c.suit {
+                        Suit::Hearts => Suit::Diamonds,
+                        Suit::Diamonds => Suit::Clubs,
+                        Suit::Clubs => Suit::Spades,
+                        Suit::Spades => Suit::Hearts,
+                    };
+                    card(c.rank, suit)
+                })
+                .collect();
+            assert_eq!(eval_of(&hand).cmp(&eval_of(&recoloured)), Ordering::Equal);
+        }
+    }
+
+    #[test]
+    fn test_seven_card_hand_equals_best_five_card_subset() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(26);
+        let mut all_cards = Vec::with_capacity(52);
+        for &suit in &SUITS {
+            for &rank in &RANKS {
+                all_cards.push(Card::new(rank, suit));
+            }
+        }
+
+        for _ in 0..300 {
+            all_cards.shuffle(&mut rng);
+            let seven = &all_cards[..7];
+            let best = best_five_card_subset(seven);
+            assert_eq!(
+                eval_of(seven).cmp(&best),
+                Ordering::Equal,
+                "7-card evaluation of {:?} differs from best subset",
+                seven
+            );
+        }
+    }
+
+    fn best_five_card_subset(seven: &[Card]) -> EvaluatedHand {
+        (0..7)
+            .flat_map(|skip_a| ((skip_a + 1)..7).map(move |skip_b| (skip_a, skip_b)))
+            .map(|(skip_a, skip_b)| {
+                let five: Vec<Card> = seven
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != skip_a && *i != skip_b)
+                    .map(|(_, c)| *c)
+                    .collect();
+                eval_of(&five)
+            })
+            .max()
+            .expect("21 subsets")
+    }
+
+    #[test]
+    fn test_pair_kicker_decides_winner() {
+        let p1 = [
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::King, Suit::Spades),
+        ];
+        let p2 = [
+            card(Rank::Ace, Suit::Spades),
+            card(Rank::Queen, Suit::Hearts),
+        ];
+        let community = [
+            card(Rank::Ace, Suit::Diamonds),
+            card(Rank::Nine, Suit::Clubs),
+            card(Rank::Seven, Suit::Hearts),
+            card(Rank::Four, Suit::Spades),
+            card(Rank::Two, Suit::Diamonds),
+        ];
+
+        assert_eq!(determine_winner(&p1, &p2, &community), 0);
+        assert_eq!(determine_winner(&p2, &p1, &community), 1);
+    }
+
+    #[test]
+    fn test_two_pair_kicker_decides_winner() {
+        let p1 = [
+            card(Rank::Queen, Suit::Hearts),
+            card(Rank::Three, Suit::Spades),
+        ];
+        let p2 = [
+            card(Rank::Jack, Suit::Spades),
+            card(Rank::Four, Suit::Hearts),
+        ];
+        let community = [
+            card(Rank::King, Suit::Diamonds),
+            card(Rank::King, Suit::Clubs),
+            card(Rank::Eight, Suit::Hearts),
+            card(Rank::Eight, Suit::Spades),
+            card(Rank::Two, Suit::Diamonds),
+        ];
+
+        assert_eq!(determine_winner(&p1, &p2, &community), 0);
+    }
+
+    #[test]
+    fn test_four_of_kind_on_board_kicker_decides_winner() {
+        let p1 = [
+            card(Rank::King, Suit::Hearts),
+            card(Rank::Two, Suit::Spades),
+        ];
+        let p2 = [
+            card(Rank::Queen, Suit::Spades),
+            card(Rank::Jack, Suit::Hearts),
+        ];
+        let community = [
+            card(Rank::Nine, Suit::Diamonds),
+            card(Rank::Nine, Suit::Clubs),
+            card(Rank::Nine, Suit::Hearts),
+            card(Rank::Nine, Suit::Spades),
+            card(Rank::Three, Suit::Diamonds),
+        ];
+
+        assert_eq!(determine_winner(&p1, &p2, &community), 0);
+    }
+
+    #[test]
+    fn test_board_plays_is_split() {
+        let p1 = [
+            card(Rank::Two, Suit::Hearts),
+            card(Rank::Three, Suit::Spades),
+        ];
+        let p2 = [card(Rank::Four, Suit::Spades), card(Rank::Two, Suit::Clubs)];
+        let community = [
+            card(Rank::Ace, Suit::Diamonds),
+            card(Rank::King, Suit::Clubs),
+            card(Rank::Queen, Suit::Hearts),
+            card(Rank::Jack, Suit::Spades),
+            card(Rank::Nine, Suit::Diamonds),
+        ];
+
+        assert_eq!(determine_winner(&p1, &p2, &community), -1);
+    }
+
+    #[test]
+    fn test_sixth_flush_card_does_not_break_tie() {
+        let p1 = [
+            card(Rank::Two, Suit::Hearts),
+            card(Rank::Three, Suit::Spades),
+        ];
+        let p2 = [
+            card(Rank::Four, Suit::Spades),
+            card(Rank::Five, Suit::Clubs),
+        ];
+        let community = [
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::King, Suit::Hearts),
+            card(Rank::Nine, Suit::Hearts),
+            card(Rank::Seven, Suit::Hearts),
+            card(Rank::Six, Suit::Hearts),
+        ];
+
+        assert_eq!(determine_winner(&p1, &p2, &community), -1);
+        let eval = evaluate_hand(&[p1.as_slice(), community.as_slice()].concat());
+        assert_eq!(eval.primary_values.len(), 5);
+    }
+
+    #[test]
+    fn test_higher_flush_card_wins() {
+        let p1 = [
+            card(Rank::Queen, Suit::Hearts),
+            card(Rank::Three, Suit::Spades),
+        ];
+        let p2 = [
+            card(Rank::Jack, Suit::Hearts),
+            card(Rank::Five, Suit::Clubs),
+        ];
+        let community = [
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::King, Suit::Hearts),
+            card(Rank::Nine, Suit::Hearts),
+            card(Rank::Seven, Suit::Hearts),
+            card(Rank::Two, Suit::Spades),
+        ];
+
+        assert_eq!(determine_winner(&p1, &p2, &community), 0);
+    }
+
+    #[test]
+    fn test_unused_high_cards_do_not_break_tie() {
+        let p1 = [
+            card(Rank::Three, Suit::Hearts),
+            card(Rank::Two, Suit::Spades),
+        ];
+        let p2 = [card(Rank::Four, Suit::Spades), card(Rank::Two, Suit::Clubs)];
+        let community = [
+            card(Rank::Ace, Suit::Diamonds),
+            card(Rank::King, Suit::Clubs),
+            card(Rank::Jack, Suit::Hearts),
+            card(Rank::Nine, Suit::Spades),
+            card(Rank::Seven, Suit::Diamonds),
+        ];
+
+        assert_eq!(determine_winner(&p1, &p2, &community), -1);
+    }
+
+    #[test]
+    fn test_two_sets_make_full_house() {
+        let hand = [
+            card(Rank::Nine, Suit::Hearts),
+            card(Rank::Nine, Suit::Spades),
+            card(Rank::Nine, Suit::Diamonds),
+            card(Rank::Four, Suit::Clubs),
+            card(Rank::Four, Suit::Hearts),
+            card(Rank::Four, Suit::Spades),
+            card(Rank::Two, Suit::Diamonds),
+        ];
+        let eval = evaluate_hand(&hand);
+        assert_eq!(eval.hand_rank, HandRank::FullHouse);
+        assert_eq!(eval.primary_values, vec![Rank::Nine, Rank::Four]);
+    }
+
+    #[test]
+    fn test_full_house_uses_highest_available_pair() {
+        let hand = [
+            card(Rank::Five, Suit::Hearts),
+            card(Rank::Five, Suit::Spades),
+            card(Rank::Five, Suit::Diamonds),
+            card(Rank::Queen, Suit::Clubs),
+            card(Rank::Queen, Suit::Hearts),
+            card(Rank::Three, Suit::Spades),
+            card(Rank::Three, Suit::Diamonds),
+        ];
+        let eval = evaluate_hand(&hand);
+        assert_eq!(eval.hand_rank, HandRank::FullHouse);
+        assert_eq!(eval.primary_values, vec![Rank::Five, Rank::Queen]);
+    }
+
+    #[test]
+    fn test_wheel_loses_to_six_high_straight() {
+        let p1 = [card(Rank::Ace, Suit::Hearts), card(Rank::Two, Suit::Spades)];
+        let p2 = [card(Rank::Six, Suit::Spades), card(Rank::Two, Suit::Clubs)];
+        let community = [
+            card(Rank::Three, Suit::Diamonds),
+            card(Rank::Four, Suit::Clubs),
+            card(Rank::Five, Suit::Hearts),
+            card(Rank::King, Suit::Spades),
+            card(Rank::Nine, Suit::Diamonds),
+        ];
+
+        assert_eq!(determine_winner(&p1, &p2, &community), 1);
+    }
+
+    #[test]
+    fn test_rank_players_multiway_tiers() {
+        let board = [
+            card(Rank::King, Suit::Diamonds),
+            card(Rank::Nine, Suit::Clubs),
+            card(Rank::Seven, Suit::Hearts),
+            card(Rank::Four, Suit::Spades),
+            card(Rank::Two, Suit::Diamonds),
+        ];
+        let hands = [
+            [
+                card(Rank::Ace, Suit::Hearts),
+                card(Rank::Three, Suit::Spades),
+            ],
+            [
+                card(Rank::King, Suit::Hearts),
+                card(Rank::Queen, Suit::Spades),
+            ],
+            [
+                card(Rank::Ace, Suit::Spades),
+                card(Rank::Three, Suit::Clubs),
+            ],
+            [card(Rank::King, Suit::Clubs), card(Rank::Jack, Suit::Clubs)],
+        ];
+
+        let tiers = rank_players(&hands, &board);
+        assert_eq!(tiers, vec![vec![1], vec![3], vec![0, 2]]);
+    }
+
+    #[test]
+    fn test_rank_players_everyone_splits_when_board_plays() {
+        let board = [
+            card(Rank::Ten, Suit::Diamonds),
+            card(Rank::Jack, Suit::Clubs),
+            card(Rank::Queen, Suit::Hearts),
+            card(Rank::King, Suit::Spades),
+            card(Rank::Ace, Suit::Diamonds),
+        ];
+        let hands = [
+            [
+                card(Rank::Two, Suit::Hearts),
+                card(Rank::Three, Suit::Spades),
+            ],
+            [
+                card(Rank::Four, Suit::Hearts),
+                card(Rank::Five, Suit::Spades),
+            ],
+            [
+                card(Rank::Six, Suit::Spades),
+                card(Rank::Seven, Suit::Clubs),
+            ],
+        ];
+
+        assert_eq!(rank_players(&hands, &board), vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn test_rank_players_single_player() {
+        let board = [
+            card(Rank::Ten, Suit::Diamonds),
+            card(Rank::Jack, Suit::Clubs),
+            card(Rank::Two, Suit::Hearts),
+            card(Rank::Five, Suit::Spades),
+            card(Rank::Eight, Suit::Diamonds),
+        ];
+        let hands = [[
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::King, Suit::Spades),
+        ]];
+
+        assert_eq!(rank_players(&hands, &board), vec![vec![0]]);
+        assert!(rank_players(&[], &board).is_empty());
+    }
+
+    #[test]
+    fn test_parse_single_cards() {
+        assert_eq!("Ah".parse::<Card>(), Ok(card(Rank::Ace, Suit::Hearts)));
+        assert_eq!("Td".parse::<Card>(), Ok(card(Rank::Ten, Suit::Diamonds)));
+        assert_eq!("10d".parse::<Card>(), Ok(card(Rank::Ten, Suit::Diamonds)));
+        assert_eq!("2c".parse::<Card>(), Ok(card(Rank::Two, Suit::Clubs)));
+        assert_eq!("ks".parse::<Card>(), Ok(card(Rank::King, Suit::Spades)));
+        assert_eq!("Q♦".parse::<Card>(), Ok(card(Rank::Queen, Suit::Diamonds)));
+    }
+
+    #[test]
+    fn test_parse_card_list() {
+        let cards = parse_cards("AhKh Qs Jd Tc").unwrap();
+        assert_eq!(
+            cards,
+            vec![
+                card(Rank::Ace, Suit::Hearts),
+                card(Rank::King, Suit::Hearts),
+                card(Rank::Queen, Suit::Spades),
+                card(Rank::Jack, Suit::Diamonds),
+                card(Rank::Ten, Suit::Clubs),
+            ]
+        );
+        assert_eq!(parse_cards("Ah, 9c,2d").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_cards(""), Err(ParseCardError::Empty));
+        assert_eq!(parse_cards("  , "), Err(ParseCardError::Empty));
+        assert_eq!(
+            parse_cards("Ah Xh"),
+            Err(ParseCardError::InvalidRank("X".to_string()))
+        );
+        assert_eq!(
+            parse_cards("Ah Kx"),
+            Err(ParseCardError::InvalidSuit("Kx".to_string()))
+        );
+        assert_eq!(
+            parse_cards("AhK"),
+            Err(ParseCardError::InvalidSuit("K".to_string()))
+        );
+        assert_eq!(
+            parse_cards("Ah Kd ah"),
+            Err(ParseCardError::Duplicate(card(Rank::Ace, Suit::Hearts)))
+        );
+        assert_eq!(
+            "AhKh".parse::<Card>(),
+            Err(ParseCardError::NotSingleCard("AhKh".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        let deck = Deck::new();
+        let all: Vec<Card> = deck.cards.clone();
+        for c in &all {
+            assert_eq!(c.to_string().parse::<Card>(), Ok(*c));
+            assert_eq!(format!("{:#}", c).parse::<Card>(), Ok(*c));
+        }
+        assert_eq!(parse_cards(&format_cards(&all)), Ok(all));
+        assert_eq!(card(Rank::Ten, Suit::Spades).to_string(), "Ts");
+        assert_eq!(format!("{:#}", card(Rank::Ten, Suit::Spades)), "10♠");
+        assert_eq!(Card::default().to_string(), "??");
+    }
+
+    #[test]
+    fn test_seeded_decks_match() {
+        use rand::{rngs::StdRng, SeedableRng};
+        let a = Deck::with_rng(&mut StdRng::seed_from_u64(35));
+        let b = Deck::with_rng(&mut StdRng::seed_from_u64(35));
+        assert_eq!(a.cards, b.cards);
+        assert_eq!(a.cards_remaining(), 52);
+    }
+
+    #[test]
+    fn test_has_draw() {
+        let draw = |hole: &str, board: &str| {
+            let hole = parse_cards(hole).unwrap();
+            has_draw(&[hole[0], hole[1]], &parse_cards(board).unwrap())
+        };
+        // Flush draws need a hole card of the suit
+        assert!(draw("Ah2h", "Kh 7h 9c"));
+        assert!(!draw("Ac2d", "Kh 7h 9h 4h"));
+        // Open-ended, gutshot and wheel straight draws
+        assert!(draw("9c8d", "7s 6h Kd"));
+        assert!(draw("9c5d", "7s 6h Kd"));
+        assert!(draw("Ac2d", "3s 4h Kd"));
+        // Made hands and draws entirely on the board do not count
+        assert!(!draw("9c8d", "7s 6h 5d"));
+        assert!(!draw("Kc2d", "9s 8h 7d 6c"));
+        assert!(!draw("KcQd", "7s 2h 4d"));
+    }
 }
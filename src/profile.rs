@@ -0,0 +1,423 @@
+//! AI personality profiles and difficulty tiers.
+//!
+//! A seat is configured with one profile and one difficulty. The profile is
+//! the parameter set for the betting strategy (how tight, how aggressive);
+//! the difficulty picks the strategy itself and how often it blunders. Both
+//! lists are read from `config/ai_profiles.json`, and the copy compiled into
+//! the binary is used when that file is missing or invalid. Profiles evolved
+//! by the `tune` command are kept in their own file and added on top.
+
+use crate::agent::AgentKind;
+use crate::constants::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// The configuration compiled into the binary.
+const BUILT_IN_CONFIG: &str = include_str!("../config/ai_profiles.json");
+
+/// Parameters for the betting strategies. Fields left out of a config file
+/// keep the values of the `AI_*` constants.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiProfile {
+    pub name: String,
+    /// Hand strength below which the threshold bot folds to a bet
+    pub fold_threshold: f32,
+    /// Minimum hand strength for the threshold bot to call
+    pub call_threshold: f32,
+    /// Minimum hand strength for the threshold bot to bet or raise
+    pub raise_threshold: f32,
+    /// Minimum hand strength for the threshold bot to play a marginal hand
+    pub marginal_threshold: f32,
+    /// Hand strength below which the threshold bot folds at bad pot odds
+    pub bad_odds_fold_threshold: f32,
+    /// Pot odds at which marginal hands fold
+    pub pot_odds_bad: f32,
+    /// Maximum pot odds for calling with medium strength
+    pub pot_odds_call: f32,
+    /// Maximum pot odds for calling with marginal strength
+    pub pot_odds_good: f32,
+    /// Strength bonus on the button
+    pub position_bonus: f32,
+    /// Extra preflop strength bonus on the button
+    pub preflop_dealer_bonus: f32,
+    /// Preflop strength adjustment out of position
+    pub preflop_non_dealer_penalty: f32,
+    /// Equity above which the equity bot bets into an unopened pot
+    pub equity_bet_threshold: f64,
+    /// Equity above which the equity bot raises
+    pub equity_raise_threshold: f64,
+    /// Share of later streets the equity bot credits as implied odds
+    pub implied_odds_factor: f64,
+    /// Equity the equity bot is willing to call short of the price
+    pub call_margin: f64,
+    /// Scale (0.0-1.0) on the balanced bluffing frequency for weak hands
+    pub bluff_frequency: f64,
+    /// Probability of betting or raising a draw instead of checking or calling
+    pub semi_bluff_frequency: f64,
+    /// Probability of just calling or checking with a monster
+    pub slow_play_frequency: f64,
+    /// Probability of checking a monster out of position to check-raise
+    pub check_raise_frequency: f64,
+}
+
+impl Default for AiProfile {
+    fn default() -> Self {
+        Self {
+            name: "Balanced".to_string(),
+            fold_threshold: AI_STRENGTH_FOLD_THRESHOLD,
+            call_threshold: AI_STRENGTH_CALL_THRESHOLD,
+            raise_threshold: AI_STRENGTH_RAISE_THRESHOLD,
+            marginal_threshold: AI_STRENGTH_MARGINAL_THRESHOLD,
+            bad_odds_fold_threshold: AI_STRENGTH_BAD_ODDS_FOLD_THRESHOLD,
+            pot_odds_bad: AI_POT_ODDS_BAD_THRESHOLD,
+            pot_odds_call: AI_POT_ODDS_CALL_THRESHOLD,
+            pot_odds_good: AI_POT_ODDS_GOOD_THRESHOLD,
+            position_bonus: AI_POSITION_BONUS,
+            preflop_dealer_bonus: AI_PREFLOP_DEALER_BONUS,
+            preflop_non_dealer_penalty: AI_PREFLOP_NON_DEALER_PENALTY,
+            equity_bet_threshold: AI_EQUITY_BET_THRESHOLD,
+            equity_raise_threshold: AI_EQUITY_RAISE_THRESHOLD,
+            implied_odds_factor: AI_IMPLIED_ODDS_FACTOR,
+            call_margin: 0.0,
+            bluff_frequency: AI_BLUFF_FREQUENCY,
+            semi_bluff_frequency: AI_SEMI_BLUFF_FREQUENCY,
+            slow_play_frequency: AI_SLOW_PLAY_FREQUENCY,
+            check_raise_frequency: AI_CHECK_RAISE_FREQUENCY,
+        }
+    }
+}
+
+impl AiProfile {
+    /// Checks that the mixing frequencies are probabilities.
+    pub fn validate(&self) -> Result<(), AiConfigError> {
+        let frequencies = [
+            self.bluff_frequency,
+            self.semi_bluff_frequency,
+            self.slow_play_frequency,
+            self.check_raise_frequency,
+        ];
+        if frequencies.iter().all(|f| (0.0..=1.0).contains(f)) {
+            Ok(())
+        } else {
+            Err(AiConfigError::Frequency(self.name.clone()))
+        }
+    }
+}
+
+/// A difficulty tier: which strategy plays and how carefully.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Difficulty {
+    pub name: String,
+    pub strategy: AgentKind,
+    /// Runouts simulated per decision by the equity strategy
+    pub equity_samples: u32,
+    /// Probability (0.0-1.0) of replacing the chosen action with a random legal one
+    pub mistake_rate: f64,
+    /// Strategy tables played by the CFR strategy, written by `train-cfr`
+    pub strategy_file: String,
+    /// Effective stack, in big blinds, below which preflop play follows the
+    /// push/fold charts; 0 never switches
+    pub push_fold_below_bb: f64,
+    /// Push/fold charts, written by `push-fold`
+    pub push_fold_file: String,
+    /// Program and arguments run by the external strategy
+    pub bot_command: Vec<String>,
+    /// Milliseconds an external bot has to answer each decision
+    pub bot_timeout_ms: u64,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self {
+            name: "Medium".to_string(),
+            strategy: AgentKind::Threshold,
+            equity_samples: AI_EQUITY_SAMPLES,
+            mistake_rate: 0.0,
+            strategy_file: AI_CFR_STRATEGY_PATH.to_string(),
+            push_fold_below_bb: 0.0,
+            push_fold_file: AI_PUSH_FOLD_PATH.to_string(),
+            bot_command: Vec::new(),
+            bot_timeout_ms: EXTERNAL_BOT_TIMEOUT_MS,
+        }
+    }
+}
+
+/// Error returned when the AI configuration cannot be loaded.
+#[derive(Debug)]
+pub enum AiConfigError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    /// The file defines no profiles or no difficulties.
+    Empty,
+    /// A profile has a mixing frequency outside 0.0-1.0.
+    Frequency(String),
+}
+
+impl fmt::Display for AiConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AiConfigError::Io(err) => write!(f, "cannot access AI config: {}", err),
+            AiConfigError::Parse(err) => write!(f, "invalid AI config: {}", err),
+            AiConfigError::Empty => {
+                write!(f, "AI config needs at least one profile and difficulty")
+            }
+            AiConfigError::Frequency(name) => {
+                write!(f, "profile '{}' has a frequency outside 0.0-1.0", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AiConfigError {}
+
+/// All profiles and difficulty tiers that seats can choose from.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AiConfig {
+    pub profiles: Vec<AiProfile>,
+    pub difficulties: Vec<Difficulty>,
+}
+
+impl AiConfig {
+    /// Parses a configuration from JSON.
+    pub fn from_json(json: &str) -> Result<Self, AiConfigError> {
+        let config: Self = serde_json::from_str(json).map_err(AiConfigError::Parse)?;
+        if config.profiles.is_empty() || config.difficulties.is_empty() {
+            return Err(AiConfigError::Empty);
+        }
+        config.profiles.iter().try_for_each(AiProfile::validate)?;
+        Ok(config)
+    }
+
+    /// Reads a configuration file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AiConfigError> {
+        let json = std::fs::read_to_string(path).map_err(AiConfigError::Io)?;
+        Self::from_json(&json)
+    }
+
+    /// The configuration compiled into the binary.
+    pub fn built_in() -> Self {
+        Self::from_json(BUILT_IN_CONFIG).expect("built-in AI config is valid")
+    }
+
+    /// Loads `path`, falling back to the built-in configuration if the file
+    /// is missing or invalid.
+    pub fn load_or_built_in(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Self::built_in();
+        }
+        Self::load(path).unwrap_or_else(|err| {
+            warn!("{} ({}); using built-in AI profiles", err, path.display());
+            Self::built_in()
+        })
+    }
+
+    /// Loads `path` like [`load_or_built_in`](Self::load_or_built_in), then
+    /// adds the profiles in `tuned_path` if that file exists.
+    pub fn load_with_tuned(path: impl AsRef<Path>, tuned_path: impl AsRef<Path>) -> Self {
+        let mut config = Self::load_or_built_in(path);
+        let tuned_path = tuned_path.as_ref();
+        if !tuned_path.exists() {
+            return config;
+        }
+        match load_profiles(tuned_path) {
+            Ok(profiles) => profiles
+                .into_iter()
+                .for_each(|profile| config.upsert_profile(profile)),
+            Err(err) => warn!(
+                "{} ({}); skipping tuned profiles",
+                err,
+                tuned_path.display()
+            ),
+        }
+        config
+    }
+
+    /// Adds `profile`, replacing any profile with the same name.
+    pub fn upsert_profile(&mut self, profile: AiProfile) {
+        upsert_profile(&mut self.profiles, profile);
+    }
+
+    /// Finds a profile by name, ignoring case.
+    pub fn profile(&self, name: &str) -> Option<&AiProfile> {
+        self.profiles
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Finds a difficulty tier by name, ignoring case.
+    pub fn difficulty(&self, name: &str) -> Option<&Difficulty> {
+        self.difficulties
+            .iter()
+            .find(|d| d.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Name of the profile `step` places after `current` in the list, wrapping
+    /// around. An unknown `current` counts as the first profile.
+    pub fn cycle_profile(&self, current: &str, step: isize) -> &str {
+        let names: Vec<&str> = self.profiles.iter().map(|p| p.name.as_str()).collect();
+        cycle_name(&names, current, step)
+    }
+
+    /// Name of the difficulty `step` places after `current`, wrapping around.
+    pub fn cycle_difficulty(&self, current: &str, step: isize) -> &str {
+        let names: Vec<&str> = self.difficulties.iter().map(|d| d.name.as_str()).collect();
+        cycle_name(&names, current, step)
+    }
+}
+
+fn cycle_name<'a>(names: &[&'a str], current: &str, step: isize) -> &'a str {
+    let index = names
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(current))
+        .unwrap_or(0);
+    let next = (index as isize + step).rem_euclid(names.len() as isize);
+    names[next as usize]
+}
+
+impl FromWorld for AiConfig {
+    fn from_world(_world: &mut World) -> Self {
+        Self::load_with_tuned(AI_PROFILES_PATH, AI_TUNED_PROFILES_PATH)
+    }
+}
+
+/// Adds `profile` to `profiles`, replacing any profile with the same name.
+pub fn upsert_profile(profiles: &mut Vec<AiProfile>, profile: AiProfile) {
+    match profiles
+        .iter_mut()
+        .find(|p| p.name.eq_ignore_ascii_case(&profile.name))
+    {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+}
+
+/// Reads a JSON list of profiles, as written by [`save_profiles`].
+pub fn load_profiles(path: impl AsRef<Path>) -> Result<Vec<AiProfile>, AiConfigError> {
+    let json = std::fs::read_to_string(path).map_err(AiConfigError::Io)?;
+    let profiles: Vec<AiProfile> = serde_json::from_str(&json).map_err(AiConfigError::Parse)?;
+    profiles.iter().try_for_each(AiProfile::validate)?;
+    Ok(profiles)
+}
+
+/// Writes `profiles` as a JSON list.
+pub fn save_profiles(path: impl AsRef<Path>, profiles: &[AiProfile]) -> Result<(), AiConfigError> {
+    let json = serde_json::to_string_pretty(profiles).map_err(AiConfigError::Parse)?;
+    std::fs::write(path, json + "\n").map_err(AiConfigError::Io)
+}
+
+/// Profile and difficulty chosen for one seat, by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeatSetup {
+    pub profile: String,
+    pub difficulty: String,
+}
+
+impl SeatSetup {
+    pub fn new(profile: &str, difficulty: &str) -> Self {
+        Self {
+            profile: profile.to_string(),
+            difficulty: difficulty.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_built_in_config_has_named_profiles_and_tiers() {
+        let config = AiConfig::built_in();
+        for name in [
+            "Balanced",
+            "Tight-Passive",
+            "Loose-Aggressive",
+            "Calling Station",
+            "Maniac",
+            "Rock",
+        ] {
+            assert!(config.profile(name).is_some(), "missing profile {}", name);
+        }
+        for name in ["Easy", "Medium", "Hard", "Expert", "Solver"] {
+            assert!(config.difficulty(name).is_some(), "missing tier {}", name);
+        }
+        assert_eq!(config.profile("balanced"), Some(&AiProfile::default()));
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let json = r#"{
+            "profiles": [{ "name": "Nit", "fold_threshold": 0.5 }],
+            "difficulties": [{ "name": "Expert", "strategy": "equity" }]
+        }"#;
+        let config = AiConfig::from_json(json).unwrap();
+        let nit = config.profile("nit").unwrap();
+        assert_eq!(nit.fold_threshold, 0.5);
+        assert_eq!(nit.raise_threshold, AI_STRENGTH_RAISE_THRESHOLD);
+        let expert = config.difficulty("Expert").unwrap();
+        assert_eq!(expert.strategy, AgentKind::Equity);
+        assert_eq!(expert.equity_samples, AI_EQUITY_SAMPLES);
+    }
+
+    #[test]
+    fn test_invalid_configs_are_rejected() {
+        assert!(matches!(
+            AiConfig::from_json(r#"{ "profiles": [], "difficulties": [] }"#),
+            Err(AiConfigError::Empty)
+        ));
+        assert!(matches!(
+            AiConfig::from_json(r#"{ "profiles": [{ "name": 3 }] }"#),
+            Err(AiConfigError::Parse(_))
+        ));
+        assert!(matches!(
+            AiConfig::from_json(
+                r#"{ "profiles": [{ "name": "Wild", "slow_play_frequency": 1.5 }],
+                     "difficulties": [{ "name": "Easy" }] }"#
+            ),
+            Err(AiConfigError::Frequency(name)) if name == "Wild"
+        ));
+        assert!(matches!(
+            AiConfig::load("does/not/exist.json"),
+            Err(AiConfigError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_tuned_profiles_are_added_on_top() {
+        let path = std::env::temp_dir().join("poker_simulation_tuned_test.json");
+        let tuned = AiProfile {
+            name: "Tuned".to_string(),
+            call_threshold: 0.42,
+            ..AiProfile::default()
+        };
+        let rock = AiProfile {
+            name: "rock".to_string(),
+            ..AiProfile::default()
+        };
+        save_profiles(&path, &[tuned.clone(), rock.clone()]).unwrap();
+        assert_eq!(load_profiles(&path).unwrap(), [tuned.clone(), rock.clone()]);
+
+        let built_in = AiConfig::built_in();
+        let config = AiConfig::load_with_tuned("does/not/exist.json", &path);
+        assert_eq!(config.profiles.len(), built_in.profiles.len() + 1);
+        assert_eq!(config.profile("tuned"), Some(&tuned));
+        // A tuned profile replaces a built-in one of the same name
+        assert_eq!(config.profile("Rock"), Some(&rock));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_cycling_wraps_around() {
+        let config = AiConfig::built_in();
+        assert_eq!(config.cycle_difficulty("Easy", 1), "Medium");
+        assert_eq!(config.cycle_difficulty("Expert", 1), "Solver");
+        assert_eq!(config.cycle_difficulty("Easy", -1), "Solver");
+        assert_eq!(config.cycle_profile("Balanced", -1), "Rock");
+        assert_eq!(config.cycle_profile("unknown", 1), "Tight-Passive");
+    }
+}
@@ -0,0 +1,518 @@
+//! Heads-up push/fold equilibrium for short stacks.
+//!
+//! With an effective stack of S big blinds the game is reduced to one
+//! decision each: the small blind moves all-in or folds, and the big blind
+//! calls or folds. [`HandMatrix::solve`] finds the equilibrium of that game
+//! for all 169 starting hands by fictitious play over a table of
+//! hand-versus-hand equities, [`PushFoldCharts`] stores the solutions for a
+//! range of stack depths, and [`PushFoldAgent`] plays them once the
+//! effective stack drops below a configured depth.
+
+use crate::agent::{DecisionBasis, Explanation, PokerAgent};
+use crate::cfr::StrategyFileError;
+use crate::constants::*;
+use crate::game::{ActionRecord, PlayerView, PokerAction};
+use crate::poker_logic::{evaluate_hand, full_deck, Card, HoleCards, PokerRound, SeatIndex, RANKS};
+use crate::range::{starting_hand_grid, starting_hand_name, Range};
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Number of two-card combos in a deck.
+const TOTAL_COMBOS: f64 = 1326.0;
+
+/// Equities between every pair of starting hands, with the number of
+/// card-compatible combo pairs behind each entry.
+pub struct HandMatrix {
+    names: Vec<String>,
+    /// `equity[i * n + j]`: share of the pot hand `i` wins against hand `j`
+    equity: Vec<f64>,
+    /// `weight[i * n + j]`: combo pairs of hands `i` and `j` that share no card
+    weight: Vec<f64>,
+}
+
+impl HandMatrix {
+    /// Estimates every matchup from `samples` random deals.
+    pub fn build<R: Rng + ?Sized>(samples: u32, rng: &mut R) -> Self {
+        let names = starting_hand_grid();
+        let combos: Vec<Vec<HoleCards>> = names
+            .iter()
+            .map(|name| {
+                let range: Range = name.parse().expect("starting hand names are valid ranges");
+                range.combos().iter().map(|c| c.cards).collect()
+            })
+            .collect();
+
+        let n = names.len();
+        let mut equity = vec![0.5; n * n];
+        let mut weight = vec![0.0; n * n];
+        let mut deck = full_deck();
+        for i in 0..n {
+            for j in i..n {
+                let (pairs, share) = matchup(&combos[i], &combos[j], samples, &mut deck, rng);
+                weight[i * n + j] = pairs;
+                weight[j * n + i] = pairs;
+                equity[i * n + j] = share;
+                equity[j * n + i] = 1.0 - share;
+            }
+        }
+        Self {
+            names,
+            equity,
+            weight,
+        }
+    }
+
+    /// Solves the push/fold game at `stack_bb` big blinds with `iterations`
+    /// rounds of fictitious play.
+    pub fn solve(&self, stack_bb: f64, iterations: u32) -> PushFoldChart {
+        let n = self.names.len();
+        let mut push = vec![1.0; n];
+        let mut call = vec![1.0; n];
+        for t in 1..=iterations {
+            let step = 1.0 / (f64::from(t) + 1.0);
+            let calls: Vec<f64> = (0..n).map(|h| self.best_call(h, &push, stack_bb)).collect();
+            for (average, best) in call.iter_mut().zip(calls) {
+                *average += (best - *average) * step;
+            }
+            let pushes: Vec<f64> = (0..n).map(|h| self.best_push(h, &call, stack_bb)).collect();
+            for (average, best) in push.iter_mut().zip(pushes) {
+                *average += (best - *average) * step;
+            }
+        }
+
+        let table = |probabilities: &[f64]| {
+            self.names
+                .iter()
+                .zip(probabilities)
+                .map(|(name, p)| (name.clone(), (p * 1e3).round() / 1e3))
+                .collect()
+        };
+        PushFoldChart {
+            stack_bb,
+            push: table(&push),
+            call: table(&call),
+        }
+    }
+}
+
+impl HandMatrix {
+    /// Weights and equities of `hand` against every other hand.
+    fn matchups(&self, hand: usize) -> impl Iterator<Item = (f64, f64)> + '_ {
+        let n = self.names.len();
+        let row = hand * n..(hand + 1) * n;
+        self.weight[row.clone()]
+            .iter()
+            .copied()
+            .zip(self.equity[row].iter().copied())
+    }
+
+    /// 1.0 if the big blind should call with `hand` against the push
+    /// frequencies `push`: calling must lose less than the blind it gives up.
+    fn best_call(&self, hand: usize, push: &[f64], stack_bb: f64) -> f64 {
+        let (mut called, mut mass) = (0.0, 0.0);
+        for ((weight, equity), pushed) in self.matchups(hand).zip(push) {
+            let w = weight * pushed;
+            called += w * stack_bb * (2.0 * equity - 1.0);
+            mass += w;
+        }
+        f64::from(u8::from(mass > 0.0 && called > -mass))
+    }
+
+    /// 1.0 if the small blind should push `hand` against the call
+    /// frequencies `call`: pushing must beat giving up the small blind.
+    fn best_push(&self, hand: usize, call: &[f64], stack_bb: f64) -> f64 {
+        let (mut pushed, mut mass) = (0.0, 0.0);
+        for ((weight, equity), called) in self.matchups(hand).zip(call) {
+            let showdown = stack_bb * (2.0 * equity - 1.0);
+            pushed += weight * ((1.0 - called) + called * showdown);
+            mass += weight;
+        }
+        f64::from(u8::from(pushed > -0.5 * mass))
+    }
+}
+
+/// Number of card-compatible combo pairs between two starting hands, and
+/// the first hand's equity estimated from `samples` of them.
+fn matchup<R: Rng + ?Sized>(
+    first: &[HoleCards],
+    second: &[HoleCards],
+    samples: u32,
+    deck: &mut [Card],
+    rng: &mut R,
+) -> (f64, f64) {
+    let pairs: Vec<(HoleCards, HoleCards)> = first
+        .iter()
+        .flat_map(|&a| second.iter().map(move |&b| (a, b)))
+        .filter(|(a, b)| !a.iter().any(|card| b.contains(card)))
+        .collect();
+    if pairs.is_empty() || samples == 0 {
+        return (pairs.len() as f64, 0.5);
+    }
+    let score: f64 = (0..samples)
+        .map(|_| {
+            let (a, b) = pairs.choose(rng).copied().expect("pairs is not empty");
+            showdown_share(&a, &b, deck, rng)
+        })
+        .sum();
+    (pairs.len() as f64, score / f64::from(samples))
+}
+
+/// Share of the pot `a` wins against `b` on a random board.
+fn showdown_share<R: Rng + ?Sized>(
+    a: &HoleCards,
+    b: &HoleCards,
+    deck: &mut [Card],
+    rng: &mut R,
+) -> f64 {
+    // Nine cards always leave five that are not in either hand
+    let (drawn, _) = deck.partial_shuffle(rng, 9);
+    let board: Vec<Card> = drawn
+        .iter()
+        .filter(|c| !a.contains(c) && !b.contains(c))
+        .take(MAX_COMMUNITY_CARDS)
+        .copied()
+        .collect();
+    let hand = |hole: &HoleCards| {
+        let cards: Vec<Card> = hole.iter().chain(&board).copied().collect();
+        evaluate_hand(&cards)
+    };
+    match hand(a).cmp(&hand(b)) {
+        Ordering::Greater => 1.0,
+        Ordering::Equal => 0.5,
+        Ordering::Less => 0.0,
+    }
+}
+
+/// Number of combos of a starting hand name.
+fn combo_count(name: &str) -> f64 {
+    match name.chars().nth(2) {
+        Some('s') => 4.0,
+        Some(_) => 12.0,
+        None => 6.0,
+    }
+}
+
+/// Equilibrium ranges at one stack depth: how often each starting hand is
+/// pushed from the small blind and called from the big blind.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushFoldChart {
+    pub stack_bb: f64,
+    pub push: BTreeMap<String, f64>,
+    pub call: BTreeMap<String, f64>,
+}
+
+impl PushFoldChart {
+    pub fn push_probability(&self, hole: &HoleCards) -> f64 {
+        self.push
+            .get(&starting_hand_name(hole))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    pub fn call_probability(&self, hole: &HoleCards) -> f64 {
+        self.call
+            .get(&starting_hand_name(hole))
+            .copied()
+            .unwrap_or(0.0)
+    }
+}
+
+/// Share of all combos played by `probabilities`.
+pub fn range_share(probabilities: &BTreeMap<String, f64>) -> f64 {
+    probabilities
+        .iter()
+        .map(|(name, p)| combo_count(name) * p)
+        .sum::<f64>()
+        / TOTAL_COMBOS
+}
+
+/// Renders `probabilities` as a 13x13 chart, showing the hands played at
+/// least half the time and a dot for the rest.
+pub fn format_chart(probabilities: &BTreeMap<String, f64>) -> String {
+    let names = starting_hand_grid();
+    names
+        .chunks(RANKS.len())
+        .map(|row| {
+            row.iter()
+                .map(|name| {
+                    let played = probabilities.get(name).is_some_and(|&p| p >= 0.5);
+                    format!("{:<4}", if played { name.as_str() } else { "." })
+                })
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Charts per stack depth, saved as JSON by the `push-fold` command.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct PushFoldCharts {
+    pub charts: Vec<PushFoldChart>,
+}
+
+impl PushFoldCharts {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, StrategyFileError> {
+        let json = std::fs::read_to_string(path).map_err(StrategyFileError::Io)?;
+        serde_json::from_str(&json).map_err(StrategyFileError::Parse)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), StrategyFileError> {
+        let json = serde_json::to_string(self).map_err(StrategyFileError::Parse)?;
+        std::fs::write(path, json).map_err(StrategyFileError::Io)
+    }
+
+    /// The chart solved for the stack depth closest to `stack_bb`.
+    pub fn nearest(&self, stack_bb: f64) -> Option<&PushFoldChart> {
+        self.charts.iter().min_by(|a, b| {
+            (a.stack_bb - stack_bb)
+                .abs()
+                .total_cmp(&(b.stack_bb - stack_bb).abs())
+        })
+    }
+}
+
+/// Plays the push/fold charts preflop while the effective stack is shorter
+/// than `below_bb` big blinds, and leaves every other decision to `inner`.
+pub struct PushFoldAgent {
+    inner: Box<dyn PokerAgent>,
+    charts: PushFoldCharts,
+    below_bb: f64,
+    /// Set when the last decision came from a chart.
+    last: Option<Explanation>,
+}
+
+impl PushFoldAgent {
+    pub fn new(inner: Box<dyn PokerAgent>, charts: PushFoldCharts, below_bb: f64) -> Self {
+        Self {
+            inner,
+            charts,
+            below_bb,
+            last: None,
+        }
+    }
+
+    /// The chart to play against `opponent`, if the stacks are short enough.
+    fn chart(&self, view: &PlayerView, opponent: SeatIndex) -> Option<&PushFoldChart> {
+        if view.round != PokerRound::PreFlop || view.big_blind == 0 {
+            return None;
+        }
+        let effective = [view.seat, opponent]
+            .map(|seat| view.stacks[seat] + view.bets[seat])
+            .into_iter()
+            .min()
+            .unwrap_or(0);
+        let effective_bb = f64::from(effective) / f64::from(view.big_blind);
+        if effective_bb >= self.below_bb {
+            return None;
+        }
+        self.charts.nearest(effective_bb)
+    }
+
+    /// Pushes or folds as the small blind's first action, and calls or folds
+    /// facing an all-in; `None` in any other spot, including any hand with
+    /// more than one opponent.
+    fn chart_action(
+        &self,
+        view: &PlayerView,
+        rng: &mut dyn RngCore,
+    ) -> Option<(PokerAction, Explanation)> {
+        let opponents: Vec<SeatIndex> = view.opponents().collect();
+        let [opponent] = opponents[..] else {
+            return None;
+        };
+        let chart = self.chart(view, opponent)?;
+        let (probability, action, kind) = if view.history.is_empty() && view.is_dealer() {
+            let push = chart.push_probability(&view.hole_cards);
+            (push, PokerAction::AllIn, "push")
+        } else if view.stacks[opponent] == 0 && view.to_call() > 0 {
+            let call = chart.call_probability(&view.hole_cards);
+            (call, PokerAction::Call, "call")
+        } else {
+            return None;
+        };
+        let probability = probability.clamp(0.0, 1.0);
+        let (action, probability) = if rng.gen_bool(probability) {
+            (action, probability)
+        } else {
+            (PokerAction::Fold, 1.0 - probability)
+        };
+        let explanation = Explanation::new(DecisionBasis::Table {
+            source: format!("{} BB {} chart", chart.stack_bb, kind),
+            probability,
+        });
+        Some((action, explanation))
+    }
+}
+
+impl PokerAgent for PushFoldAgent {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        actions: &[PokerAction],
+        rng: &mut dyn RngCore,
+    ) -> PokerAction {
+        match self.chart_action(view, rng) {
+            Some((action, explanation)) if actions.contains(&action) => {
+                self.last = Some(explanation);
+                action
+            }
+            _ => {
+                self.last = None;
+                self.inner.choose_action(view, actions, rng)
+            }
+        }
+    }
+
+    fn observe_hand_end(&mut self, history: &[ActionRecord], went_to_showdown: bool) {
+        self.inner.observe_hand_end(history, went_to_showdown);
+    }
+
+    fn explain(&self) -> Option<Explanation> {
+        self.last.clone().or_else(|| self.inner.explain())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::ThresholdAgent;
+    use crate::poker_logic::parse_cards;
+    use crate::profile::AiProfile;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::OnceLock;
+
+    fn matrix() -> &'static HandMatrix {
+        static MATRIX: OnceLock<HandMatrix> = OnceLock::new();
+        MATRIX.get_or_init(|| HandMatrix::build(6, &mut StdRng::seed_from_u64(38)))
+    }
+
+    fn hole(cards: &str) -> HoleCards {
+        let cards = parse_cards(cards).unwrap();
+        [cards[0], cards[1]]
+    }
+
+    #[test]
+    fn test_matrix_weights_count_card_removal() {
+        let matrix = matrix();
+        let n = matrix.names.len();
+        let index = |name: &str| matrix.names.iter().position(|h| h == name).unwrap();
+        // AA against AA: one pair of combos leaves the other two aces
+        assert_eq!(matrix.weight[index("AA") * n + index("AA")], 6.0);
+        assert_eq!(matrix.weight[index("AA") * n + index("KK")], 36.0);
+        assert_eq!(matrix.weight[index("AKs") * n + index("AA")], 12.0);
+        let (aa, seven_two) = (index("AA"), index("72o"));
+        assert!(matrix.equity[aa * n + seven_two] > 0.7);
+        assert!(
+            (matrix.equity[aa * n + seven_two] + matrix.equity[seven_two * n + aa] - 1.0).abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_push_ranges_widen_as_stacks_shrink() {
+        let deep = matrix().solve(20.0, 200);
+        let short = matrix().solve(2.0, 200);
+        assert!(range_share(&short.push) > range_share(&deep.push));
+        assert!(range_share(&short.push) > 0.8);
+        assert_eq!(deep.push["AA"], 1.0);
+        assert_eq!(deep.call["AA"], 1.0);
+        assert!(deep.call["72o"] < 0.5);
+        let chart = format_chart(&deep.push);
+        assert_eq!(chart.lines().count(), 13);
+        assert!(chart.starts_with("AA"));
+    }
+
+    fn chart() -> PushFoldCharts {
+        let probabilities = |hands: &[&str]| hands.iter().map(|h| (h.to_string(), 1.0)).collect();
+        PushFoldCharts {
+            charts: vec![PushFoldChart {
+                stack_bb: 10.0,
+                push: probabilities(&["AA", "K9s"]),
+                call: probabilities(&["AA"]),
+            }],
+        }
+    }
+
+    fn view(hole_cards: &str, stacks: Vec<u32>, seat: usize) -> PlayerView {
+        PlayerView {
+            seat,
+            hole_cards: hole(hole_cards),
+            board: Vec::new(),
+            round: PokerRound::PreFlop,
+            hand_number: 1,
+            dealer_position: 0,
+            stacks,
+            bets: vec![25, 50],
+            folded: vec![false; HEADS_UP_SEATS],
+            current_bet: 50,
+            pot: 75,
+            big_blind: 50,
+            bet_amount: 50,
+            raise_amount: 100,
+            history: Vec::new(),
+            payouts: Vec::new(),
+        }
+    }
+
+    const SMALL_BLIND: [PokerAction; 4] = [
+        PokerAction::Call,
+        PokerAction::Raise,
+        PokerAction::AllIn,
+        PokerAction::Fold,
+    ];
+
+    #[test]
+    fn test_agent_switches_to_charts_below_depth() {
+        let inner = Box::new(ThresholdAgent::new(AiProfile::default()));
+        let mut agent = PushFoldAgent::new(inner, chart(), 12.0);
+        let mut rng = StdRng::seed_from_u64(1);
+        let actions = &SMALL_BLIND;
+
+        let short = view("Kh9h", vec![475, 450], 0);
+        assert_eq!(
+            agent.choose_action(&short, actions, &mut rng),
+            PokerAction::AllIn
+        );
+        let trash = view("7c2d", vec![475, 450], 0);
+        assert_eq!(
+            agent.choose_action(&trash, actions, &mut rng),
+            PokerAction::Fold
+        );
+        let deep = view("Kh9h", vec![975, 950], 0);
+        assert_ne!(
+            agent.choose_action(&deep, actions, &mut rng),
+            PokerAction::AllIn
+        );
+
+        let mut facing_shove = view("AsAd", vec![0, 450], 1);
+        facing_shove.bets = vec![500, 50];
+        facing_shove.current_bet = 500;
+        facing_shove.history.push(ActionRecord {
+            seat: 0,
+            round: PokerRound::PreFlop,
+            action: PokerAction::AllIn,
+            amount: 475,
+        });
+        let facing = [PokerAction::Call, PokerAction::Fold];
+        assert_eq!(
+            agent.choose_action(&facing_shove, &facing, &mut rng),
+            PokerAction::Call
+        );
+        facing_shove.hole_cards = hole("Kh9h");
+        assert_eq!(
+            agent.choose_action(&facing_shove, &facing, &mut rng),
+            PokerAction::Fold
+        );
+    }
+}
@@ -0,0 +1,565 @@
+//! Hand ranges in standard notation and equity between ranges.
+//!
+//! A range is a weighted set of two-card combos written as a comma-separated
+//! list, for example `"22+, A2s+, KTo+, QJs, AhKh:0.5"`:
+//!
+//! - `QQ` a pocket pair, `QQ+` that pair and every higher pair, `55-88` a span
+//! - `AKs` suited, `AKo` offsuit, `AK` both
+//! - `A2s+` raises the kicker up to one below the top card (A2s..AKs)
+//! - `K9o-KQo` a span of kickers with the same top card
+//! - `AhKh` one exact combo
+//! - `:weight` after any entry sets how often (0.0-1.0) its combos are held
+//!
+//! When an entry repeats a combo the later weight replaces the earlier one.
+
+use crate::constants::MAX_COMMUNITY_CARDS;
+use crate::equity::{record_showdown, Equity, EquityError, EquityResult};
+use crate::poker_logic::{full_deck, parse_cards, Board, Card, HoleCards, Rank, RANKS, SUITS};
+use rand::{seq::SliceRandom, Rng};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// Number of attempts to draw a conflict-free set of combos for one trial.
+const MAX_DEAL_ATTEMPTS: usize = 100;
+
+/// A two-card combo together with how often it is part of the range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightedCombo {
+    pub cards: HoleCards,
+    pub weight: f64,
+}
+
+/// A weighted set of starting hands.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Range {
+    combos: Vec<WeightedCombo>,
+}
+
+/// Error returned when range notation cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseRangeError {
+    /// The range did not contain any hands.
+    Empty,
+    /// An entry is not valid range notation.
+    InvalidToken(String),
+    /// A weight is not a number between 0 and 1.
+    InvalidWeight(String),
+}
+
+impl fmt::Display for ParseRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseRangeError::Empty => write!(f, "range contains no hands"),
+            ParseRangeError::InvalidToken(token) => write!(f, "invalid range entry '{}'", token),
+            ParseRangeError::InvalidWeight(token) => write!(f, "invalid weight in '{}'", token),
+        }
+    }
+}
+
+impl std::error::Error for ParseRangeError {}
+
+/// Puts the higher card first so each combo has a single representation.
+fn normalize(cards: HoleCards) -> HoleCards {
+    let [a, b] = cards;
+    if (a.rank, a.suit) >= (b.rank, b.suit) {
+        [a, b]
+    } else {
+        [b, a]
+    }
+}
+
+/// Suitedness requested by a range entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Suitedness {
+    Suited,
+    Offsuit,
+    Any,
+}
+
+/// All combos of the given two ranks with the requested suitedness.
+fn rank_combos(high: Rank, low: Rank, suitedness: Suitedness) -> Vec<HoleCards> {
+    let mut combos = Vec::new();
+    for (i, &first_suit) in SUITS.iter().enumerate() {
+        for (j, &second_suit) in SUITS.iter().enumerate() {
+            // Pairs need distinct suits and are only counted once per suit pair
+            if high == low && j <= i {
+                continue;
+            }
+            let suited = first_suit == second_suit;
+            let wanted = match suitedness {
+                Suitedness::Suited => suited,
+                Suitedness::Offsuit => !suited,
+                Suitedness::Any => true,
+            };
+            if wanted {
+                combos.push([Card::new(high, first_suit), Card::new(low, second_suit)]);
+            }
+        }
+    }
+    combos
+}
+
+/// Parses "AK", "AKs", "AKo" or "QQ" into (high, low, suitedness).
+fn parse_hand_class(s: &str) -> Option<(Rank, Rank, Suitedness)> {
+    let chars: Vec<char> = s.chars().collect();
+    let (first, second, suitedness) = match chars.as_slice() {
+        [a, b] => (*a, *b, Suitedness::Any),
+        [a, b, 's' | 'S'] => (*a, *b, Suitedness::Suited),
+        [a, b, 'o' | 'O'] => (*a, *b, Suitedness::Offsuit),
+        _ => return None,
+    };
+    let first = Rank::from_char(first)?;
+    let second = Rank::from_char(second)?;
+    if first == second && suitedness != Suitedness::Any {
+        return None;
+    }
+    Some((first.max(second), first.min(second), suitedness))
+}
+
+/// Ranks from `from` to `to` inclusive, in either order, lowest first.
+fn ranks_between(from: Rank, to: Rank) -> impl Iterator<Item = Rank> {
+    let (lo, hi) = (from.min(to), from.max(to));
+    RANKS.iter().copied().filter(move |&r| r >= lo && r <= hi)
+}
+
+/// Expands one entry (without its weight) into combos.
+fn expand_entry(body: &str) -> Option<Vec<HoleCards>> {
+    if let Some(base) = body.strip_suffix('+') {
+        let (high, low, suitedness) = parse_hand_class(base)?;
+        let combos = if high == low {
+            ranks_between(high, Rank::Ace)
+                .flat_map(|r| rank_combos(r, r, suitedness))
+                .collect()
+        } else {
+            ranks_between(low, high)
+                .filter(|&kicker| kicker < high)
+                .flat_map(|kicker| rank_combos(high, kicker, suitedness))
+                .collect()
+        };
+        return Some(combos);
+    }
+
+    if let Some((from, to)) = body.split_once('-') {
+        let (from_high, from_low, from_suit) = parse_hand_class(from)?;
+        let (to_high, to_low, to_suit) = parse_hand_class(to)?;
+        if from_suit != to_suit {
+            return None;
+        }
+        if from_high == from_low && to_high == to_low {
+            return Some(
+                ranks_between(from_high, to_high)
+                    .flat_map(|r| rank_combos(r, r, from_suit))
+                    .collect(),
+            );
+        }
+        if from_high != to_high || from_high == from_low || to_high == to_low {
+            return None;
+        }
+        return Some(
+            ranks_between(from_low, to_low)
+                .flat_map(|kicker| rank_combos(from_high, kicker, from_suit))
+                .collect(),
+        );
+    }
+
+    if let Some((high, low, suitedness)) = parse_hand_class(body) {
+        return Some(rank_combos(high, low, suitedness));
+    }
+
+    match parse_cards(body).ok()?.as_slice() {
+        [first, second] => Some(vec![[*first, *second]]),
+        _ => None,
+    }
+}
+
+/// Parses the weight part of `token`, which must lie between 0 and 1.
+fn parse_weight(token: &str, weight: &str) -> Result<f64, ParseRangeError> {
+    match weight.trim().parse::<f64>() {
+        Ok(weight) if (0.0..=1.0).contains(&weight) => Ok(weight),
+        _ => Err(ParseRangeError::InvalidWeight(token.to_string())),
+    }
+}
+
+impl FromStr for Range {
+    type Err = ParseRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut range = Range::default();
+        for token in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let (body, weight) = match token.split_once(':') {
+                Some((body, weight)) => (body.trim(), parse_weight(token, weight)?),
+                None => (token, 1.0),
+            };
+            let combos = expand_entry(body)
+                .ok_or_else(|| ParseRangeError::InvalidToken(token.to_string()))?;
+            for cards in combos {
+                range.insert(cards, weight);
+            }
+        }
+
+        if range.combos.is_empty() {
+            return Err(ParseRangeError::Empty);
+        }
+        Ok(range)
+    }
+}
+
+impl Range {
+    /// A range holding exactly one hand.
+    pub fn from_hand(cards: HoleCards) -> Self {
+        let mut range = Range::default();
+        range.insert(cards, 1.0);
+        range
+    }
+
+    /// Adds a combo, replacing the weight if it is already present.
+    pub fn insert(&mut self, cards: HoleCards, weight: f64) {
+        let cards = normalize(cards);
+        match self.combos.iter_mut().find(|c| c.cards == cards) {
+            Some(existing) => existing.weight = weight,
+            None => self.combos.push(WeightedCombo { cards, weight }),
+        }
+    }
+
+    /// Every combo in the range with its weight.
+    pub fn combos(&self) -> &[WeightedCombo] {
+        &self.combos
+    }
+
+    /// Combos that do not use any of `dead` cards and have a positive weight.
+    pub fn combos_without(&self, dead: &[Card]) -> Vec<WeightedCombo> {
+        self.combos
+            .iter()
+            .filter(|c| c.weight > 0.0 && !c.cards.iter().any(|card| dead.contains(card)))
+            .copied()
+            .collect()
+    }
+}
+
+/// Names of the 169 starting hands in chart order: one row per rank from
+/// aces down to deuces, pairs on the diagonal, suited hands to its right and
+/// offsuit hands to its left.
+pub fn starting_hand_grid() -> Vec<String> {
+    let ranks: Vec<Rank> = RANKS.iter().rev().copied().collect();
+    let mut names = Vec::with_capacity(ranks.len() * ranks.len());
+    for (row, &first) in ranks.iter().enumerate() {
+        for (col, &second) in ranks.iter().enumerate() {
+            names.push(match row.cmp(&col) {
+                Ordering::Equal => format!("{0}{0}", first.to_char()),
+                Ordering::Less => format!("{}{}s", first.to_char(), second.to_char()),
+                Ordering::Greater => format!("{}{}o", second.to_char(), first.to_char()),
+            });
+        }
+    }
+    names
+}
+
+/// The starting-hand name of two hole cards, such as "AKs", "QQ" or "72o".
+pub fn starting_hand_name(hole: &HoleCards) -> String {
+    let [high, low] = normalize(*hole);
+    let mut name: String = [high.rank.to_char(), low.rank.to_char()].iter().collect();
+    if high.rank != low.rank {
+        name.push(if high.suit == low.suit { 's' } else { 'o' });
+    }
+    name
+}
+
+/// Combos of one range prepared for weighted sampling.
+struct Sampler {
+    combos: Vec<WeightedCombo>,
+    cumulative: Vec<f64>,
+}
+
+impl Sampler {
+    fn new(combos: Vec<WeightedCombo>) -> Self {
+        let cumulative = combos
+            .iter()
+            .scan(0.0, |total, c| {
+                *total += c.weight;
+                Some(*total)
+            })
+            .collect();
+        Self { combos, cumulative }
+    }
+
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> HoleCards {
+        let total = self.cumulative.last().copied().unwrap_or(0.0);
+        let target = rng.gen::<f64>() * total;
+        let index = self
+            .cumulative
+            .partition_point(|&c| c <= target)
+            .min(self.combos.len() - 1);
+        self.combos[index].cards
+    }
+}
+
+/// Calculates the equity of each range against the others by sampling.
+///
+/// Every trial draws one combo from each range in proportion to its weight,
+/// skipping combos blocked by the board, dead cards or combos already drawn
+/// for another range (card removal), then completes the board at random.
+/// An exact hand can take part as `Range::from_hand`, which gives
+/// range-vs-hand equity.
+pub fn range_equity<R: Rng + ?Sized>(
+    ranges: &[Range],
+    board: &[Card],
+    dead_cards: &[Card],
+    samples: u32,
+    rng: &mut R,
+) -> Result<EquityResult, EquityError> {
+    if ranges.len() < 2 {
+        return Err(EquityError::NotEnoughHands);
+    }
+    let board: Vec<Card> = board
+        .iter()
+        .copied()
+        .filter(|c| !c.is_placeholder)
+        .collect();
+    if board.len() > MAX_COMMUNITY_CARDS {
+        return Err(EquityError::TooManyBoardCards(board.len()));
+    }
+    let mut blocked: Vec<Card> = Vec::with_capacity(board.len() + dead_cards.len());
+    for &card in board.iter().chain(dead_cards) {
+        if blocked.contains(&card) {
+            return Err(EquityError::DuplicateCard(card));
+        }
+        blocked.push(card);
+    }
+
+    let samplers: Vec<Sampler> = ranges
+        .iter()
+        .enumerate()
+        .map(|(i, range)| {
+            let combos = range.combos_without(&blocked);
+            if combos.is_empty() {
+                Err(EquityError::EmptyRange(i))
+            } else {
+                Ok(Sampler::new(combos))
+            }
+        })
+        .collect::<Result<_, _>>()?;
+
+    let deck: Vec<Card> = full_deck()
+        .into_iter()
+        .filter(|c| !blocked.contains(c))
+        .collect();
+    let missing = MAX_COMMUNITY_CARDS - board.len();
+    let mut full_board = Board::default();
+    full_board[..board.len()].copy_from_slice(&board);
+
+    let mut equities = vec![Equity::default(); ranges.len()];
+    let mut trials = 0;
+    let mut hands: Vec<HoleCards> = Vec::with_capacity(ranges.len());
+    for _ in 0..samples {
+        if !deal_hands(&samplers, &mut hands, rng) {
+            continue;
+        }
+        let mut available: Vec<Card> = deck
+            .iter()
+            .copied()
+            .filter(|c| !hands.iter().flatten().any(|h| h == c))
+            .collect();
+        if available.len() < missing {
+            continue;
+        }
+        let (runout, _) = available.partial_shuffle(rng, missing);
+        full_board[board.len()..].copy_from_slice(runout);
+
+        record_showdown(&hands, &full_board, &mut equities);
+        trials += 1;
+    }
+
+    if trials == 0 {
+        return Err(EquityError::NotEnoughCards);
+    }
+    Ok(EquityResult {
+        equities,
+        trials,
+        exhaustive: false,
+    })
+}
+
+/// Draws one non-conflicting combo per range into `hands`.
+fn deal_hands<R: Rng + ?Sized>(
+    samplers: &[Sampler],
+    hands: &mut Vec<HoleCards>,
+    rng: &mut R,
+) -> bool {
+    for _ in 0..MAX_DEAL_ATTEMPTS {
+        hands.clear();
+        for sampler in samplers {
+            let combo = sampler.sample(rng);
+            if hands.iter().flatten().any(|c| combo.contains(c)) {
+                break;
+            }
+            hands.push(combo);
+        }
+        if hands.len() == samplers.len() {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn range(s: &str) -> Range {
+        s.parse().unwrap()
+    }
+
+    fn hole(s: &str) -> HoleCards {
+        let cards = parse_cards(s).unwrap();
+        [cards[0], cards[1]]
+    }
+
+    #[test]
+    fn test_starting_hand_grid_and_names() {
+        let grid = starting_hand_grid();
+        assert_eq!(grid.len(), 169);
+        assert_eq!(&grid[..3], ["AA", "AKs", "AQs"]);
+        assert_eq!(grid[13], "AKo");
+        assert_eq!(grid[168], "22");
+        for name in &grid {
+            let combos = range(name).combos().len();
+            assert_eq!(starting_hand_name(&range(name).combos()[0].cards), *name);
+            assert!(
+                matches!(combos, 4 | 6 | 12),
+                "{} has {} combos",
+                name,
+                combos
+            );
+        }
+        assert_eq!(starting_hand_name(&hole("2d7c")), "72o");
+    }
+
+    #[test]
+    fn test_combo_counts() {
+        assert_eq!(range("QQ").combos().len(), 6);
+        assert_eq!(range("AKs").combos().len(), 4);
+        assert_eq!(range("AKo").combos().len(), 12);
+        assert_eq!(range("AK").combos().len(), 16);
+        assert_eq!(range("AhKh").combos().len(), 1);
+    }
+
+    #[test]
+    fn test_plus_notation() {
+        // 22 through AA
+        assert_eq!(range("22+").combos().len(), 13 * 6);
+        // A2s through AKs
+        assert_eq!(range("A2s+").combos().len(), 12 * 4);
+        // KTo, KJo, KQo
+        assert_eq!(range("KTo+").combos().len(), 3 * 12);
+        assert_eq!(range("QQ+").combos().len(), 18);
+    }
+
+    #[test]
+    fn test_dash_notation() {
+        assert_eq!(range("55-88").combos().len(), 4 * 6);
+        assert_eq!(range("88-55").combos().len(), 4 * 6);
+        assert_eq!(range("K9o-KQo").combos().len(), 4 * 12);
+        assert!("K9o-QJo".parse::<Range>().is_err());
+        assert!("K9s-KQo".parse::<Range>().is_err());
+    }
+
+    #[test]
+    fn test_combined_range_and_overlap() {
+        let r = range("22+, A2s+, KTo+, QJs");
+        assert_eq!(r.combos().len(), 78 + 48 + 36 + 4);
+
+        // Overlapping entries keep one copy of each combo, with the later weight
+        let r = range("AK, AKs:0.25");
+        assert_eq!(r.combos().len(), 16);
+        let suited: Vec<f64> = r
+            .combos()
+            .iter()
+            .filter(|c| c.cards[0].suit == c.cards[1].suit)
+            .map(|c| c.weight)
+            .collect();
+        assert_eq!(suited, vec![0.25; 4]);
+    }
+
+    #[test]
+    fn test_reversed_ranks_are_normalized() {
+        assert_eq!(range("KAs"), range("AKs"));
+        assert_eq!(range("KhAh"), range("AhKh"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<Range>(), Err(ParseRangeError::Empty));
+        assert_eq!(
+            "AKx".parse::<Range>(),
+            Err(ParseRangeError::InvalidToken("AKx".to_string()))
+        );
+        assert_eq!(
+            "QQs".parse::<Range>(),
+            Err(ParseRangeError::InvalidToken("QQs".to_string()))
+        );
+        assert_eq!(
+            "AK:1.5".parse::<Range>(),
+            Err(ParseRangeError::InvalidWeight("AK:1.5".to_string()))
+        );
+        assert_eq!(
+            "AK:abc".parse::<Range>(),
+            Err(ParseRangeError::InvalidWeight("AK:abc".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_card_removal_against_board() {
+        let r = range("AA, KK");
+        let board = parse_cards("Ah Kd Kc").unwrap();
+        // Three aces left make 3 combos, two kings left make 1 combo
+        assert_eq!(r.combos_without(&board).len(), 4);
+    }
+
+    #[test]
+    fn test_range_vs_hand_matches_exact_equity() {
+        let mut rng = StdRng::seed_from_u64(30);
+        let result = range_equity(
+            &[range("AsAd"), Range::from_hand(hole("7h2c"))],
+            &[],
+            &[],
+            3000,
+            &mut rng,
+        )
+        .unwrap();
+        let share = result.equities[0].share();
+        assert!((0.84..0.92).contains(&share), "unexpected equity {}", share);
+    }
+
+    #[test]
+    fn test_range_vs_range_is_reproducible_and_sums_to_one() {
+        let ranges = [range("QQ+, AKs"), range("22-99, ATo+")];
+        let first = range_equity(&ranges, &[], &[], 2000, &mut StdRng::seed_from_u64(1)).unwrap();
+        let second = range_equity(&ranges, &[], &[], 2000, &mut StdRng::seed_from_u64(1)).unwrap();
+
+        assert_eq!(first, second);
+        let total: f64 = first.equities.iter().map(Equity::share).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(first.equities[0].share() > 0.6);
+    }
+
+    #[test]
+    fn test_zero_weight_combos_are_never_dealt() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let result =
+            range_equity(&[range("AA:0, 72o"), range("KK")], &[], &[], 1000, &mut rng).unwrap();
+        // Only 72o can be dealt, which is a big underdog to kings
+        assert!(result.equities[0].share() < 0.2);
+    }
+
+    #[test]
+    fn test_range_blocked_by_board_is_an_error() {
+        let board = parse_cards("As Ad Ah").unwrap();
+        let mut rng = StdRng::seed_from_u64(5);
+        assert_eq!(
+            range_equity(&[range("AhAc"), range("KK")], &board, &[], 10, &mut rng),
+            Err(EquityError::EmptyRange(0))
+        );
+    }
+}
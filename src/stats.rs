@@ -0,0 +1,260 @@
+//! Per-player statistics built from the actions of completed hands.
+//!
+//! The usual HUD numbers are tracked for every seat:
+//!
+//! - VPIP: share of hands where the player put chips in preflop voluntarily
+//! - PFR: share of hands where the player bet or raised preflop
+//! - 3-bet: share of chances to re-raise a single preflop raise that were
+//!   taken. The big blind counts as the first bet, so the first preflop raise
+//!   is the open and a raise over it is the 3-bet.
+//! - AF: post-flop bets and raises divided by post-flop calls
+//! - Fold to c-bet: how often the player folded when the preflop aggressor
+//!   bet the flop into them
+//! - WTSD: share of hands that saw a flop and reached showdown
+
+use crate::game::{ActionRecord, PokerAction};
+use crate::poker_logic::{PokerRound, SeatIndex};
+
+/// Counters for one player. The percentages are `None` until there is at
+/// least one sample.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlayerStats {
+    pub hands: u32,
+    pub vpip_hands: u32,
+    pub pfr_hands: u32,
+    pub three_bet_chances: u32,
+    pub three_bets: u32,
+    pub postflop_aggressive: u32,
+    pub postflop_calls: u32,
+    pub cbets_faced: u32,
+    pub cbets_folded: u32,
+    pub saw_flop: u32,
+    pub showdowns: u32,
+}
+
+fn ratio(count: u32, total: u32) -> Option<f64> {
+    (total > 0).then(|| f64::from(count) / f64::from(total))
+}
+
+impl PlayerStats {
+    pub fn vpip(&self) -> Option<f64> {
+        ratio(self.vpip_hands, self.hands)
+    }
+
+    pub fn pfr(&self) -> Option<f64> {
+        ratio(self.pfr_hands, self.hands)
+    }
+
+    pub fn three_bet(&self) -> Option<f64> {
+        ratio(self.three_bets, self.three_bet_chances)
+    }
+
+    /// Post-flop (bets + raises) / calls; `None` until the player has called.
+    pub fn aggression_factor(&self) -> Option<f64> {
+        ratio(self.postflop_aggressive, self.postflop_calls)
+    }
+
+    pub fn fold_to_cbet(&self) -> Option<f64> {
+        ratio(self.cbets_folded, self.cbets_faced)
+    }
+
+    pub fn went_to_showdown(&self) -> Option<f64> {
+        ratio(self.showdowns, self.saw_flop)
+    }
+}
+
+/// Statistics for every seat at the table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatsTracker {
+    players: Vec<PlayerStats>,
+}
+
+pub(crate) fn is_aggressive(action: PokerAction) -> bool {
+    matches!(
+        action,
+        PokerAction::Bet | PokerAction::Raise | PokerAction::AllIn
+    )
+}
+
+impl StatsTracker {
+    pub fn new(seats: usize) -> Self {
+        Self {
+            players: vec![PlayerStats::default(); seats],
+        }
+    }
+
+    /// Statistics for `seat`.
+    pub fn player(&self, seat: SeatIndex) -> &PlayerStats {
+        &self.players[seat]
+    }
+
+    /// Updates every seat from the action history of a finished hand.
+    /// `went_to_showdown` is false when the hand ended with a fold.
+    pub fn observe_hand(&mut self, history: &[ActionRecord], went_to_showdown: bool) {
+        let preflop: Vec<&ActionRecord> = history
+            .iter()
+            .filter(|r| r.round == PokerRound::PreFlop)
+            .collect();
+        let flop: Vec<&ActionRecord> = history
+            .iter()
+            .filter(|r| r.round == PokerRound::Flop)
+            .collect();
+        let reached_flop = history.iter().any(|r| r.round != PokerRound::PreFlop)
+            || (went_to_showdown && !history.is_empty());
+        let preflop_aggressor = preflop
+            .iter()
+            .rev()
+            .find(|r| is_aggressive(r.action))
+            .map(|r| r.seat);
+        let cbet = preflop_aggressor.and_then(|aggressor| {
+            flop.iter()
+                .position(|r| is_aggressive(r.action))
+                .filter(|&i| flop[i].seat == aggressor)
+        });
+
+        for (seat, stats) in self.players.iter_mut().enumerate() {
+            if !history.iter().any(|r| r.seat == seat) {
+                continue;
+            }
+            let mine = |r: &&&ActionRecord| r.seat == seat;
+            stats.hands += 1;
+            let voluntary =
+                |r: &&ActionRecord| !matches!(r.action, PokerAction::Check | PokerAction::Fold);
+            stats.vpip_hands += u32::from(preflop.iter().filter(mine).any(voluntary));
+            stats.pfr_hands +=
+                u32::from(preflop.iter().filter(mine).any(|r| is_aggressive(r.action)));
+
+            if let Some(took_it) = three_bet_chance(&preflop, seat) {
+                stats.three_bet_chances += 1;
+                stats.three_bets += u32::from(took_it);
+            }
+
+            let postflop = history
+                .iter()
+                .filter(|r| r.seat == seat && r.round != PokerRound::PreFlop);
+            for record in postflop {
+                stats.postflop_aggressive += u32::from(is_aggressive(record.action));
+                stats.postflop_calls += u32::from(record.action == PokerAction::Call);
+            }
+
+            if let Some(folded) = cbet.and_then(|index| cbet_response(&flop, index, seat)) {
+                stats.cbets_faced += 1;
+                stats.cbets_folded += u32::from(folded);
+            }
+
+            let folded = history
+                .iter()
+                .any(|r| r.seat == seat && r.action == PokerAction::Fold);
+            let folded_preflop = preflop
+                .iter()
+                .any(|r| r.seat == seat && r.action == PokerAction::Fold);
+            if reached_flop && !folded_preflop {
+                stats.saw_flop += 1;
+                stats.showdowns += u32::from(went_to_showdown && !folded);
+            }
+        }
+    }
+}
+
+/// Whether `seat` had a 3-bet chance preflop (acting after exactly one bet
+/// or raise) and, if so, whether it raised.
+fn three_bet_chance(preflop: &[&ActionRecord], seat: SeatIndex) -> Option<bool> {
+    let mut raises_so_far = 0;
+    for record in preflop {
+        if record.seat == seat && raises_so_far == 1 {
+            return Some(is_aggressive(record.action));
+        }
+        raises_so_far += u32::from(is_aggressive(record.action));
+    }
+    None
+}
+
+/// Whether `seat` folded to the c-bet at `cbet_index` of the flop actions,
+/// or `None` if it made the c-bet or never responded.
+fn cbet_response(flop: &[&ActionRecord], cbet_index: usize, seat: SeatIndex) -> Option<bool> {
+    if flop[cbet_index].seat == seat {
+        return None;
+    }
+    flop[cbet_index + 1..]
+        .iter()
+        .find(|r| r.seat == seat)
+        .map(|r| r.action == PokerAction::Fold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        apply_action, get_valid_actions, post_blinds, GameConfig, GameStateResource,
+    };
+
+    use PokerAction::*;
+
+    /// Plays `actions` through the engine from the blinds of a heads-up
+    /// hand with seat 0 on the button, and feeds the hand to `tracker`.
+    fn observe(tracker: &mut StatsTracker, actions: &[PokerAction]) {
+        let config = GameConfig::default();
+        let mut game_state = GameStateResource::with_stacks(vec![1000; 2]);
+        post_blinds(&mut game_state, &config);
+        for &action in actions {
+            assert!(get_valid_actions(&game_state, &config).contains(&action));
+            apply_action(&mut game_state, &config, action);
+        }
+        let went_to_showdown =
+            game_state.current_round == PokerRound::Showdown && game_state.winner.is_none();
+        tracker.observe_hand(&game_state.action_history, went_to_showdown);
+    }
+
+    #[test]
+    fn test_preflop_stats() {
+        let mut tracker = StatsTracker::new(2);
+        // P1 opens, P2 3-bets, P1 folds
+        observe(&mut tracker, &[Raise, Raise, Fold]);
+        // P1 limps, P2 checks, then P1 calls a flop bet and folds the turn
+        observe(&mut tracker, &[Call, Check, Bet, Call, Bet, Fold]);
+        // P1 opens, P2 flats, then P2 folds to the c-bet
+        observe(&mut tracker, &[Raise, Call, Check, Bet, Fold]);
+
+        let p1 = tracker.player(0);
+        assert_eq!(p1.hands, 3);
+        assert_eq!(p1.vpip(), Some(1.0));
+        assert_eq!(p1.pfr_hands, 2);
+        assert_eq!(p1.three_bet(), None);
+        assert_eq!(p1.aggression_factor(), Some(1.0));
+        assert_eq!(p1.went_to_showdown(), Some(0.0));
+
+        let p2 = tracker.player(1);
+        assert_eq!((p2.vpip_hands, p2.pfr_hands), (2, 1));
+        assert_eq!(p2.three_bet_chances, 2);
+        assert_eq!(p2.three_bet(), Some(0.5));
+        assert_eq!(p2.aggression_factor(), None);
+        assert_eq!(p2.saw_flop, 2);
+    }
+
+    #[test]
+    fn test_fold_to_cbet_and_showdown() {
+        let mut tracker = StatsTracker::new(2);
+        // P1 opens, P2 calls and checks the flop to the c-bet
+        observe(&mut tracker, &[Raise, Call, Check, Bet, Fold]);
+        observe(
+            &mut tracker,
+            &[Raise, Call, Check, Bet, Call, Check, Check, Check, Check],
+        );
+
+        let p2 = tracker.player(1);
+        assert_eq!(p2.cbets_faced, 2);
+        assert_eq!(p2.fold_to_cbet(), Some(0.5));
+        assert_eq!(p2.went_to_showdown(), Some(0.5));
+        assert_eq!(tracker.player(0).fold_to_cbet(), None);
+        assert_eq!(tracker.player(0).went_to_showdown(), Some(0.5));
+    }
+
+    #[test]
+    fn test_donk_bet_is_not_a_cbet() {
+        let mut tracker = StatsTracker::new(2);
+        // P2 leads into the preflop raiser
+        observe(&mut tracker, &[Raise, Call, Bet, Fold]);
+        assert_eq!(tracker.player(1).cbets_faced, 0);
+        assert_eq!(tracker.player(0).cbets_faced, 0);
+    }
+}
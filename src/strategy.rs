@@ -0,0 +1,340 @@
+//! Mixed-strategy adjustments shared by the built-in agents.
+//!
+//! An agent first picks its straightforward action, then [`mix_action`]
+//! randomizes it the way a thinking player would: strong hands are sometimes
+//! slow-played or checked with the plan to check-raise, draws are sometimes
+//! bet as semi-bluffs, and weak hands bluff at a frequency derived from the
+//! bet size. All randomness comes from the RNG the engine passes in, so a
+//! seeded game replays exactly.
+
+use crate::game::{PlayerView, PokerAction};
+use crate::poker_logic::has_draw;
+use crate::profile::AiProfile;
+use rand::{Rng, RngCore};
+use std::fmt;
+
+/// How an agent rates its hand before mixing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandClass {
+    /// Not worth a value bet.
+    Weak,
+    /// Good enough to bet or call for value.
+    Made,
+    /// Strong enough to give the opponent a free card.
+    Monster,
+}
+
+/// Share of a betting range that should be bluffs so a call of `bet` into
+/// `pot` breaks even: bet / (pot + 2 * bet).
+pub fn balanced_bluff_share(pot: u32, bet: u32) -> f64 {
+    if bet == 0 {
+        return 0.0;
+    }
+    f64::from(bet) / (f64::from(pot) + 2.0 * f64::from(bet))
+}
+
+/// Why [`mix_action`] replaced the straightforward action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MixReason {
+    /// Checked a monster planning to raise a bet.
+    CheckRaise,
+    /// Flat-called or checked a monster.
+    SlowPlay,
+    /// Bet or raised a draw.
+    SemiBluff,
+    /// Bet a weak hand.
+    Bluff,
+}
+
+impl fmt::Display for MixReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::CheckRaise => "check-raise",
+            Self::SlowPlay => "slow-play",
+            Self::SemiBluff => "semi-bluff",
+            Self::Bluff => "bluff",
+        };
+        f.write_str(text)
+    }
+}
+
+/// The action [`mix_action`] settled on and, when it differs from the
+/// base action, the reason.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MixedAction {
+    pub action: PokerAction,
+    pub reason: Option<MixReason>,
+}
+
+/// Randomizes `base` according to the profile's mixing frequencies.
+///
+/// Only ever returns a member of `actions`.
+pub fn mix_action(
+    base: PokerAction,
+    class: HandClass,
+    view: &PlayerView,
+    actions: &[PokerAction],
+    profile: &AiProfile,
+    rng: &mut dyn RngCore,
+) -> MixedAction {
+    let available = |action: PokerAction| actions.contains(&action);
+    let aggressive = [PokerAction::Bet, PokerAction::Raise]
+        .into_iter()
+        .find(|&action| available(action));
+    let is_aggressive = matches!(base, PokerAction::Bet | PokerAction::Raise);
+    let postflop = !view.board.is_empty();
+    let mixed = |action: PokerAction, reason: MixReason| MixedAction {
+        action,
+        reason: Some(reason),
+    };
+    let unchanged = MixedAction {
+        action: base,
+        reason: None,
+    };
+
+    match class {
+        HandClass::Monster if postflop && is_aggressive => {
+            let unopened = view.to_call() == 0;
+            if unopened
+                && !view.is_dealer()
+                && rng.gen_bool(profile.check_raise_frequency.clamp(0.0, 1.0))
+            {
+                return mixed(PokerAction::Check, MixReason::CheckRaise);
+            }
+            if rng.gen_bool(profile.slow_play_frequency.clamp(0.0, 1.0)) {
+                let flat = if unopened {
+                    PokerAction::Check
+                } else {
+                    PokerAction::Call
+                };
+                if available(flat) {
+                    return mixed(flat, MixReason::SlowPlay);
+                }
+            }
+        }
+        HandClass::Weak | HandClass::Made if !is_aggressive => {
+            let Some(aggressive) = aggressive else {
+                return unchanged;
+            };
+            let drawing =
+                matches!(view.board.len(), 3 | 4) && has_draw(&view.hole_cards, &view.board);
+            if drawing && rng.gen_bool(profile.semi_bluff_frequency.clamp(0.0, 1.0)) {
+                return mixed(aggressive, MixReason::SemiBluff);
+            }
+            if class == HandClass::Weak && view.to_call() == 0 {
+                let bluff_share = balanced_bluff_share(view.pot, view.bet_amount);
+                let bluff = (profile.bluff_frequency * bluff_share).clamp(0.0, 1.0);
+                if rng.gen_bool(bluff) {
+                    return mixed(aggressive, MixReason::Bluff);
+                }
+            }
+        }
+        _ => {}
+    }
+    unchanged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::HEADS_UP_SEATS;
+    use crate::poker_logic::{parse_cards, PokerRound};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const UNOPENED: [PokerAction; 3] = [PokerAction::Check, PokerAction::Bet, PokerAction::Fold];
+    const FACING_BET: [PokerAction; 3] = [PokerAction::Call, PokerAction::Raise, PokerAction::Fold];
+
+    fn view(hole: &str, board: &str, to_call: u32, seat: usize) -> PlayerView {
+        let hole = parse_cards(hole).unwrap();
+        let mut bets = vec![0; HEADS_UP_SEATS];
+        bets[(seat + 1) % HEADS_UP_SEATS] = to_call;
+        PlayerView {
+            seat,
+            hole_cards: [hole[0], hole[1]],
+            board: parse_cards(board).unwrap(),
+            round: PokerRound::Flop,
+            hand_number: 1,
+            dealer_position: 0,
+            stacks: vec![1000; HEADS_UP_SEATS],
+            bets,
+            folded: vec![false; HEADS_UP_SEATS],
+            current_bet: to_call,
+            pot: 100,
+            big_blind: 50,
+            bet_amount: 50,
+            raise_amount: 100,
+            history: Vec::new(),
+            payouts: Vec::new(),
+        }
+    }
+
+    fn profile(f: impl FnOnce(&mut AiProfile)) -> AiProfile {
+        let mut profile = AiProfile {
+            bluff_frequency: 0.0,
+            semi_bluff_frequency: 0.0,
+            slow_play_frequency: 0.0,
+            check_raise_frequency: 0.0,
+            ..AiProfile::default()
+        };
+        f(&mut profile);
+        profile
+    }
+
+    /// How often `mix_action` turns `base` into `wanted` over many trials.
+    fn frequency(
+        base: PokerAction,
+        class: HandClass,
+        view: &PlayerView,
+        actions: &[PokerAction],
+        profile: &AiProfile,
+        wanted: PokerAction,
+    ) -> f64 {
+        let mut rng = StdRng::seed_from_u64(35);
+        let trials = 4000;
+        let hits = (0..trials)
+            .filter(|_| mix_action(base, class, view, actions, profile, &mut rng).action == wanted)
+            .count();
+        hits as f64 / f64::from(trials)
+    }
+
+    #[test]
+    fn test_pure_strategy_when_frequencies_are_zero() {
+        let profile = profile(|_| {});
+        let mut rng = StdRng::seed_from_u64(1);
+        let weak = view("7c2d", "Ks Qh 9d", 0, 1);
+        let monster = view("KcKd", "Ks Qh 9d", 50, 1);
+        for _ in 0..100 {
+            let mixed = mix_action(
+                PokerAction::Check,
+                HandClass::Weak,
+                &weak,
+                &UNOPENED,
+                &profile,
+                &mut rng,
+            );
+            assert_eq!(mixed.action, PokerAction::Check);
+            assert_eq!(mixed.reason, None);
+            let mixed = mix_action(
+                PokerAction::Raise,
+                HandClass::Monster,
+                &monster,
+                &FACING_BET,
+                &profile,
+                &mut rng,
+            );
+            assert_eq!(mixed.action, PokerAction::Raise);
+        }
+    }
+
+    #[test]
+    fn test_bluffs_at_balanced_frequency() {
+        let profile = profile(|p| p.bluff_frequency = 1.0);
+        let weak = view("7c2d", "Ks Qh 9d", 0, 1);
+        let expected = balanced_bluff_share(100, 50);
+        assert!((expected - 0.25).abs() < 1e-9);
+        let seen = frequency(
+            PokerAction::Check,
+            HandClass::Weak,
+            &weak,
+            &UNOPENED,
+            &profile,
+            PokerAction::Bet,
+        );
+        assert!((seen - expected).abs() < 0.03, "bluffed {}", seen);
+    }
+
+    #[test]
+    fn test_semi_bluffs_only_with_draws() {
+        let profile = profile(|p| p.semi_bluff_frequency = 1.0);
+        let draw = view("Ah2h", "Kh 7h 9c", 50, 1);
+        let air = view("7c2d", "Ks Qh 9d", 50, 1);
+        let raise_draw = frequency(
+            PokerAction::Fold,
+            HandClass::Weak,
+            &draw,
+            &FACING_BET,
+            &profile,
+            PokerAction::Raise,
+        );
+        let raise_air = frequency(
+            PokerAction::Fold,
+            HandClass::Weak,
+            &air,
+            &FACING_BET,
+            &profile,
+            PokerAction::Raise,
+        );
+        assert_eq!(raise_draw, 1.0);
+        assert_eq!(raise_air, 0.0);
+        let mixed = mix_action(
+            PokerAction::Fold,
+            HandClass::Weak,
+            &draw,
+            &FACING_BET,
+            &profile,
+            &mut StdRng::seed_from_u64(1),
+        );
+        assert_eq!(mixed.reason, Some(MixReason::SemiBluff));
+    }
+
+    #[test]
+    fn test_monsters_slow_play_and_check_raise() {
+        let slow = profile(|p| p.slow_play_frequency = 1.0);
+        let facing = view("KcKd", "Ks Qh 9d", 50, 1);
+        let called = frequency(
+            PokerAction::Raise,
+            HandClass::Monster,
+            &facing,
+            &FACING_BET,
+            &slow,
+            PokerAction::Call,
+        );
+        assert_eq!(called, 1.0);
+
+        let check_raise = profile(|p| p.check_raise_frequency = 0.5);
+        let out_of_position = view("KcKd", "Ks Qh 9d", 0, 1);
+        let in_position = view("KcKd", "Ks Qh 9d", 0, 0);
+        let checked = frequency(
+            PokerAction::Bet,
+            HandClass::Monster,
+            &out_of_position,
+            &UNOPENED,
+            &check_raise,
+            PokerAction::Check,
+        );
+        assert!((checked - 0.5).abs() < 0.03, "checked {}", checked);
+        let checked = frequency(
+            PokerAction::Bet,
+            HandClass::Monster,
+            &in_position,
+            &UNOPENED,
+            &check_raise,
+            PokerAction::Check,
+        );
+        assert_eq!(checked, 0.0);
+    }
+
+    #[test]
+    fn test_seeded_mixing_is_reproducible() {
+        let profile = AiProfile::default();
+        let draw = view("Ah2h", "Kh 7h 9c", 0, 1);
+        let run = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..200)
+                .map(|_| {
+                    mix_action(
+                        PokerAction::Check,
+                        HandClass::Weak,
+                        &draw,
+                        &UNOPENED,
+                        &profile,
+                        &mut rng,
+                    )
+                    .action
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(7), run(7));
+    }
+}
@@ -0,0 +1,486 @@
+//! Sit-and-go tournaments.
+//!
+//! Every player starts with the same stack and plays until one of them has
+//! all the chips. The blinds and antes rise on a [`BlindSchedule`], either
+//! every so many hands or every so many seconds of play, and players are
+//! knocked out when they run out of chips. A [`Tournament`] tracks the
+//! level and the eliminations around the ordinary hand loop, so the same
+//! bookkeeping drives the game window and [`simulate`], which plays a whole
+//! tournament headless. Prizes are paid from a payout table, and the
+//! stacks in play are valued with the Independent Chip Model.
+
+use crate::agent::SeatAgents;
+use crate::constants::*;
+use crate::game::{
+    begin_hand, perform_validated_action, process_showdown_result, GameConfig, GameStateResource,
+};
+use crate::icm::icm_equity;
+use crate::poker_logic::{Deck, PokerRound, SeatIndex};
+use bevy::prelude::*;
+use rand::RngCore;
+
+/// The forced bets of one level of the schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlindLevel {
+    pub small_blind: u32,
+    pub big_blind: u32,
+    pub ante: u32,
+}
+
+/// What makes the blinds go up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LevelClock {
+    /// A new level every this many hands
+    Hands(u32),
+    /// A new level every this many seconds of play
+    Seconds(f32),
+}
+
+impl LevelClock {
+    /// Parses `hands:N` or `minutes:M`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let invalid = || format!("expected hands:N or minutes:M, got '{}'", s);
+        let (unit, amount) = s.split_once(':').ok_or_else(invalid)?;
+        match unit {
+            "hands" => match amount.parse() {
+                Ok(hands) if hands > 0 => Ok(Self::Hands(hands)),
+                _ => Err(invalid()),
+            },
+            "minutes" => match amount.parse::<f32>() {
+                Ok(minutes) if minutes > 0.0 => Ok(Self::Seconds(minutes * 60.0)),
+                _ => Err(invalid()),
+            },
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// The blind levels in order and how long each one lasts. The last level
+/// stays in play once it is reached.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlindSchedule {
+    pub levels: Vec<BlindLevel>,
+    pub clock: LevelClock,
+}
+
+impl BlindSchedule {
+    /// The standard levels from `TOURNAMENT_BLIND_LEVELS`.
+    pub fn standard(clock: LevelClock) -> Self {
+        Self {
+            levels: TOURNAMENT_BLIND_LEVELS
+                .iter()
+                .map(|&(small_blind, big_blind, ante)| BlindLevel {
+                    small_blind,
+                    big_blind,
+                    ante,
+                })
+                .collect(),
+            clock,
+        }
+    }
+
+    /// The level in play after `hands_played` hands and `elapsed` seconds.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn level_index(&self, hands_played: u32, elapsed: f32) -> usize {
+        let passed = match self.clock {
+            LevelClock::Hands(hands) => (hands_played / hands.max(1)) as usize,
+            LevelClock::Seconds(seconds) => (elapsed.max(0.0) / seconds) as usize,
+        };
+        passed.min(self.levels.len().saturating_sub(1))
+    }
+
+    /// When the level after `index` starts, e.g. "in 4 hands" or "in 2:30",
+    /// or `None` at the last level.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn next_level(&self, index: usize, hands_played: u32, elapsed: f32) -> Option<String> {
+        if index + 1 >= self.levels.len() {
+            return None;
+        }
+        // Safe: the schedule has a handful of levels
+        let next = index as u32 + 1;
+        Some(match self.clock {
+            LevelClock::Hands(hands) => {
+                format!("in {} hands", (next * hands).saturating_sub(hands_played))
+            }
+            LevelClock::Seconds(seconds) => {
+                let left = (next as f32 * seconds - elapsed).max(0.0).ceil() as u32;
+                format!("in {}:{:02}", left / 60, left % 60)
+            }
+        })
+    }
+}
+
+/// "1st", "2nd", "3rd", "4th" and so on.
+pub fn ordinal(place: usize) -> String {
+    let suffix = match (place % 10, place % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", place, suffix)
+}
+
+/// A sit-and-go in progress: the blind level, who has been knocked out and
+/// in what order.
+#[derive(Resource, Debug, Clone)]
+pub struct Tournament {
+    pub schedule: BlindSchedule,
+    /// Share of the prize pool paid for each place from first down
+    pub payouts: Vec<f64>,
+    level: usize,
+    /// Seconds of play when the first hand began
+    started_at: Option<f32>,
+    /// Stacks as the current hand began, to order players busting in it
+    stacks_at_start: Vec<u32>,
+    /// Seats knocked out, in the order they went
+    eliminated: Vec<SeatIndex>,
+    finished: bool,
+}
+
+impl Tournament {
+    /// A winner-takes-all tournament on `schedule`.
+    pub fn new(schedule: BlindSchedule) -> Self {
+        Self {
+            schedule,
+            payouts: vec![1.0],
+            level: 0,
+            started_at: None,
+            stacks_at_start: Vec::new(),
+            eliminated: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// Pays out `payouts` instead, e.g. from
+    /// [`PayoutStructure::payouts`](crate::icm::PayoutStructure::payouts).
+    pub fn with_payouts(mut self, payouts: Vec<f64>) -> Self {
+        self.payouts = payouts;
+        self
+    }
+
+    /// The current level's index and blinds.
+    pub fn level(&self) -> (usize, BlindLevel) {
+        (self.level, self.schedule.levels[self.level])
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Sets the blinds for the hand about to be dealt, `now` seconds into
+    /// play, and returns whether a new level has started. The bet and raise
+    /// sizes grow with the big blind, and the payouts are passed on for the
+    /// AI to weigh its chips by.
+    pub fn begin_hand(
+        &mut self,
+        game_state: &GameStateResource,
+        config: &mut GameConfig,
+        now: f32,
+    ) -> bool {
+        let started_at = *self.started_at.get_or_insert(now);
+        let hands_played = u32::try_from(game_state.hand_number.max(0)).unwrap_or(0);
+        let level = self.schedule.level_index(hands_played, now - started_at);
+        let changed = level != self.level;
+        self.level = level;
+        self.stacks_at_start.clone_from(&game_state.player_chips);
+
+        let blinds = self.schedule.levels[level];
+        config.small_blind = blinds.small_blind;
+        config.big_blind = blinds.big_blind;
+        config.ante = blinds.ante;
+        config.bet_amount = blinds.big_blind;
+        config.raise_amount = blinds.big_blind * 2;
+        config.payouts.clone_from(&self.payouts);
+        changed
+    }
+
+    /// Knocks out every player the finished hand left without chips and
+    /// returns them; of two players busting in the same hand, the one who
+    /// began it with more chips finishes higher. Ends the tournament once
+    /// one player is left.
+    pub fn end_hand(&mut self, game_state: &GameStateResource) -> Vec<SeatIndex> {
+        let mut busted: Vec<SeatIndex> = (0..game_state.seat_count())
+            .filter(|&seat| game_state.player_chips[seat] == 0)
+            .filter(|seat| !self.eliminated.contains(seat))
+            .collect();
+        busted.sort_by_key(|&seat| self.stacks_at_start.get(seat).copied().unwrap_or(0));
+        self.eliminated.extend(&busted);
+        self.finished = self.eliminated.len() + 1 >= game_state.seat_count();
+        busted
+    }
+
+    /// Seats from first place to last: players still in by chip count,
+    /// then the eliminated in reverse order of going out.
+    pub fn standings(&self, game_state: &GameStateResource) -> Vec<SeatIndex> {
+        let mut alive: Vec<SeatIndex> = (0..game_state.seat_count())
+            .filter(|seat| !self.eliminated.contains(seat))
+            .collect();
+        alive.sort_by_key(|&seat| std::cmp::Reverse(game_state.player_chips[seat]));
+        alive.extend(self.eliminated.iter().rev());
+        alive
+    }
+
+    /// The place `seat` finished in, once it has been knocked out.
+    pub fn finishing_place(&self, seat: SeatIndex, seats: usize) -> Option<usize> {
+        let out = self.eliminated.iter().position(|&s| s == seat)?;
+        Some(seats - out)
+    }
+
+    /// The prize a place pays, as a share of the pool.
+    fn prize(&self, place: usize) -> f64 {
+        self.payouts.get(place - 1).copied().unwrap_or(0.0)
+    }
+
+    /// Each seat's expected share of the prize pool: the prize for the
+    /// place they finished in once knocked out, the ICM value of their
+    /// stack while still in.
+    pub fn prize_equity(&self, game_state: &GameStateResource) -> Vec<f64> {
+        let seats = game_state.seat_count();
+        let alive: Vec<SeatIndex> = (0..seats)
+            .filter(|seat| !self.eliminated.contains(seat))
+            .collect();
+        let stacks: Vec<u32> = alive
+            .iter()
+            .map(|&seat| game_state.player_chips[seat])
+            .collect();
+        let mut equity = vec![0.0; seats];
+        for (&seat, value) in alive.iter().zip(icm_equity(&stacks, &self.payouts)) {
+            equity[seat] = value;
+        }
+        for &seat in &self.eliminated {
+            if let Some(place) = self.finishing_place(seat, seats) {
+                equity[seat] = self.prize(place);
+            }
+        }
+        equity
+    }
+
+    /// The ICM value of each stack still in, e.g. "ICM  P1 41.2%  P3 25.0%".
+    pub fn equity_summary(&self, game_state: &GameStateResource) -> String {
+        let equity = self.prize_equity(game_state);
+        let seats: Vec<String> = (0..game_state.seat_count())
+            .filter(|seat| !self.eliminated.contains(seat))
+            .map(|seat| format!("P{} {:.1}%", seat + 1, equity[seat] * 100.0))
+            .collect();
+        format!("ICM  {}", seats.join("  "))
+    }
+
+    /// The current level and when the next one starts, for the table HUD.
+    pub fn level_summary(&self, hand_number: i32, now: f32) -> String {
+        let (index, blinds) = self.level();
+        let mut summary = format!(
+            "Level {}: {}/{}",
+            index + 1,
+            blinds.small_blind,
+            blinds.big_blind
+        );
+        if blinds.ante > 0 {
+            summary.push_str(&format!(" ante {}", blinds.ante));
+        }
+        let hands_played = u32::try_from(hand_number.max(1) - 1).unwrap_or(0);
+        let elapsed = now - self.started_at.unwrap_or(now);
+        if let Some(next) = self.schedule.next_level(index, hands_played, elapsed) {
+            summary.push_str(&format!("  next {}", next));
+        }
+        summary
+    }
+
+    /// The finishing order, one line per place.
+    pub fn results(&self, game_state: &GameStateResource) -> String {
+        let lines: Vec<String> = self
+            .standings(game_state)
+            .iter()
+            .enumerate()
+            .map(|(place, seat)| {
+                let prize = self.prize(place + 1);
+                if prize > 0.0 {
+                    format!(
+                        "{}  P{}  {:.0}%",
+                        ordinal(place + 1),
+                        seat + 1,
+                        prize * 100.0
+                    )
+                } else {
+                    format!("{}  P{}", ordinal(place + 1), seat + 1)
+                }
+            })
+            .collect();
+        format!("Tournament over\n\n{}", lines.join("\n"))
+    }
+}
+
+/// Plays a whole tournament without a window and returns the seats in
+/// finishing order. `config` gives the starting stack and receives each
+/// level's blinds; a timed schedule counts every hand as
+/// `TOURNAMENT_SIM_SECONDS_PER_HAND` seconds. `on_event` hears about new
+/// levels and eliminations.
+#[allow(clippy::cast_precision_loss)]
+pub fn simulate(
+    config: &mut GameConfig,
+    tournament: &mut Tournament,
+    agents: &mut SeatAgents,
+    rng: &mut dyn RngCore,
+    mut on_event: impl FnMut(&str),
+) -> Vec<SeatIndex> {
+    let seats = config.seats.len();
+    let mut game_state = GameStateResource {
+        deck: Deck::with_rng(rng),
+        ..GameStateResource::with_stacks(vec![config.starting_chips; seats])
+    };
+    while !tournament.is_finished() {
+        let now = game_state.hand_number as f32 * TOURNAMENT_SIM_SECONDS_PER_HAND;
+        if tournament.begin_hand(&game_state, config, now) {
+            let summary = tournament.level_summary(game_state.hand_number + 1, now);
+            on_event(&format!("Hand {}: {}", game_state.hand_number + 1, summary));
+        }
+        begin_hand(&mut game_state, config, rng);
+        while game_state.current_round != PokerRound::Showdown {
+            let seat = game_state.current_player;
+            perform_validated_action(&mut game_state, config, agents.seat_mut(seat), rng);
+        }
+
+        // A winner is already set when the hand ended with a fold
+        let went_to_showdown = game_state.winner.is_none();
+        if went_to_showdown {
+            process_showdown_result(&mut game_state);
+        }
+        agents.observe_hand_end(&game_state.action_history, went_to_showdown);
+        for seat in tournament.end_hand(&game_state) {
+            let place = tournament.finishing_place(seat, seats).unwrap_or(seats);
+            on_event(&format!(
+                "Hand {}: P{} is out in {} place",
+                game_state.hand_number,
+                seat + 1,
+                ordinal(place)
+            ));
+        }
+    }
+    tournament.standings(&game_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::AiConfig;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn schedule(clock: LevelClock) -> BlindSchedule {
+        BlindSchedule::standard(clock)
+    }
+
+    #[test]
+    fn test_levels_rise_by_hand_count_or_elapsed_time() {
+        let by_hands = schedule(LevelClock::Hands(10));
+        assert_eq!(by_hands.level_index(0, 0.0), 0);
+        assert_eq!(by_hands.level_index(9, 1e6), 0);
+        assert_eq!(by_hands.level_index(10, 0.0), 1);
+        assert_eq!(by_hands.level_index(10_000, 0.0), by_hands.levels.len() - 1);
+
+        let by_time = schedule(LevelClock::Seconds(300.0));
+        assert_eq!(by_time.level_index(500, 299.0), 0);
+        assert_eq!(by_time.level_index(0, 600.0), 2);
+        assert_eq!(by_time.next_level(2, 0, 630.0).as_deref(), Some("in 4:30"));
+    }
+
+    #[test]
+    fn test_level_clock_parses_hands_and_minutes() {
+        assert_eq!(LevelClock::parse("hands:8"), Ok(LevelClock::Hands(8)));
+        assert_eq!(
+            LevelClock::parse("minutes:2"),
+            Ok(LevelClock::Seconds(120.0))
+        );
+        assert!(LevelClock::parse("hands:0").is_err());
+        assert!(LevelClock::parse("laps:3").is_err());
+    }
+
+    #[test]
+    fn test_new_levels_set_the_blinds_and_antes() {
+        let mut tournament = Tournament::new(schedule(LevelClock::Hands(10)));
+        let mut config = GameConfig::default();
+        let mut game_state = GameStateResource::with_stacks(vec![1500; 3]);
+
+        assert!(!tournament.begin_hand(&game_state, &mut config, 0.0));
+        assert_eq!(
+            (config.small_blind, config.big_blind, config.ante),
+            (10, 20, 0)
+        );
+        game_state.hand_number = 30;
+        assert!(tournament.begin_hand(&game_state, &mut config, 0.0));
+        assert_eq!(
+            (config.small_blind, config.big_blind, config.ante),
+            (50, 100, 10)
+        );
+        assert_eq!(config.raise_amount, 200);
+        assert!(tournament
+            .level_summary(31, 0.0)
+            .starts_with("Level 4: 50/100 ante 10"));
+    }
+
+    #[test]
+    fn test_players_busting_together_are_placed_by_starting_stack() {
+        let mut tournament = Tournament::new(schedule(LevelClock::Hands(10)));
+        let mut config = GameConfig::default();
+        let mut game_state = GameStateResource::with_stacks(vec![300, 800, 1000, 900]);
+        tournament.begin_hand(&game_state, &mut config, 0.0);
+        game_state.player_chips = vec![0, 0, 3000, 0];
+
+        assert_eq!(tournament.end_hand(&game_state), [0, 1, 3]);
+        assert!(tournament.is_finished());
+        assert_eq!(tournament.standings(&game_state), [2, 3, 1, 0]);
+        assert_eq!(tournament.finishing_place(3, 4), Some(2));
+        assert_eq!(ordinal(2), "2nd");
+        assert_eq!(ordinal(11), "11th");
+    }
+
+    #[test]
+    fn test_prize_equity_values_stacks_in_play_by_icm() {
+        let mut tournament =
+            Tournament::new(schedule(LevelClock::Hands(10))).with_payouts(vec![0.5, 0.3, 0.2]);
+        let mut config = GameConfig::default();
+        let mut game_state = GameStateResource::with_stacks(vec![2500; 4]);
+        tournament.begin_hand(&game_state, &mut config, 0.0);
+        assert_eq!(config.payouts, [0.5, 0.3, 0.2]);
+        game_state.player_chips = vec![5000, 0, 3000, 2000];
+        tournament.end_hand(&game_state);
+
+        let equity = tournament.prize_equity(&game_state);
+        assert_eq!(equity[1], 0.0);
+        assert!((equity[0] - 0.38393).abs() < 1e-4);
+        assert!((equity.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(
+            tournament.equity_summary(&game_state),
+            "ICM  P1 38.4%  P3 32.8%  P4 28.9%"
+        );
+        assert!(tournament.results(&game_state).contains("1st  P1  50%"));
+    }
+
+    #[test]
+    fn test_a_headless_tournament_plays_down_to_one_player() {
+        let mut config = GameConfig::with_seat_count(4);
+        config.starting_chips = 500;
+        let mut tournament = Tournament::new(schedule(LevelClock::Hands(3)));
+        let mut agents = SeatAgents::from_config(&config, &AiConfig::built_in());
+        let mut rng = StdRng::seed_from_u64(48);
+        let mut events = Vec::new();
+
+        let standings = simulate(
+            &mut config,
+            &mut tournament,
+            &mut agents,
+            &mut rng,
+            |event| events.push(event.to_string()),
+        );
+
+        let mut seats = standings.clone();
+        seats.sort_unstable();
+        assert_eq!(seats, [0, 1, 2, 3]);
+        assert_eq!(
+            events.iter().filter(|e| e.contains(" is out in ")).count(),
+            3
+        );
+        assert!(events.iter().any(|e| e.contains("Level 2")));
+    }
+}
@@ -0,0 +1,365 @@
+//! Evolves the threshold bot's parameters through headless self-play.
+//!
+//! [`Tuner`] runs a small evolution strategy. Each generation every
+//! candidate profile plays duplicate matches in the [`Arena`] against a
+//! fixed pool of baseline profiles and is scored by its mean win rate. The
+//! best candidates survive unchanged and the rest of the population is bred
+//! from them by uniform crossover and Gaussian mutation, with the mutation
+//! shrinking as the run goes on. All candidates of a generation face the
+//! same deals, so they are compared on equal cards.
+
+use crate::agent::{PokerAgent, ThresholdAgent};
+use crate::arena::Arena;
+use crate::constants::*;
+use crate::game::GameConfig;
+use crate::profile::AiProfile;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, SeedableRng};
+
+/// A tuned field of [`AiProfile`] and the range it is searched in.
+pub struct Parameter {
+    pub name: &'static str,
+    field: fn(&mut AiProfile) -> &mut f32,
+    min: f32,
+    max: f32,
+}
+
+impl Parameter {
+    pub fn get(&self, profile: &AiProfile) -> f32 {
+        *(self.field)(&mut profile.clone())
+    }
+}
+
+/// Every parameter the tuner evolves, in genome order.
+pub const PARAMETERS: [Parameter; 11] = [
+    Parameter {
+        name: "fold_threshold",
+        field: |p| &mut p.fold_threshold,
+        min: 0.0,
+        max: 1.0,
+    },
+    Parameter {
+        name: "marginal_threshold",
+        field: |p| &mut p.marginal_threshold,
+        min: 0.0,
+        max: 1.0,
+    },
+    Parameter {
+        name: "call_threshold",
+        field: |p| &mut p.call_threshold,
+        min: 0.0,
+        max: 1.0,
+    },
+    Parameter {
+        name: "raise_threshold",
+        field: |p| &mut p.raise_threshold,
+        min: 0.0,
+        max: 1.0,
+    },
+    Parameter {
+        name: "bad_odds_fold_threshold",
+        field: |p| &mut p.bad_odds_fold_threshold,
+        min: 0.0,
+        max: 1.0,
+    },
+    Parameter {
+        name: "pot_odds_good",
+        field: |p| &mut p.pot_odds_good,
+        min: 0.0,
+        max: 0.5,
+    },
+    Parameter {
+        name: "pot_odds_call",
+        field: |p| &mut p.pot_odds_call,
+        min: 0.0,
+        max: 0.5,
+    },
+    Parameter {
+        name: "pot_odds_bad",
+        field: |p| &mut p.pot_odds_bad,
+        min: 0.0,
+        max: 0.5,
+    },
+    Parameter {
+        name: "position_bonus",
+        field: |p| &mut p.position_bonus,
+        min: -0.1,
+        max: 0.3,
+    },
+    Parameter {
+        name: "preflop_dealer_bonus",
+        field: |p| &mut p.preflop_dealer_bonus,
+        min: -0.2,
+        max: 0.2,
+    },
+    Parameter {
+        name: "preflop_non_dealer_penalty",
+        field: |p| &mut p.preflop_non_dealer_penalty,
+        min: -0.2,
+        max: 0.2,
+    },
+];
+
+/// Genome positions of the strength thresholds, weakest first.
+const STRENGTH_ORDER: [usize; 4] = [0, 1, 2, 3];
+/// Genome positions of the pot odds limits, most demanding first.
+const POT_ODDS_ORDER: [usize; 3] = [5, 6, 7];
+
+type Genome = Vec<f32>;
+
+/// Clamps every gene to its range and restores the ordering the threshold
+/// rules assume: fold < marginal < call < raise, and good < call < bad
+/// pot odds.
+fn repair(genome: &mut Genome) {
+    for (gene, parameter) in genome.iter_mut().zip(&PARAMETERS) {
+        *gene = gene.clamp(parameter.min, parameter.max);
+    }
+    for order in [&STRENGTH_ORDER[..], &POT_ODDS_ORDER[..]] {
+        let mut values: Vec<f32> = order.iter().map(|&i| genome[i]).collect();
+        values.sort_by(f32::total_cmp);
+        for (&i, value) in order.iter().zip(values) {
+            genome[i] = value;
+        }
+    }
+}
+
+/// A standard normal sample (Box-Muller).
+fn gaussian(rng: &mut dyn RngCore) -> f64 {
+    let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
+/// How long and how widely the tuner searches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TunerSettings {
+    pub generations: u32,
+    pub population: usize,
+    /// Candidates kept unchanged for the next generation.
+    pub elites: usize,
+    /// Duplicate deal pairs per match against each baseline.
+    pub deals: u32,
+    /// Initial mutation, as a share of each parameter's range.
+    pub mutation: f64,
+}
+
+impl Default for TunerSettings {
+    fn default() -> Self {
+        Self {
+            generations: TUNE_GENERATIONS,
+            population: TUNE_POPULATION,
+            elites: TUNE_ELITES,
+            deals: TUNE_DEALS,
+            mutation: TUNE_MUTATION,
+        }
+    }
+}
+
+/// Progress after one generation has been scored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generation {
+    pub index: u32,
+    /// Best and mean fitness, in milli-big-blinds per hand.
+    pub best: f64,
+    pub mean: f64,
+}
+
+/// Searches for the threshold parameters that beat a pool of baselines.
+pub struct Tuner {
+    base: AiProfile,
+    baselines: Vec<AiProfile>,
+    settings: TunerSettings,
+}
+
+impl Tuner {
+    /// Tunes `base`; every field that is not a [`PARAMETERS`] entry, such as
+    /// the mixing frequencies, is kept as it is.
+    pub fn new(base: AiProfile, baselines: Vec<AiProfile>, settings: TunerSettings) -> Self {
+        Self {
+            base,
+            baselines,
+            settings,
+        }
+    }
+
+    fn genome(&self, profile: &AiProfile) -> Genome {
+        PARAMETERS.iter().map(|p| p.get(profile)).collect()
+    }
+
+    fn profile(&self, genome: &Genome) -> AiProfile {
+        let mut profile = self.base.clone();
+        for (&gene, parameter) in genome.iter().zip(&PARAMETERS) {
+            *(parameter.field)(&mut profile) = gene;
+        }
+        profile
+    }
+
+    /// Mean win rate of `profile` against the baselines, in milli-big-blinds
+    /// per hand, on the deals drawn from `seed`.
+    pub fn fitness(&self, profile: &AiProfile, seed: u64) -> f64 {
+        let config = GameConfig::default();
+        let big_blind = config.big_blind;
+        let mut arena = Arena::new(config);
+        for entry in std::iter::once(profile).chain(&self.baselines) {
+            let entry = entry.clone();
+            arena.register(
+                entry.name.clone(),
+                Box::new(move || {
+                    Box::new(ThresholdAgent::new(entry.clone())) as Box<dyn PokerAgent>
+                }),
+            );
+        }
+        if self.baselines.is_empty() {
+            return 0.0;
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let total: f64 = (1..=self.baselines.len())
+            .map(|baseline| {
+                let result = arena.play_match(0, baseline, self.settings.deals, &mut rng);
+                result.mbb_per_hand(big_blind).0
+            })
+            .sum();
+        total / self.baselines.len() as f64
+    }
+
+    /// A child of two parents: each gene from either parent, then mutated by
+    /// `sigma` of its range.
+    fn breed(&self, a: &Genome, b: &Genome, sigma: f64, rng: &mut dyn RngCore) -> Genome {
+        let mut child: Genome = a
+            .iter()
+            .zip(b)
+            .zip(&PARAMETERS)
+            .map(|((&x, &y), parameter)| {
+                let gene = if rng.gen_bool(0.5) { x } else { y };
+                let spread = f64::from(parameter.max - parameter.min);
+                gene + (gaussian(rng) * sigma * spread) as f32
+            })
+            .collect();
+        repair(&mut child);
+        child
+    }
+
+    /// Runs the search, calling `on_generation` after each generation, and
+    /// returns the best profile of the last generation with its fitness.
+    pub fn run(
+        &self,
+        rng: &mut dyn RngCore,
+        mut on_generation: impl FnMut(&Generation),
+    ) -> (AiProfile, f64) {
+        let settings = self.settings;
+        let population = settings.population.max(2);
+        let elites = settings.elites.clamp(1, population);
+        let start = self.genome(&self.base);
+        let mut genomes = vec![start.clone()];
+        while genomes.len() < population {
+            genomes.push(self.breed(&start, &start, settings.mutation, rng));
+        }
+
+        let mut best = (self.base.clone(), f64::NEG_INFINITY);
+        for index in 0..settings.generations {
+            let seed = rng.next_u64();
+            let mut scored: Vec<(f64, Genome)> = genomes
+                .drain(..)
+                .map(|genome| (self.fitness(&self.profile(&genome), seed), genome))
+                .collect();
+            scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+            let mean = scored.iter().map(|(score, _)| score).sum::<f64>() / scored.len() as f64;
+            best = (self.profile(&scored[0].1), scored[0].0);
+            on_generation(&Generation {
+                index,
+                best: best.1,
+                mean,
+            });
+
+            let remaining = 1.0 - f64::from(index + 1) / f64::from(settings.generations);
+            let sigma = settings.mutation * remaining.max(TUNE_MIN_MUTATION_SHARE);
+            let parents: Vec<Genome> = scored
+                .into_iter()
+                .take(elites)
+                .map(|(_, genome)| genome)
+                .collect();
+            genomes = parents.clone();
+            while genomes.len() < population {
+                let a = parents.choose(rng).expect("at least one elite");
+                let b = parents.choose(rng).expect("at least one elite");
+                genomes.push(self.breed(a, b, sigma, rng));
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> TunerSettings {
+        TunerSettings {
+            generations: 2,
+            population: 4,
+            elites: 2,
+            deals: 8,
+            mutation: 0.2,
+        }
+    }
+
+    fn tuner() -> Tuner {
+        let baselines = vec![
+            AiProfile::default(),
+            AiProfile {
+                name: "Loose".to_string(),
+                fold_threshold: 0.1,
+                ..AiProfile::default()
+            },
+        ];
+        let base = AiProfile {
+            name: "Tuned".to_string(),
+            ..AiProfile::default()
+        };
+        Tuner::new(base, baselines, settings())
+    }
+
+    #[test]
+    fn test_genome_round_trips_through_the_profile() {
+        let tuner = tuner();
+        let genome = tuner.genome(&tuner.base);
+        assert_eq!(genome[0], AI_STRENGTH_FOLD_THRESHOLD);
+        assert_eq!(genome[1], AI_STRENGTH_MARGINAL_THRESHOLD);
+        assert_eq!(genome[4], AI_STRENGTH_BAD_ODDS_FOLD_THRESHOLD);
+        assert_eq!(tuner.profile(&genome), tuner.base);
+    }
+
+    #[test]
+    fn test_repair_clamps_and_orders_thresholds() {
+        let mut genome: Genome = vec![0.9, 0.2, 1.4, 0.5, -0.3, 0.4, 0.1, 0.2, 0.0, 0.0, 0.0];
+        repair(&mut genome);
+        assert_eq!(&genome[..5], &[0.2, 0.5, 0.9, 1.0, 0.0]);
+        assert_eq!(&genome[5..8], &[0.1, 0.2, 0.4]);
+    }
+
+    #[test]
+    fn test_fitness_is_reproducible_and_zero_sum_in_self_play() {
+        let tuner = tuner();
+        let profile = AiProfile::default();
+        assert_eq!(tuner.fitness(&profile, 7), tuner.fitness(&profile, 7));
+        let mirror = Tuner::new(profile.clone(), vec![profile.clone()], settings());
+        assert_eq!(mirror.fitness(&profile, 7), 0.0);
+    }
+
+    #[test]
+    fn test_run_reports_every_generation_and_keeps_the_name() {
+        let tuner = tuner();
+        let mut generations = Vec::new();
+        let (best, fitness) = tuner.run(&mut StdRng::seed_from_u64(41), |g| {
+            generations.push(g.clone())
+        });
+        assert_eq!(generations.len(), 2);
+        assert!(generations.iter().all(|g| g.best >= g.mean));
+        assert_eq!(fitness, generations[1].best);
+        assert_eq!(best.name, "Tuned");
+        assert!(best.fold_threshold <= best.marginal_threshold);
+        assert!(best.call_threshold <= best.raise_threshold);
+        assert_eq!(best.bluff_frequency, tuner.base.bluff_frequency);
+    }
+}
@@ -1,7 +1,13 @@
+use crate::agent::SeatAgents;
+use crate::cash::CashGame;
 use crate::constants::*;
+use crate::equity::equity;
 use crate::game::*;
-use crate::poker_logic::{Card, PokerRound};
+use crate::poker_logic::{Card, HoleCards, PokerRound, SeatIndex};
+use crate::profile::AiConfig;
+use crate::tournament::Tournament;
 use bevy::prelude::*;
+use bevy::sprite::Anchor;
 
 /// Spawns the table background with two layers of green felt.
 pub fn spawn_table(
@@ -43,36 +49,52 @@ pub fn spawn_table(
     ));
 }
 
-/// Spawns a player's hole cards and labels.
+/// Size of each seat's cards and labels: full size heads-up, smaller once
+/// more seats share the table.
+fn seat_scale(seats: usize) -> f32 {
+    if seats > HEADS_UP_SEATS {
+        MULTIWAY_SEAT_SCALE
+    } else {
+        1.0
+    }
+}
+
+/// Labels over each seat. Heads-up, a viewer sees "YOU" and "OPP"; at a
+/// bigger table, or for a spectator, seats go by number.
+pub fn seat_labels(seats: usize, viewer: Option<SeatIndex>) -> Vec<String> {
+    (0..seats)
+        .map(|seat| match viewer {
+            Some(viewer) if viewer == seat => "YOU".to_string(),
+            Some(_) if seats == HEADS_UP_SEATS => "OPP".to_string(),
+            _ => format!("P{}", seat + 1),
+        })
+        .collect()
+}
+
+/// Spawns a player's hole cards and labels. A seat that sat down busted
+/// gets its labels but no cards.
 #[allow(clippy::cast_precision_loss)]
 pub fn spawn_player(
     commands: &mut Commands,
-    game_state: &mut GameStateResource,
+    game_state: &GameStateResource,
     config: &GameConfig,
     colors: &ColorPalette,
     id: usize,
+    label: &str,
     x_pos: f32,
     y_pos: f32,
     animation_start_time: f32,
 ) {
-    let card_target_y = y_pos + config.card_target_y_offset;
-
-    for j in 0..2 {
-        let card_offset = (j as f32 - PLAYER_CARD_CENTER_OFFSET) * config.card_offset_spacing;
+    let seats = game_state.seat_count();
+    let scale = seat_scale(seats);
+    let card_target_y = y_pos + config.card_target_y_offset * scale;
+    let dealt = !game_state.folded[id];
+
+    for j in (0..2).filter(|_| dealt) {
+        let card_offset =
+            (j as f32 - PLAYER_CARD_CENTER_OFFSET) * config.card_offset_spacing * scale;
         let target_pos = Vec3::new(x_pos + card_offset, card_target_y, 1.0);
-        let card = match draw_card(game_state) {
-            Ok(c) => c,
-            Err(e) => {
-                error!("Critical: Failed to draw card: {}", e);
-                continue;
-            }
-        };
-
-        if id == 0 {
-            game_state.p1_hole[j] = card;
-        } else {
-            game_state.p2_hole[j] = card;
-        }
+        let card = game_state.hole_cards[id][j];
 
         let text_color = if card.is_red() {
             colors.card_text_red
@@ -83,8 +105,15 @@ pub fn spawn_player(
         commands.spawn((
             SpriteBundle {
                 sprite: Sprite {
-                    color: colors.face_up_white,
-                    custom_size: Some(Vec2::new(config.card_width, config.card_height)),
+                    color: if card.is_placeholder {
+                        colors.face_down_dark
+                    } else {
+                        colors.face_up_white
+                    },
+                    custom_size: Some(Vec2::new(
+                        config.card_width * scale,
+                        config.card_height * scale,
+                    )),
                     ..default()
                 },
                 transform: Transform::from_xyz(0.0, config.animation_start_y, CARD_Z_POSITION),
@@ -96,37 +125,40 @@ pub fn spawn_player(
                 target_pos,
                 start_time: animation_start_time,
                 duration: config.animations.deal_duration,
-                delay: (id * 2 + j) as f32 * config.animations.card_deal_delay,
+                delay: (j * seats + id) as f32 * config.animations.card_deal_delay,
             },
             HandMarker,
         ));
 
-        spawn_card_text(
-            commands,
-            card,
-            target_pos,
-            text_color,
-            HOLE_CARD_FONT_SIZE,
-            config,
-        );
+        // A card the viewer may not see stays face down
+        if !card.is_placeholder {
+            spawn_card_text(
+                commands,
+                card,
+                target_pos,
+                text_color,
+                HOLE_CARD_FONT_SIZE * scale,
+                config,
+                scale,
+            );
+        }
     }
 
-    let player_label = if id == 0 { "YOU" } else { "OPP" };
-    let chip_y_offset = config.ui_positions.chip_label_offset;
+    let chip_y_offset = config.ui_positions.chip_label_offset * scale;
 
     commands.spawn((
         Text2dBundle {
             text: Text::from_section(
-                player_label,
+                label,
                 TextStyle {
-                    font_size: PLAYER_LABEL_FONT_SIZE,
+                    font_size: PLAYER_LABEL_FONT_SIZE * scale,
                     color: Color::WHITE,
                     ..default()
                 },
             ),
             transform: Transform::from_xyz(
                 x_pos,
-                y_pos + config.ui_positions.player_label_offset,
+                y_pos + config.ui_positions.player_label_offset * scale,
                 UI_TEXT_Z_POSITION,
             ),
             ..default()
@@ -140,7 +172,7 @@ pub fn spawn_player(
             text: Text::from_section(
                 chip_text,
                 TextStyle {
-                    font_size: CHIP_LABEL_FONT_SIZE,
+                    font_size: CHIP_LABEL_FONT_SIZE * scale,
                     color: colors.chip_gold,
                     ..default()
                 },
@@ -148,33 +180,25 @@ pub fn spawn_player(
             transform: Transform::from_xyz(x_pos, y_pos + chip_y_offset, UI_TEXT_Z_POSITION),
             ..default()
         },
+        SeatChipsDisplay { seat: id },
         HandMarker,
     ));
 }
 
-/// Spawns a community card with face-down animation.
+/// Spawns a community card with face-down animation. A hidden card shows
+/// no face until its street is reached.
 #[allow(clippy::cast_precision_loss)]
 pub fn spawn_community_card(
     commands: &mut Commands,
-    game_state: &mut GameStateResource,
+    game_state: &GameStateResource,
     config: &GameConfig,
     colors: &ColorPalette,
     i: usize,
+    is_hidden: bool,
     animation_start_time: f32,
 ) {
     let x_offset = (i as f32 - COMMUNITY_CARD_CENTER_INDEX) * config.card_offset_spacing;
-    let community_card = match draw_card(game_state) {
-        Ok(c) => c,
-        Err(e) => {
-            error!("Critical: Failed to draw community card: {}", e);
-            return;
-        }
-    };
-
-    game_state.community_cards[i] = community_card;
-
-    let is_hidden = matches!(i, 3 | 4);
-
+    let community_card = game_state.community_cards[i];
     let target_pos = Vec3::new(x_offset, 0.0, CARD_TARGET_Z);
 
     commands.spawn((
@@ -231,6 +255,7 @@ pub fn spawn_community_card(
             text_color,
             COMMUNITY_CARD_FONT_SIZE,
             config,
+            1.0,
         );
     }
 }
@@ -266,55 +291,44 @@ pub fn spawn_ui(
     commands.spawn((
         Text2dBundle {
             text: Text::from_section(
-                format!("Hand: #{}", game_state.hand_number),
+                side_pots_text(game_state),
                 TextStyle {
-                    font_size: HAND_NUMBER_FONT_SIZE,
-                    color: colors.text_gray_dim,
+                    font_size: SIDE_POTS_FONT_SIZE,
+                    color: colors.chip_gold,
                     ..default()
                 },
             ),
+            // More pots stack upwards, away from the pot total
+            text_anchor: Anchor::BottomCenter,
             transform: Transform::from_xyz(
-                config.ui_positions.hand_number_x,
-                config.ui_positions.hand_number_y,
+                0.0,
+                config.ui_positions.side_pots_display_y,
                 UI_TEXT_Z_POSITION,
             ),
             ..default()
         },
-        HandNumberDisplay,
+        SidePotsDisplay,
         HandMarker,
     ));
 
     commands.spawn((
         Text2dBundle {
             text: Text::from_section(
-                format!("Chips: ${}", game_state.player_chips[0]),
+                format!("Hand: #{}", game_state.hand_number),
                 TextStyle {
-                    font_size: PLAYER_CHIPS_FONT_SIZE,
-                    color: colors.text_gray_light,
+                    font_size: HAND_NUMBER_FONT_SIZE,
+                    color: colors.text_gray_dim,
                     ..default()
                 },
             ),
-            transform: Transform::from_xyz(0.0, PLAYER_CHIPS_Y, UI_TEXT_Z_POSITION),
-            ..default()
-        },
-        PlayerChipsDisplay,
-        HandMarker,
-    ));
-
-    commands.spawn((
-        Text2dBundle {
-            text: Text::from_section(
-                format!("P2: ${}", game_state.player_chips[1]),
-                TextStyle {
-                    font_size: OPPONENT_CHIPS_FONT_SIZE,
-                    color: colors.text_gray_med,
-                    ..default()
-                },
+            transform: Transform::from_xyz(
+                config.ui_positions.hand_number_x,
+                config.ui_positions.hand_number_y,
+                UI_TEXT_Z_POSITION,
             ),
-            transform: Transform::from_xyz(0.0, OPPONENT_CHIPS_Y, UI_TEXT_Z_POSITION),
             ..default()
         },
-        OpponentChipsDisplay,
+        HandNumberDisplay,
         HandMarker,
     ));
 
@@ -359,45 +373,79 @@ pub fn spawn_ui(
         ActionDisplay,
         HandMarker,
     ));
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                String::new(),
+                TextStyle {
+                    font_size: EQUITY_FONT_SIZE,
+                    color: colors.text_gray_light,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_xyz(
+                0.0,
+                config.ui_positions.equity_display_y,
+                UI_TEXT_Z_POSITION,
+            ),
+            ..default()
+        },
+        EquityDisplay,
+        HandMarker,
+    ));
 }
 
-/// Spawns both players with their hole cards and labels.
+/// Where each seat sits: on an ellipse around the table, seat 0 at the top
+/// and the rest clockwise, so heads-up the seats face each other top and
+/// bottom.
+#[allow(clippy::cast_precision_loss)]
+pub fn seat_positions(seats: usize, config: &GameConfig) -> Vec<Vec2> {
+    let player_y_top = config.screen_height * PLAYER_Y_TOP_RATIO;
+    let player_y_bottom = config.screen_height * PLAYER_Y_BOTTOM_RATIO;
+    let center_y = (player_y_top + player_y_bottom) / 2.0;
+    let radius = Vec2::new(
+        config.screen_width * SEAT_X_RADIUS_RATIO,
+        (player_y_top - player_y_bottom) / 2.0,
+    );
+    (0..seats)
+        .map(|seat| {
+            let angle =
+                std::f32::consts::FRAC_PI_2 - seat as f32 * std::f32::consts::TAU / seats as f32;
+            Vec2::new(radius.x * angle.cos(), center_y + radius.y * angle.sin())
+        })
+        .collect()
+}
+
+/// Spawns every seat with its hole cards and the given labels.
 pub fn spawn_all_players(
     commands: &mut Commands,
-    game_state: &mut GameStateResource,
+    game_state: &GameStateResource,
     config: &GameConfig,
     colors: ColorPalette,
+    labels: &[String],
     animation_start_time: f32,
 ) {
-    let player_y_top = config.screen_height * PLAYER_Y_TOP_RATIO;
-    let player_y_bottom = config.screen_height * PLAYER_Y_BOTTOM_RATIO;
-
-    spawn_player(
-        commands,
-        game_state,
-        config,
-        &colors,
-        0,
-        0.0,
-        player_y_top,
-        animation_start_time,
-    );
-    spawn_player(
-        commands,
-        game_state,
-        config,
-        &colors,
-        1,
-        0.0,
-        player_y_bottom,
-        animation_start_time,
-    );
+    let positions = seat_positions(game_state.seat_count(), config);
+    for (seat, position) in positions.into_iter().enumerate() {
+        spawn_player(
+            commands,
+            game_state,
+            config,
+            &colors,
+            seat,
+            &labels[seat],
+            position.x,
+            position.y,
+            animation_start_time,
+        );
+    }
 }
 
 /// Spawns all 5 community cards in their initial face-down positions.
 pub fn spawn_all_community_cards(
     commands: &mut Commands,
-    game_state: &mut GameStateResource,
+    game_state: &GameStateResource,
     config: &GameConfig,
     colors: &ColorPalette,
     animation_start_time: f32,
@@ -409,6 +457,7 @@ pub fn spawn_all_community_cards(
             config,
             colors,
             i,
+            matches!(i, 3 | 4),
             animation_start_time,
         );
     }
@@ -470,7 +519,8 @@ impl CardTextParams {
     }
 }
 
-/// Spawns the text elements for a card (rank and suit).
+/// Spawns the text elements for a card (rank and suit) on a card drawn at
+/// `scale` times its normal size.
 pub fn spawn_card_text(
     commands: &mut Commands,
     card: Card,
@@ -478,12 +528,13 @@ pub fn spawn_card_text(
     text_color: Color,
     font_size: f32,
     config: &GameConfig,
+    scale: f32,
 ) {
     CardTextParams::new(
         card,
         target_pos,
-        -config.card_width / 2.0 + CARD_TEXT_TOP_OFFSET_X,
-        config.card_height / 2.0 + CARD_TEXT_TOP_OFFSET_Y,
+        (-config.card_width / 2.0 + CARD_TEXT_TOP_OFFSET_X) * scale,
+        (config.card_height / 2.0 + CARD_TEXT_TOP_OFFSET_Y) * scale,
         0.0,
         text_color,
         font_size,
@@ -493,8 +544,8 @@ pub fn spawn_card_text(
     CardTextParams::new(
         card,
         target_pos,
-        config.card_width / 2.0 + CARD_TEXT_BOTTOM_OFFSET_X,
-        -config.card_height / 2.0 + CARD_TEXT_BOTTOM_OFFSET_Y,
+        (config.card_width / 2.0 + CARD_TEXT_BOTTOM_OFFSET_X) * scale,
+        (-config.card_height / 2.0 + CARD_TEXT_BOTTOM_OFFSET_Y) * scale,
         std::f32::consts::PI,
         text_color,
         font_size,
@@ -502,13 +553,31 @@ pub fn spawn_card_text(
     .spawn(commands);
 }
 
+/// Each pot and its size, or nothing while there is only the main pot.
+fn side_pots_text(game_state: &GameStateResource) -> String {
+    let pots = game_state.pots();
+    if pots.len() < 2 {
+        return String::new();
+    }
+    let pots: Vec<String> = pots
+        .iter()
+        .enumerate()
+        .map(|(index, pot)| format!("{} ${}", pot_name(index), pot.amount))
+        .collect();
+    let lines: Vec<String> = pots
+        .chunks(SIDE_POTS_PER_LINE)
+        .map(|line| line.join("  "))
+        .collect();
+    lines.join("\n")
+}
+
 /// Updates UI text elements to reflect current game state.
 pub fn update_ui(
     game_state: Res<GameStateResource>,
     mut pot_query: Query<&mut Text, With<PotDisplay>>,
+    mut side_pots_query: Query<&mut Text, With<SidePotsDisplay>>,
     mut hand_number_query: Query<&mut Text, With<HandNumberDisplay>>,
-    mut player_chips_query: Query<&mut Text, With<PlayerChipsDisplay>>,
-    mut opponent_chips_query: Query<&mut Text, With<OpponentChipsDisplay>>,
+    mut seat_chips_query: Query<(&mut Text, &SeatChipsDisplay)>,
     mut round_query: Query<&mut Text, With<RoundDisplay>>,
     mut action_query: Query<&mut Text, With<ActionDisplay>>,
 ) {
@@ -516,16 +585,18 @@ pub fn update_ui(
         text.sections[0].value = format!("Pot: ${}", game_state.pot);
     }
 
-    for mut text in hand_number_query.iter_mut() {
-        text.sections[0].value = format!("Hand: #{}", game_state.hand_number);
+    for mut text in side_pots_query.iter_mut() {
+        text.sections[0].value = side_pots_text(&game_state);
     }
 
-    for mut text in player_chips_query.iter_mut() {
-        text.sections[0].value = format!("Chips: ${}", game_state.player_chips[0]);
+    for mut text in hand_number_query.iter_mut() {
+        text.sections[0].value = format!("Hand: #{}", game_state.hand_number);
     }
 
-    for mut text in opponent_chips_query.iter_mut() {
-        text.sections[0].value = format!("P2: ${}", game_state.player_chips[1]);
+    // A client has no seats until the first table state arrives
+    let chips = |seat: SeatIndex| game_state.player_chips.get(seat).copied().unwrap_or(0);
+    for (mut text, display) in seat_chips_query.iter_mut() {
+        text.sections[0].value = format!("${}", chips(display.seat));
     }
 
     for mut text in round_query.iter_mut() {
@@ -569,3 +640,336 @@ pub fn update_card_visuals(
         }
     }
 }
+
+/// Updates the equity overlay with each player's chance of winning.
+///
+/// Equity is only recalculated when a new hand starts or a street is dealt;
+/// in between the cached text is reused so the per-frame cost stays negligible.
+pub fn update_equity_display(
+    game_state: Res<GameStateResource>,
+    mut cache: Local<Option<(i32, PokerRound, String)>>,
+    mut query: Query<&mut Text, With<EquityDisplay>>,
+) {
+    let key = (game_state.hand_number, game_state.current_round);
+    let is_stale = cache
+        .as_ref()
+        .is_none_or(|(hand, round, _)| (*hand, *round) != key);
+    if is_stale {
+        let seats: Vec<SeatIndex> = game_state.seats_in_hand().collect();
+        let hands: Vec<HoleCards> = seats
+            .iter()
+            .map(|&seat| game_state.hole_cards[seat])
+            .collect();
+        let text = match equity(&hands, visible_community_cards(&game_state), &[]) {
+            Ok(result) => {
+                let shares: Vec<String> = seats
+                    .iter()
+                    .zip(&result.equities)
+                    .map(|(seat, share)| format!("P{} {:.1}%", seat + 1, share.share() * 100.0))
+                    .collect();
+                let lines: Vec<String> = shares
+                    .chunks(EQUITY_SEATS_PER_LINE)
+                    .map(|line| line.join("  "))
+                    .collect();
+                format!("Equity  {}", lines.join("\n"))
+            }
+            Err(_) => String::new(),
+        };
+        *cache = Some((key.0, key.1, text));
+    }
+
+    if let Some((_, _, text)) = cache.as_ref() {
+        for mut display in query.iter_mut() {
+            display.sections[0].value.clone_from(text);
+        }
+    }
+}
+
+/// Spawns the tournament level and results text when a tournament is
+/// being played. It lives across hands.
+pub fn spawn_tournament_display(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    colors: Res<ColorPalette>,
+    tournament: Option<Res<Tournament>>,
+) {
+    if tournament.is_none() {
+        return;
+    }
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                String::new(),
+                TextStyle {
+                    font_size: TOURNAMENT_FONT_SIZE,
+                    color: colors.chip_gold,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_xyz(
+                0.0,
+                config.ui_positions.tournament_display_y,
+                UI_TEXT_Z_POSITION,
+            ),
+            ..default()
+        },
+        TournamentDisplay,
+    ));
+}
+
+/// Shows the blind level and the ICM value of each stack while the
+/// tournament runs, and the finishing order with the prizes in the middle
+/// of the cleared table once it is over.
+pub fn update_tournament_display(
+    tournament: Option<Res<Tournament>>,
+    game_state: Res<GameStateResource>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+    mut query: Query<(&mut Text, &mut Transform), With<TournamentDisplay>>,
+) {
+    let Some(tournament) = tournament else {
+        return;
+    };
+    for (mut text, mut transform) in query.iter_mut() {
+        if tournament.is_finished() {
+            text.sections[0].value = tournament.results(&game_state);
+            text.sections[0].style.font_size = TOURNAMENT_RESULTS_FONT_SIZE;
+            transform.translation.y = 0.0;
+        } else {
+            text.sections[0].value = format!(
+                "{}\n{}",
+                tournament.level_summary(game_state.hand_number, time.elapsed_seconds()),
+                tournament.equity_summary(&game_state)
+            );
+            transform.translation.y = config.ui_positions.tournament_display_y;
+        }
+    }
+}
+
+/// Spawns the session profit and loss text when a cash game is being
+/// played. It lives across hands.
+pub fn spawn_session_display(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    colors: Res<ColorPalette>,
+    cash: Option<Res<CashGame>>,
+) {
+    if cash.is_none() {
+        return;
+    }
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                String::new(),
+                TextStyle {
+                    font_size: CASH_FONT_SIZE,
+                    color: colors.chip_gold,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_xyz(
+                0.0,
+                config.ui_positions.session_display_y,
+                UI_TEXT_Z_POSITION,
+            ),
+            ..default()
+        },
+        SessionDisplay,
+    ));
+}
+
+/// Shows each player's profit while the cash game runs, and the bankrolls
+/// in the middle of the cleared table once it closes.
+pub fn update_session_display(
+    cash: Option<Res<CashGame>>,
+    game_state: Res<GameStateResource>,
+    config: Res<GameConfig>,
+    mut query: Query<(&mut Text, &mut Transform), With<SessionDisplay>>,
+) {
+    let Some(cash) = cash else {
+        return;
+    };
+    for (mut text, mut transform) in query.iter_mut() {
+        if cash.is_closed() {
+            text.sections[0].value = cash.results(&game_state);
+            text.sections[0].style.font_size = CASH_RESULTS_FONT_SIZE;
+            transform.translation.y = 0.0;
+        } else {
+            text.sections[0].value = cash.session_summary(&game_state);
+            transform.translation.y = config.ui_positions.session_display_y;
+        }
+    }
+}
+
+/// Spawns the AI settings menu text. It lives across hands, so it is not
+/// tagged with `HandMarker`.
+pub fn spawn_settings_menu(mut commands: Commands, colors: Res<ColorPalette>) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                String::new(),
+                TextStyle {
+                    font_size: SETTINGS_FONT_SIZE,
+                    color: colors.text_gray_light,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_xyz(0.0, SETTINGS_MENU_Y, SETTINGS_MENU_Z_POSITION),
+            ..default()
+        },
+        SettingsMenuDisplay,
+    ));
+}
+
+/// Handles the keyboard for the AI settings menu: S opens and closes it,
+/// Up/Down select a row and Left/Right cycle the value. The affected seat's
+/// agent is rebuilt immediately.
+pub fn handle_settings_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut menu: ResMut<SettingsMenu>,
+    mut config: ResMut<GameConfig>,
+    ai_config: Res<AiConfig>,
+    mut agents: ResMut<SeatAgents>,
+) {
+    if keys.just_pressed(KeyCode::KeyS) {
+        menu.open = !menu.open;
+    }
+    if !menu.open {
+        return;
+    }
+
+    let rows = config.seats.len() * 2;
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        menu.selected_row = (menu.selected_row + 1) % rows;
+    }
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        menu.selected_row = (menu.selected_row + rows - 1) % rows;
+    }
+
+    let step = if keys.just_pressed(KeyCode::ArrowRight) {
+        1
+    } else if keys.just_pressed(KeyCode::ArrowLeft) {
+        -1
+    } else {
+        return;
+    };
+    let seat = menu.selected_row / 2;
+    let setup = &mut config.seats[seat];
+    match menu.selected_row % 2 {
+        0 => setup.profile = ai_config.cycle_profile(&setup.profile, step).to_string(),
+        _ => {
+            setup.difficulty = ai_config
+                .cycle_difficulty(&setup.difficulty, step)
+                .to_string();
+        }
+    }
+    agents.set(seat, SeatAgents::build_seat(&config, &ai_config, seat));
+}
+
+/// Renders the AI settings menu, or a one-line hint while it is closed.
+pub fn update_settings_menu(
+    menu: Res<SettingsMenu>,
+    config: Res<GameConfig>,
+    mut query: Query<&mut Text, With<SettingsMenuDisplay>>,
+) {
+    if !menu.is_changed() && !config.is_changed() {
+        return;
+    }
+
+    let text = if menu.open {
+        let rows = config.seats.iter().enumerate().flat_map(|(seat, setup)| {
+            [
+                format!("P{} profile: < {} >", seat + 1, setup.profile),
+                format!("P{} difficulty: < {} >", seat + 1, setup.difficulty),
+            ]
+        });
+        let mut lines = vec!["AI settings (S to close)".to_string()];
+        for (row, line) in rows.enumerate() {
+            let marker = if row == menu.selected_row { ">" } else { " " };
+            lines.push(format!("{} {}", marker, line));
+        }
+        lines.join("\n")
+    } else {
+        "S: AI settings   D: AI decisions".to_string()
+    };
+
+    for mut display in query.iter_mut() {
+        display.sections[0].value.clone_from(&text);
+    }
+}
+
+/// Spawns the AI decision panel text. Like the settings menu it lives
+/// across hands.
+pub fn spawn_decision_panel(mut commands: Commands, colors: Res<ColorPalette>) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                String::new(),
+                TextStyle {
+                    font_size: DECISION_PANEL_FONT_SIZE,
+                    color: colors.text_white,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_xyz(0.0, DECISION_PANEL_Y, DECISION_PANEL_Z_POSITION),
+            ..default()
+        },
+        DecisionPanelDisplay,
+    ));
+}
+
+/// D shows and hides the AI decision panel.
+pub fn handle_decision_panel_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut panel: ResMut<DecisionPanel>,
+) {
+    if keys.just_pressed(KeyCode::KeyD) {
+        panel.open = !panel.open;
+    }
+}
+
+/// Renders each seat's latest action and the explanation behind it.
+pub fn update_decision_panel(
+    panel: Res<DecisionPanel>,
+    game_state: Res<GameStateResource>,
+    agents: Res<SeatAgents>,
+    mut query: Query<&mut Text, With<DecisionPanelDisplay>>,
+) {
+    if !panel.is_changed() && !game_state.is_changed() {
+        return;
+    }
+
+    let text = if panel.open {
+        decision_panel_text(&game_state, &agents)
+    } else {
+        String::new()
+    };
+    for mut display in query.iter_mut() {
+        display.sections[0].value.clone_from(&text);
+    }
+}
+
+fn decision_panel_text(game_state: &GameStateResource, agents: &SeatAgents) -> String {
+    let mut lines = vec!["AI decisions (D to close)".to_string()];
+    for (seat, explanation) in game_state.last_explanations.iter().enumerate() {
+        let last_action = game_state
+            .action_history
+            .iter()
+            .rev()
+            .find(|record| record.seat == seat)
+            .map_or_else(|| "-".to_string(), |record| format!("{:?}", record.action));
+        lines.push(format!(
+            "P{} {}: {}",
+            seat + 1,
+            agents.seat(seat).name(),
+            last_action
+        ));
+        match explanation {
+            Some(explanation) => {
+                lines.extend(explanation.lines().iter().map(|l| format!("  {}", l)))
+            }
+            None => lines.push("  no decision yet".to_string()),
+        }
+    }
+    lines.join("\n")
+}